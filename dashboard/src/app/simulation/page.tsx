@@ -24,10 +24,24 @@ interface SimulationOrder {
     side: "buy" | "sell";
 }
 
+interface SideLadderReport {
+    levels_in: number;
+    levels_out: number;
+    merged: number;
+    dropped: number;
+    capped: number;
+}
+
+interface LadderReport {
+    buy: SideLadderReport;
+    sell: SideLadderReport;
+}
+
 interface SimulationResult {
     scenario_name: string;
     price_history: { timestamp: number; price: number }[];
     projected_grids: SimulationOrder[][];
+    ladder_reports: LadderReport[];
     total_buy_orders: number;
     total_sell_orders: number;
     price_range: { min: number; max: number };
@@ -276,6 +290,44 @@ export default function SimulationPage() {
                                         </div>
                                     </div>
 
+                                    {/* Ladder Fit - what the market's tick and lot sizes did to the last grid */}
+                                    {result.ladder_reports.length > 0 && (
+                                        <div className="bg-white/5 rounded-xl p-4">
+                                            <p className="text-[10px] font-black uppercase tracking-widest text-slate-500 mb-3">{t("ladderFit")}</p>
+                                            <table className="w-full text-sm">
+                                                <thead>
+                                                    <tr className="border-b border-white/5">
+                                                        <th className="text-left py-2 text-slate-500 font-medium">{t("side")}</th>
+                                                        <th className="text-right py-2 text-slate-500 font-medium">{t("levelsIn")}</th>
+                                                        <th className="text-right py-2 text-slate-500 font-medium">{t("levelsOut")}</th>
+                                                        <th className="text-right py-2 text-slate-500 font-medium">{t("mergedLevels")}</th>
+                                                        <th className="text-right py-2 text-slate-500 font-medium">{t("droppedDust")}</th>
+                                                        <th className="text-right py-2 text-slate-500 font-medium">{t("cappedLevels")}</th>
+                                                    </tr>
+                                                </thead>
+                                                <tbody>
+                                                    {(["buy", "sell"] as const).map((side) => {
+                                                        const report = result.ladder_reports[result.ladder_reports.length - 1][side];
+                                                        return (
+                                                            <tr key={side} className="border-b border-white/5">
+                                                                <td className="py-2">
+                                                                    <span className={`px-2 py-0.5 rounded text-[10px] font-black uppercase ${side === 'buy' ? 'bg-green-500/20 text-green-400' : 'bg-red-500/20 text-red-400'}`}>
+                                                                        {side === 'buy' ? t("buyOrders") : t("sellOrders")}
+                                                                    </span>
+                                                                </td>
+                                                                <td className="text-right py-2 font-mono text-slate-300">{report.levels_in}</td>
+                                                                <td className="text-right py-2 font-mono text-slate-300">{report.levels_out}</td>
+                                                                <td className="text-right py-2 font-mono text-slate-300">{report.merged}</td>
+                                                                <td className="text-right py-2 font-mono text-slate-300">{report.dropped}</td>
+                                                                <td className="text-right py-2 font-mono text-slate-300">{report.capped}</td>
+                                                            </tr>
+                                                        );
+                                                    })}
+                                                </tbody>
+                                            </table>
+                                        </div>
+                                    )}
+
                                     {/* Orders Table - Using last grid of simulation */}
                                     <div>
                                         <p className="text-[10px] font-black uppercase tracking-widest text-slate-500 mb-3">{t("projectedOrders")} ({result.projected_grids[result.projected_grids.length - 1]?.length || 0})</p>
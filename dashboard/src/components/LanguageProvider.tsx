@@ -146,6 +146,12 @@ type TranslationKey =
   | "simulationEngineActive"
   | "configureAndRun"
   | "moreOrders"
+  | "ladderFit"
+  | "levelsIn"
+  | "levelsOut"
+  | "mergedLevels"
+  | "droppedDust"
+  | "cappedLevels"
   | "side"
   | "price"
   | "step"
@@ -294,6 +300,12 @@ const translations: Record<Language, Record<TranslationKey, string>> = {
     simulationEngineActive: "Движок симуляции активен",
     configureAndRun: "Настройте и запустите симуляцию для просмотра результатов",
     moreOrders: "+ {count} еще ордеров",
+    ladderFit: "Подгонка сетки под рынок",
+    levelsIn: "Уровней до",
+    levelsOut: "Уровней после",
+    mergedLevels: "Объединено",
+    droppedDust: "Отброшено (пыль)",
+    cappedLevels: "Урезано лимитом",
     side: "Сторона",
     price: "Цена",
     step: "Шаг",
@@ -441,6 +453,12 @@ const translations: Record<Language, Record<TranslationKey, string>> = {
     simulationEngineActive: "Simulation Engine Active",
     configureAndRun: "Configure and run a simulation to see results",
     moreOrders: "+ {count} more orders",
+    ladderFit: "Ladder Fit",
+    levelsIn: "Levels In",
+    levelsOut: "Levels Out",
+    mergedLevels: "Merged",
+    droppedDust: "Dropped Dust",
+    cappedLevels: "Capped",
     side: "Side",
     price: "Price",
     step: "Step",
@@ -1,14 +1,23 @@
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderSide {
     Buy,
     Sell,
 }
 
+impl OrderSide {
+    pub fn opposite(self) -> Self {
+        match self {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        }
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -27,7 +36,7 @@ pub struct AssetPosition {
     pub notional_usd: Decimal,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GridLevel {
     pub price: Decimal,
     pub size: Decimal,
@@ -50,14 +59,106 @@ impl Default for RiskLimits {
     }
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Cross rate between two currencies: `price` units of the quote per one unit of the base.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FiatQuote {
+    pub timestamp: i64,
     pub provider: String,
-    pub pair: String, // e.g., "USD/SOL"
+    pub pair: String, // e.g., "SOL/USD"
     pub price: Decimal,
 }
 
+impl FiatQuote {
+    pub const SOL_USD: &'static str = "SOL/USD";
+    pub const USD_EUR: &'static str = "USD/EUR";
+}
+
+/// Currency that PnL, stats and reports are expressed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum FiatCurrency {
+    Usd,
+    Eur,
+}
+
+impl FiatCurrency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FiatCurrency::Usd => "USD",
+            FiatCurrency::Eur => "EUR",
+        }
+    }
+}
+
+impl std::str::FromStr for FiatCurrency {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "USD" => Ok(FiatCurrency::Usd),
+            "EUR" => Ok(FiatCurrency::Eur),
+            _ => Err(anyhow::anyhow!("Unsupported fiat currency '{}'", s)),
+        }
+    }
+}
+
+/// Kind of operational cost booked in the cost ledger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostKind {
+    JitoTip,
+    NetworkFee,
+    PriorityFee,
+    RentPaid,
+    RentReclaimed,
+    SwapSlippage,
+}
+
+impl CostKind {
+    pub const ALL: [CostKind; 6] = [
+        CostKind::JitoTip,
+        CostKind::NetworkFee,
+        CostKind::PriorityFee,
+        CostKind::RentPaid,
+        CostKind::RentReclaimed,
+        CostKind::SwapSlippage,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostKind::JitoTip => "jito_tip",
+            CostKind::NetworkFee => "network_fee",
+            CostKind::PriorityFee => "priority_fee",
+            CostKind::RentPaid => "rent_paid",
+            CostKind::RentReclaimed => "rent_reclaimed",
+            CostKind::SwapSlippage => "swap_slippage",
+        }
+    }
+}
+
+impl std::str::FromStr for CostKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CostKind::ALL
+            .into_iter()
+            .find(|k| k.as_str() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown cost kind '{}'", s))
+    }
+}
+
+/// One line of the cost ledger. `amount_sol` is what the swarm paid; reclaimed rent and
+/// favourable slippage are booked as negative amounts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostEntry {
+    pub timestamp: i64,
+    /// Transaction or bundle the cost belongs to, when one was returned.
+    pub signature: Option<String>,
+    pub kind: CostKind,
+    pub wallet: String,
+    pub amount_sol: Decimal,
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletSnapshot {
@@ -69,11 +170,43 @@ pub struct WalletSnapshot {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trade {
     pub id: String,
+    /// Block time when known, otherwise the time the trade was ingested.
     pub timestamp: i64,
     pub price: Decimal,
     pub volume: Decimal,
     pub side: OrderSide,
     pub wallet: String,
+    #[serde(default)]
+    pub slot: Option<u64>,
+    #[serde(default)]
+    pub block_time: Option<i64>,
+    /// Maker and taker accounts of the fill, when the event carried them.
+    #[serde(default)]
+    pub maker: Option<String>,
+    #[serde(default)]
+    pub taker: Option<String>,
+}
+
+impl Trade {
+    /// Both sides of the fill belong to `accounts`, i.e. the swarm traded with itself.
+    pub fn is_internal(&self, accounts: &HashSet<String>) -> bool {
+        match (&self.maker, &self.taker) {
+            (Some(maker), Some(taker)) => accounts.contains(maker) && accounts.contains(taker),
+            _ => false,
+        }
+    }
+
+    /// Side the swarm took in the fill; `side` is the taker's, so a swarm maker took the
+    /// opposite. `None` when the swarm was not part of the fill or was on both sides of it.
+    pub fn swarm_side(&self, accounts: &HashSet<String>) -> Option<OrderSide> {
+        let is_swarm =
+            |account: &Option<String>| account.as_ref().is_some_and(|a| accounts.contains(a));
+        match (is_swarm(&self.maker), is_swarm(&self.taker)) {
+            (false, true) => Some(self.side),
+            (true, false) => Some(self.side.opposite()),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,12 +214,42 @@ pub struct MarketUpdate {
     pub timestamp: i64,
     pub price: Decimal,
     pub volume_24h: Decimal,
+    #[serde(default)]
+    pub trade_count_24h: u64,
+}
+
+/// Traded base volume and number of fills since a point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradeVolume {
+    pub volume: Decimal,
+    pub trade_count: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderbookLevel {
     pub price: Decimal,
     pub size: Decimal,
+    /// Part of `size` resting in orders owned by our swarm wallets.
+    #[serde(default)]
+    pub own_size: Decimal,
+    /// Number of resting orders aggregated into this level.
+    #[serde(default)]
+    pub order_count: u32,
+}
+
+/// A single resting order decoded from a book side leaf node (L3 view).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderbookOrder {
+    pub order_id: u128,
+    /// Owner recorded in the leaf (the OpenOrders account on OpenBook).
+    pub owner: String,
+    pub client_order_id: u64,
+    /// Placement time in unix seconds; V1 leaves do not carry one.
+    pub timestamp: Option<u64>,
+    pub price: Decimal,
+    pub size: Decimal,
+    #[serde(default)]
+    pub is_own: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,14 +258,93 @@ pub struct Orderbook {
     pub timestamp: i64,
     pub bids: Vec<OrderbookLevel>,
     pub asks: Vec<OrderbookLevel>,
+    #[serde(default)]
+    pub bid_orders: Vec<OrderbookOrder>,
+    #[serde(default)]
+    pub ask_orders: Vec<OrderbookOrder>,
 }
 
 impl Orderbook {
+    pub fn from_orders(
+        market_id: &str,
+        timestamp: i64,
+        bid_orders: Vec<OrderbookOrder>,
+        ask_orders: Vec<OrderbookOrder>,
+    ) -> Self {
+        Self {
+            market_id: market_id.to_string(),
+            timestamp,
+            bids: levels_from_orders(&bid_orders),
+            asks: levels_from_orders(&ask_orders),
+            bid_orders,
+            ask_orders,
+        }
+    }
+
     pub fn get_mid_price(&self) -> Option<Decimal> {
         let best_bid = self.bids.first()?.price;
         let best_ask = self.asks.first()?.price;
         Some((best_bid + best_ask) / Decimal::from(2))
     }
+
+    /// Flags orders whose owner is in `owners` and rebuilds the L2 view accordingly.
+    pub fn mark_own_orders(&mut self, owners: &HashSet<String>) {
+        for order in self.bid_orders.iter_mut().chain(self.ask_orders.iter_mut()) {
+            order.is_own = owners.contains(&order.owner);
+        }
+        self.bids = levels_from_orders(&self.bid_orders);
+        self.asks = levels_from_orders(&self.ask_orders);
+    }
+
+    /// Bid depth with our own orders removed.
+    pub fn external_bids(&self) -> Vec<OrderbookLevel> {
+        external_levels(&self.bids)
+    }
+
+    /// Ask depth with our own orders removed.
+    pub fn external_asks(&self) -> Vec<OrderbookLevel> {
+        external_levels(&self.asks)
+    }
+}
+
+/// Aggregates orders into one level per price, keeping the order they are given in
+/// (best price first as produced by the book parsers).
+fn levels_from_orders(orders: &[OrderbookOrder]) -> Vec<OrderbookLevel> {
+    let mut levels: Vec<OrderbookLevel> = Vec::new();
+    let mut index_by_price: HashMap<Decimal, usize> = HashMap::new();
+
+    for order in orders {
+        let idx = *index_by_price.entry(order.price).or_insert_with(|| {
+            levels.push(OrderbookLevel {
+                price: order.price,
+                size: Decimal::ZERO,
+                own_size: Decimal::ZERO,
+                order_count: 0,
+            });
+            levels.len() - 1
+        });
+        let level = &mut levels[idx];
+        level.size += order.size;
+        level.order_count += 1;
+        if order.is_own {
+            level.own_size += order.size;
+        }
+    }
+
+    levels
+}
+
+fn external_levels(levels: &[OrderbookLevel]) -> Vec<OrderbookLevel> {
+    levels
+        .iter()
+        .filter(|l| l.size > l.own_size)
+        .map(|l| OrderbookLevel {
+            price: l.price,
+            size: l.size - l.own_size,
+            own_size: Decimal::ZERO,
+            order_count: l.order_count,
+        })
+        .collect()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,6 +352,99 @@ pub struct PriceTick {
     pub timestamp: i64,
     pub asset_price: Decimal,
     pub sol_price: Decimal,
+    /// Where the prices came from; ticks are unique per (timestamp, source).
+    #[serde(default)]
+    pub source: String,
+}
+
+impl PriceTick {
+    /// Recorded by the trading loop from the aggregated prices.
+    pub const TRADING_LOOP: &'static str = "trading_loop";
+    /// Startup SOL/USD history; its asset price is a placeholder, not an observation.
+    pub const BINANCE_BACKFILL: &'static str = "binance_backfill";
+    /// Recorded by the trading loop while the price guard holds the last good asset price.
+    pub const TRADING_LOOP_HELD: &'static str = "trading_loop_held";
+
+    /// Whether `asset_price` was observed on the market rather than filled in.
+    pub fn has_live_asset_price(&self) -> bool {
+        self.source != Self::BINANCE_BACKFILL && self.source != Self::TRADING_LOOP_HELD
+    }
+}
+
+/// A single price observation from one source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceQuote {
+    pub price: Decimal,
+    /// When the price was published, or fetched if the source carries no timestamp.
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum CandleInterval {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 4] = [
+        CandleInterval::OneMinute,
+        CandleInterval::FiveMinutes,
+        CandleInterval::OneHour,
+        CandleInterval::OneDay,
+    ];
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            CandleInterval::OneMinute => 60,
+            CandleInterval::FiveMinutes => 300,
+            CandleInterval::OneHour => 3600,
+            CandleInterval::OneDay => 86_400,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleInterval::OneMinute => "1m",
+            CandleInterval::FiveMinutes => "5m",
+            CandleInterval::OneHour => "1h",
+            CandleInterval::OneDay => "1d",
+        }
+    }
+
+    /// Start of the bucket containing `timestamp`.
+    pub fn bucket_start(&self, timestamp: i64) -> i64 {
+        timestamp - timestamp.rem_euclid(self.seconds())
+    }
+}
+
+impl std::str::FromStr for CandleInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CandleInterval::ALL
+            .into_iter()
+            .find(|i| i.as_str() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown candle interval '{}'", s))
+    }
+}
+
+/// OHLCV bar; `trade_count` is 0 for bars built from mid-price ticks only.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Candle {
+    pub interval: CandleInterval,
+    pub start: i64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+    pub trade_count: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -120,6 +455,22 @@ pub struct LatencyTick {
     pub latency_ms: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub failed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionLogs {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub logs: Vec<String>,
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
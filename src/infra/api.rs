@@ -1,5 +1,12 @@
-use crate::infra::{Auth, DatabaseProvider, HealthChecker, SolanaProvider, WalletManager};
-use crate::services::{GridBuilder, PivotEngine, SimulationEngine};
+use crate::infra::openbook::BookLayout;
+use crate::infra::{
+    Auth, DatabaseProvider, HealthChecker, HttpClient, SolanaProvider, WalletManager,
+};
+use crate::services::{
+    ChannelWidthController, CostReport, FiatRates, GridBuilder, MarketConstraints, OrderbookFeed,
+    PivotEngine, SimulationEngine,
+};
+use crate::utils::settings::{GridSizing, GridSpacing};
 use crate::utils::BotSettings;
 use anyhow::Result;
 use argon2::{
@@ -30,10 +37,13 @@ struct ApiState {
     settings: Arc<RwLock<BotSettings>>,
     database: Arc<dyn DatabaseProvider>,
     solana: Arc<dyn SolanaProvider>,
+    http: Arc<HttpClient>,
     wallet_manager: Arc<WalletManager>,
     pivot_engine: Arc<PivotEngine>,
     auth: Arc<Auth>,
     simulation_engine: Arc<SimulationEngine>,
+    orderbook_feed: OrderbookFeed,
+    channel_widths: Arc<ChannelWidthController>,
 }
 
 pub struct ApiServer {
@@ -60,6 +70,16 @@ struct BotStats {
     resistance_90: rust_decimal::Decimal,
     bids: Vec<crate::domain::OrderbookLevel>,
     asks: Vec<crate::domain::OrderbookLevel>,
+    // Depth excluding the swarm's own orders
+    external_bid_depth: f64,
+    external_ask_depth: f64,
+    // Fiat reporting; rates are absent until the trading loop has recorded them
+    fiat_currency: crate::domain::FiatCurrency,
+    sol_fiat_rate: Option<rust_decimal::Decimal>,
+    total_value_fiat: Option<f64>,
+    // Rolling 24h activity from the trade store
+    volume_24h: rust_decimal::Decimal,
+    trade_count_24h: u64,
 }
 
 #[derive(Serialize)]
@@ -84,11 +104,29 @@ struct LoginRequest {
     password: String,
 }
 
+#[derive(Deserialize)]
+struct FiatQuery {
+    fiat: Option<String>,
+}
+
 #[derive(Deserialize)]
 struct RangeQuery {
     range: Option<String>,
 }
 
+#[derive(Deserialize)]
+struct PeriodQuery {
+    from: Option<i64>,
+    to: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct CandleQuery {
+    interval: Option<String>,
+    from: Option<i64>,
+    to: Option<i64>,
+}
+
 #[derive(Serialize)]
 struct LoginResponse {
     token: String,
@@ -116,23 +154,30 @@ struct TokenHoldersResponse {
 }
 
 impl ApiServer {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         settings: Arc<RwLock<BotSettings>>,
         database: Arc<dyn DatabaseProvider>,
         solana: Arc<dyn SolanaProvider>,
+        http: Arc<HttpClient>,
         wallet_manager: Arc<WalletManager>,
         pivot_engine: Arc<PivotEngine>,
         auth: Arc<Auth>,
+        orderbook_feed: OrderbookFeed,
+        channel_widths: Arc<ChannelWidthController>,
     ) -> Self {
         Self {
             state: ApiState {
                 settings,
                 database,
                 solana,
+                http,
                 wallet_manager,
                 pivot_engine,
                 auth,
                 simulation_engine: Arc::new(SimulationEngine::new(GridBuilder::default())),
+                orderbook_feed,
+                channel_widths,
             },
         }
     }
@@ -162,6 +207,8 @@ impl ApiServer {
                 Router::new()
                     .route("/stats", get(handle_stats))
                     .route("/history", get(handle_history))
+                    .route("/candles", get(handle_candles))
+                    .route("/costs", get(handle_costs))
                     .route("/latency", get(handle_latency))
                     .route("/holders", get(handle_holders))
                     .route("/wallets", get(handle_list_wallets))
@@ -188,21 +235,31 @@ impl ApiServer {
 
 async fn handle_health(State(state): State<ApiState>) -> Json<serde_json::Value> {
     let settings = state.settings.read().await;
-    let health_checker = HealthChecker::new(state.solana, state.database, settings.clone());
+    let health_checker =
+        HealthChecker::new(state.solana, state.database, state.http, settings.clone());
     drop(settings);
     let reports = health_checker.run_all_checks().await;
     Json(serde_json::to_value(reports).unwrap_or_default())
 }
 
-async fn handle_stats(State(state): State<ApiState>) -> Json<BotStats> {
+async fn handle_stats(
+    State(state): State<ApiState>,
+    axum::extract::Query(query): axum::extract::Query<FiatQuery>,
+) -> Json<BotStats> {
     info!("GET /api/stats - Starting data aggregation");
     let wallets = state.wallet_manager.get_all_wallets().await;
 
     let settings = state.settings.read().await;
+    let fiat_currency = query
+        .fiat
+        .as_deref()
+        .and_then(|f| f.parse().ok())
+        .unwrap_or(settings.fiat.reporting_currency);
     let usdc_mint = Pubkey::from_str(&settings.wallets.usdc_wallet_3).unwrap_or_default();
     let market_id = settings.openbook_market_id.clone();
     let token_mint_str = settings.token_mint.clone();
-    let channel_bounds = settings.channel_bounds.clone();
+    // The widths the grid is built with, adapted to volatility when that is enabled
+    let channel_widths = state.channel_widths.current(&settings.channel_bounds);
     drop(settings);
 
     let mut total_sol = 0.0;
@@ -242,29 +299,35 @@ async fn handle_stats(State(state): State<ApiState>) -> Json<BotStats> {
         total_usdc += usdc;
     }
 
-    // 1. Orderbook Metrics (V1) - with timeout
-    let orderbook = match tokio::time::timeout(
-        std::time::Duration::from_secs(2),
-        state.solana.get_orderbook(&market_id),
-    )
-    .await
-    {
-        Ok(result) => {
-            if result.is_err() {
-                warn!(market_id = %market_id, "Failed to fetch orderbook");
-            }
-            result.ok()
-        }
-        Err(_) => {
-            warn!(market_id = %market_id, "Orderbook fetch timed out");
-            None
-        }
-    };
+    // 1. Orderbook Metrics - local book first, RPC with timeout while it syncs
+    let orderbook =
+        match crate::services::orderbook_service::latest_orderbook(&state.orderbook_feed) {
+            Some(ob) => Some(ob),
+            None => match tokio::time::timeout(
+                std::time::Duration::from_secs(2),
+                state.solana.get_orderbook(&market_id),
+            )
+            .await
+            {
+                Ok(result) => {
+                    if result.is_err() {
+                        warn!(market_id = %market_id, "Failed to fetch orderbook");
+                    }
+                    result.ok()
+                }
+                Err(_) => {
+                    warn!(market_id = %market_id, "Orderbook fetch timed out");
+                    None
+                }
+            },
+        };
 
     let mut spread_bps = 0.0;
     let mut imbalance_index = 0.0;
     let mut bids = Vec::new();
     let mut asks = Vec::new();
+    let mut external_bid_depth = 0.0;
+    let mut external_ask_depth = 0.0;
 
     let mut support_50 = rust_decimal::Decimal::ZERO;
     let mut support_90 = rust_decimal::Decimal::ZERO;
@@ -272,6 +335,11 @@ async fn handle_stats(State(state): State<ApiState>) -> Json<BotStats> {
     let mut resistance_90 = rust_decimal::Decimal::ZERO;
 
     if let Some(ob) = orderbook {
+        let depth = |levels: Vec<crate::domain::OrderbookLevel>| -> f64 {
+            levels.iter().map(|l| l.size.to_f64().unwrap_or(0.0)).sum()
+        };
+        external_bid_depth = depth(ob.external_bids());
+        external_ask_depth = depth(ob.external_asks());
         bids = ob.bids;
         asks = ob.asks;
 
@@ -376,10 +444,38 @@ async fn handle_stats(State(state): State<ApiState>) -> Json<BotStats> {
         }
     }
 
+    // 4. Rolling 24h volume and fill count
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let rolling = state
+        .database
+        .get_trade_volume(now - 86400)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to load 24h trade volume");
+            Default::default()
+        });
+
+    // 5. Fiat valuation of the swarm's SOL and USDC
+    let fiat_rates = FiatRates::load(state.database.as_ref())
+        .await
+        .unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to load fiat rates");
+            Default::default()
+        });
+    let sol_fiat_rate = fiat_rates.sol_to(fiat_currency);
+    let total_value_fiat = sol_fiat_rate
+        .zip(fiat_rates.usd_to(fiat_currency))
+        .and_then(|(sol_rate, usd_rate)| {
+            Some(total_sol * sol_rate.to_f64()? + total_usdc * usd_rate.to_f64()?)
+        });
+
     let res = Json(BotStats {
         pivot_price: state.pivot_engine.get_last_pivot().await,
-        buy_channel_width: channel_bounds.buy_percent,
-        sell_channel_width: channel_bounds.sell_percent,
+        buy_channel_width: channel_widths.buy,
+        sell_channel_width: channel_widths.sell,
         active_wallets: wallets.len(),
         kill_switch_active: false,
         total_sol_balance: total_sol,
@@ -394,6 +490,13 @@ async fn handle_stats(State(state): State<ApiState>) -> Json<BotStats> {
         resistance_90,
         bids: bids.into_iter().take(20).collect(), // Send top 20 for depth chart
         asks: asks.into_iter().take(20).collect(),
+        external_bid_depth,
+        external_ask_depth,
+        fiat_currency,
+        sol_fiat_rate,
+        total_value_fiat,
+        volume_24h: rolling.volume,
+        trade_count_24h: rolling.trade_count,
     });
     info!("GET /api/stats - Aggregation complete");
     res
@@ -483,6 +586,63 @@ async fn handle_history(
     Json(history)
 }
 
+async fn handle_candles(
+    State(state): State<ApiState>,
+    axum::extract::Query(query): axum::extract::Query<CandleQuery>,
+) -> Result<Json<Vec<crate::domain::Candle>>, StatusCode> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+
+    let interval = query
+        .interval
+        .as_deref()
+        .unwrap_or("1h")
+        .parse::<crate::domain::CandleInterval>()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    let to = query.to.unwrap_or(now);
+    let from = query.from.unwrap_or(to - 86400);
+    if from > to {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let candles = state
+        .database
+        .get_candles(interval, from, to)
+        .await
+        .map_err(|e| {
+            error!(error = ?e, "Failed to load candles");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(candles))
+}
+
+async fn handle_costs(
+    State(state): State<ApiState>,
+    axum::extract::Query(query): axum::extract::Query<PeriodQuery>,
+) -> Result<Json<CostReport>, StatusCode> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+
+    let to = query.to.unwrap_or(now);
+    let from = query.from.unwrap_or(to - 86400);
+    if from > to {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let accounts = state.pivot_engine.swarm_accounts().await;
+    let report = CostReport::load(state.database.as_ref(), from, to, &accounts)
+        .await
+        .map_err(|e| {
+            error!(error = ?e, "Failed to build cost report");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(report))
+}
+
 async fn handle_latency(State(state): State<ApiState>) -> Json<serde_json::Value> {
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -568,23 +728,76 @@ struct SimulationRequest {
     base_price: rust_decimal::Decimal,
     steps: usize,
     volatility: rust_decimal::Decimal,
+    /// Ladder overrides previewed on top of the configured grid
+    #[serde(default)]
+    orders_per_side: Option<u32>,
+    #[serde(default)]
+    spacing: Option<GridSpacing>,
+    #[serde(default)]
+    sizing: Option<GridSizing>,
 }
 
 async fn handle_simulation(
     State(state): State<ApiState>,
     Json(payload): Json<SimulationRequest>,
-) -> Json<crate::services::SimulationResult> {
+) -> Result<Json<crate::services::SimulationResult>, (StatusCode, String)> {
     info!(scenario = ?payload.scenario, "Running simulation");
+    let (mut grid_builder, market_id) = {
+        let settings = state.settings.read().await;
+        (
+            GridBuilder::from_settings(&settings.order_grid, &settings.channel_bounds),
+            settings.openbook_market_id.clone(),
+        )
+    };
+    if let Some(orders_per_side) = payload.orders_per_side {
+        grid_builder.orders_per_side = orders_per_side;
+    }
+    if let Some(spacing) = payload.spacing {
+        grid_builder.spacing = spacing;
+    }
+    if let Some(sizing) = payload.sizing {
+        grid_builder.sizing = sizing;
+    }
+    // Also bounds the ladder size, before anything is allocated for it
+    grid_builder
+        .validate()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    // Preview the ladder the market would accept; raw levels if the market can't be read
+    grid_builder.constraints = match market_constraints(&state, &market_id).await {
+        Ok(constraints) => constraints,
+        Err(e) => {
+            warn!(market_id = %market_id, error = %e, "simulation_market_constraints_unavailable");
+            None
+        }
+    };
+
     let result = state
         .simulation_engine
-        .run_simulation(
+        .run_simulation_with(
+            &grid_builder,
             payload.scenario,
             payload.base_price,
             payload.steps,
             payload.volatility,
         )
         .await;
-    Json(result)
+    Ok(Json(result))
+}
+
+async fn market_constraints(
+    state: &ApiState,
+    market_id: &str,
+) -> Result<Option<MarketConstraints>> {
+    let market = Pubkey::from_str(market_id)?;
+    let data = tokio::time::timeout(
+        std::time::Duration::from_secs(2),
+        state.solana.get_account_data(&market),
+    )
+    .await??;
+    Ok(MarketConstraints::from_layout(
+        &BookLayout::from_market_data(&data)?,
+    ))
 }
 
 async fn handle_holders(State(state): State<ApiState>) -> Json<TokenHoldersResponse> {
@@ -1,9 +1,10 @@
-use crate::domain::{OrderSide, Trade};
+use crate::domain::{Candle, CandleInterval, OrderSide, Trade};
 use anyhow::Result;
 use metrics::counter;
 use rust_decimal::Decimal;
 use sqlx::postgres::PgPool;
 use std::str::FromStr;
+use tracing::warn;
 
 #[allow(dead_code)]
 pub struct Database {
@@ -28,45 +29,40 @@ impl crate::infra::DatabaseProvider for Database {
         self.save_trade_impl(trade).await
     }
 
-    async fn save_price_tick(&self, asset_price: Decimal, sol_price: Decimal) -> Result<()> {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs() as i64;
-
-        sqlx::query(
-            "INSERT INTO price_history (timestamp, asset_price, sol_price)
-            VALUES ($1, $2, $3)
-            ON CONFLICT (timestamp) DO NOTHING",
+    async fn get_trade_volume(&self, since_ts: i64) -> Result<crate::domain::TradeVolume> {
+        // Served from idx_trades_history_timestamp; the sum is done in NUMERIC to stay exact
+        let (trade_count, volume): (i64, String) = sqlx::query_as(
+            "SELECT COUNT(*), COALESCE(SUM(CAST(volume AS NUMERIC)), 0)::TEXT
+             FROM trades_history WHERE timestamp >= $1",
         )
-        .bind(timestamp)
-        .bind(asset_price.to_string())
-        .bind(sol_price.to_string())
-        .execute(&self.pool)
+        .bind(since_ts)
+        .fetch_one(&self.pool)
         .await?;
 
-        Ok(())
+        Ok(crate::domain::TradeVolume {
+            volume: Decimal::from_str(&volume).unwrap_or_default(),
+            trade_count: trade_count as u64,
+        })
     }
 
-    async fn save_historical_price_ticks(&self, ticks: Vec<(i64, Decimal, Decimal)>) -> Result<()> {
-        for (ts, asset_price, sol_price) in ticks {
-            sqlx::query(
-                "INSERT INTO price_history (timestamp, asset_price, sol_price)
-                VALUES ($1, $2, $3)
-                ON CONFLICT (timestamp) DO NOTHING",
-            )
-            .bind(ts)
-            .bind(asset_price.to_string())
-            .bind(sol_price.to_string())
-            .execute(&self.pool)
-            .await?;
+    async fn save_price_tick(&self, tick: &crate::domain::PriceTick) -> Result<()> {
+        self.save_price_tick_impl(tick).await
+    }
+
+    async fn save_historical_price_ticks(
+        &self,
+        ticks: Vec<crate::domain::PriceTick>,
+    ) -> Result<()> {
+        for tick in &ticks {
+            self.save_price_tick_impl(tick).await?;
         }
         Ok(())
     }
 
     async fn get_price_history(&self, since_ts: i64) -> Result<Vec<crate::domain::PriceTick>> {
-        let rows: Vec<(i64, String, String)> = sqlx::query_as(
-            "SELECT timestamp, asset_price, sol_price FROM price_history 
-             WHERE timestamp >= $1 ORDER BY timestamp ASC",
+        let rows: Vec<(i64, String, String, String)> = sqlx::query_as(
+            "SELECT timestamp, asset_price, sol_price, source FROM price_history 
+             WHERE timestamp >= $1 ORDER BY timestamp ASC, source ASC",
         )
         .bind(since_ts)
         .fetch_all(&self.pool)
@@ -78,12 +74,95 @@ impl crate::infra::DatabaseProvider for Database {
                 timestamp: r.0,
                 asset_price: Decimal::from_str(&r.1).unwrap_or_default(),
                 sol_price: Decimal::from_str(&r.2).unwrap_or_default(),
+                source: r.3,
             })
             .collect();
 
         Ok(ticks)
     }
 
+    async fn save_fiat_quote(&self, quote: &crate::domain::FiatQuote) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO fiat_rates (timestamp, pair, provider, price)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (timestamp, pair, provider) DO UPDATE SET price = EXCLUDED.price",
+        )
+        .bind(quote.timestamp)
+        .bind(&quote.pair)
+        .bind(&quote.provider)
+        .bind(quote.price.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_latest_fiat_quote(&self, pair: &str) -> Result<Option<crate::domain::FiatQuote>> {
+        let row: Option<(i64, String, String, String)> = sqlx::query_as(
+            "SELECT timestamp, pair, provider, price FROM fiat_rates
+             WHERE pair = $1 ORDER BY timestamp DESC LIMIT 1",
+        )
+        .bind(pair)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| crate::domain::FiatQuote {
+            timestamp: r.0,
+            pair: r.1,
+            provider: r.2,
+            price: Decimal::from_str(&r.3).unwrap_or_default(),
+        }))
+    }
+
+    async fn save_cost_entries(&self, entries: &[crate::domain::CostEntry]) -> Result<()> {
+        for entry in entries {
+            sqlx::query(
+                "INSERT INTO cost_ledger (timestamp, signature, kind, wallet, amount_sol)
+                VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(entry.timestamp)
+            .bind(&entry.signature)
+            .bind(entry.kind.as_str())
+            .bind(&entry.wallet)
+            .bind(entry.amount_sol.to_string())
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn get_cost_entries(
+        &self,
+        from_ts: i64,
+        to_ts: i64,
+    ) -> Result<Vec<crate::domain::CostEntry>> {
+        let rows: Vec<(i64, Option<String>, String, String, String)> = sqlx::query_as(
+            "SELECT timestamp, signature, kind, wallet, amount_sol FROM cost_ledger
+             WHERE timestamp >= $1 AND timestamp <= $2 ORDER BY timestamp ASC, id ASC",
+        )
+        .bind(from_ts)
+        .bind(to_ts)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let entries = rows
+            .into_iter()
+            .filter_map(|r| {
+                let kind = crate::domain::CostKind::from_str(&r.2)
+                    .map_err(|e| warn!(error = %e, "cost_entry_unknown_kind"))
+                    .ok()?;
+                Some(crate::domain::CostEntry {
+                    timestamp: r.0,
+                    signature: r.1,
+                    kind,
+                    wallet: r.3,
+                    amount_sol: Decimal::from_str(&r.4).unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
     async fn save_latency_report(&self, report: &crate::infra::health::HealthReport) -> Result<()> {
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)?
@@ -149,6 +228,66 @@ impl crate::infra::DatabaseProvider for Database {
                 .await?;
         Ok(rows)
     }
+
+    async fn save_candles(&self, candles: &[Candle]) -> Result<()> {
+        for candle in candles {
+            sqlx::query(
+                "INSERT INTO candles (interval, start_ts, open, high, low, close, volume, trade_count)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (interval, start_ts) DO UPDATE SET
+                    open = EXCLUDED.open,
+                    high = EXCLUDED.high,
+                    low = EXCLUDED.low,
+                    close = EXCLUDED.close,
+                    volume = EXCLUDED.volume,
+                    trade_count = EXCLUDED.trade_count",
+            )
+            .bind(candle.interval.as_str())
+            .bind(candle.start)
+            .bind(candle.open.to_string())
+            .bind(candle.high.to_string())
+            .bind(candle.low.to_string())
+            .bind(candle.close.to_string())
+            .bind(candle.volume.to_string())
+            .bind(candle.trade_count as i64)
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn get_candles(
+        &self,
+        interval: CandleInterval,
+        from_ts: i64,
+        to_ts: i64,
+    ) -> Result<Vec<Candle>> {
+        let rows: Vec<(i64, String, String, String, String, String, i64)> = sqlx::query_as(
+            "SELECT start_ts, open, high, low, close, volume, trade_count FROM candles
+             WHERE interval = $1 AND start_ts >= $2 AND start_ts <= $3 ORDER BY start_ts ASC",
+        )
+        .bind(interval.as_str())
+        .bind(from_ts)
+        .bind(to_ts)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let candles = rows
+            .into_iter()
+            .map(|r| Candle {
+                interval,
+                start: r.0,
+                open: Decimal::from_str(&r.1).unwrap_or_default(),
+                high: Decimal::from_str(&r.2).unwrap_or_default(),
+                low: Decimal::from_str(&r.3).unwrap_or_default(),
+                close: Decimal::from_str(&r.4).unwrap_or_default(),
+                volume: Decimal::from_str(&r.5).unwrap_or_default(),
+                trade_count: r.6 as u32,
+            })
+            .collect();
+
+        Ok(candles)
+    }
 }
 
 #[allow(dead_code)]
@@ -174,22 +313,93 @@ impl Database {
                 price TEXT NOT NULL,
                 volume TEXT NOT NULL,
                 side TEXT NOT NULL,
-                wallet TEXT NOT NULL
+                wallet TEXT NOT NULL,
+                slot BIGINT,
+                block_time BIGINT,
+                maker TEXT,
+                taker TEXT
             )",
         )
         .execute(&pool)
         .await?;
 
+        sqlx::query("ALTER TABLE trades_history ADD COLUMN IF NOT EXISTS slot BIGINT")
+            .execute(&pool)
+            .await?;
+        sqlx::query("ALTER TABLE trades_history ADD COLUMN IF NOT EXISTS block_time BIGINT")
+            .execute(&pool)
+            .await?;
+        sqlx::query("ALTER TABLE trades_history ADD COLUMN IF NOT EXISTS maker TEXT")
+            .execute(&pool)
+            .await?;
+        sqlx::query("ALTER TABLE trades_history ADD COLUMN IF NOT EXISTS taker TEXT")
+            .execute(&pool)
+            .await?;
+
         sqlx::query(
             "CREATE TABLE IF NOT EXISTS price_history (
-                timestamp BIGINT PRIMARY KEY,
+                timestamp BIGINT NOT NULL,
                 asset_price TEXT NOT NULL,
-                sol_price TEXT NOT NULL
+                sol_price TEXT NOT NULL,
+                source TEXT NOT NULL DEFAULT 'legacy',
+                CONSTRAINT price_history_timestamp_source_pkey PRIMARY KEY (timestamp, source)
             )",
         )
         .execute(&pool)
         .await?;
 
+        // Older tables were keyed by timestamp alone, which dropped ticks landing in the same second
+        sqlx::query(
+            "ALTER TABLE price_history ADD COLUMN IF NOT EXISTS source TEXT NOT NULL DEFAULT 'legacy'",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "DO $$
+            BEGIN
+                IF NOT EXISTS (
+                    SELECT 1 FROM pg_constraint WHERE conname = 'price_history_timestamp_source_pkey'
+                ) THEN
+                    ALTER TABLE price_history DROP CONSTRAINT IF EXISTS price_history_pkey;
+                    ALTER TABLE price_history
+                        ADD CONSTRAINT price_history_timestamp_source_pkey PRIMARY KEY (timestamp, source);
+                END IF;
+            END $$",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS fiat_rates (
+                timestamp BIGINT NOT NULL,
+                pair TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                price TEXT NOT NULL,
+                PRIMARY KEY (timestamp, pair, provider)
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS cost_ledger (
+                id BIGSERIAL PRIMARY KEY,
+                timestamp BIGINT NOT NULL,
+                signature TEXT,
+                kind TEXT NOT NULL,
+                wallet TEXT NOT NULL,
+                amount_sol TEXT NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_cost_ledger_timestamp ON cost_ledger (timestamp)",
+        )
+        .execute(&pool)
+        .await?;
+
         sqlx::query(
             "CREATE TABLE IF NOT EXISTS latency_history (
                 id SERIAL PRIMARY KEY,
@@ -212,6 +422,22 @@ impl Database {
         .execute(&pool)
         .await?;
 
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS candles (
+                interval TEXT NOT NULL,
+                start_ts BIGINT NOT NULL,
+                open TEXT NOT NULL,
+                high TEXT NOT NULL,
+                low TEXT NOT NULL,
+                close TEXT NOT NULL,
+                volume TEXT NOT NULL,
+                trade_count BIGINT NOT NULL,
+                PRIMARY KEY (interval, start_ts)
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
         // Add index on service_name and timestamp for faster queries
         sqlx::query(
             "CREATE INDEX IF NOT EXISTS idx_latency_history_service_time ON latency_history (service_name, timestamp)",
@@ -276,8 +502,9 @@ impl Database {
         };
 
         sqlx::query(
-            "INSERT INTO trades_history (id, timestamp, price, volume, side, wallet)
-            VALUES ($1, $2, $3, $4, $5, $6)",
+            "INSERT INTO trades_history (id, timestamp, price, volume, side, wallet, slot, block_time, maker, taker)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (id) DO NOTHING",
         )
         .bind(&trade.id)
         .bind(trade.timestamp)
@@ -285,6 +512,10 @@ impl Database {
         .bind(trade.volume.to_string())
         .bind(side_str)
         .bind(&trade.wallet)
+        .bind(trade.slot.map(|s| s as i64))
+        .bind(trade.block_time)
+        .bind(&trade.maker)
+        .bind(&trade.taker)
         .execute(&self.pool)
         .await?;
 
@@ -294,8 +525,20 @@ impl Database {
     }
 
     pub async fn get_recent_trades_impl(&self, since_timestamp: i64) -> Result<Vec<Trade>> {
-        let rows: Vec<(String, i64, String, String, String, String)> = sqlx::query_as(
-            "SELECT id, timestamp, price, volume, side, wallet FROM trades_history WHERE timestamp >= $1 ORDER BY timestamp ASC, id ASC",
+        #[allow(clippy::type_complexity)]
+        let rows: Vec<(
+            String,
+            i64,
+            String,
+            String,
+            String,
+            String,
+            Option<i64>,
+            Option<i64>,
+            Option<String>,
+            Option<String>,
+        )> = sqlx::query_as(
+            "SELECT id, timestamp, price, volume, side, wallet, slot, block_time, maker, taker FROM trades_history WHERE timestamp >= $1 ORDER BY timestamp ASC, id ASC",
         )
         .bind(since_timestamp)
         .fetch_all(&self.pool)
@@ -314,12 +557,32 @@ impl Database {
                     OrderSide::Sell
                 },
                 wallet: row.5,
+                slot: row.6.map(|s| s as u64),
+                block_time: row.7,
+                maker: row.8,
+                taker: row.9,
             });
         }
 
         Ok(trades)
     }
 
+    pub async fn save_price_tick_impl(&self, tick: &crate::domain::PriceTick) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO price_history (timestamp, asset_price, sol_price, source)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (timestamp, source) DO NOTHING",
+        )
+        .bind(tick.timestamp)
+        .bind(tick.asset_price.to_string())
+        .bind(tick.sol_price.to_string())
+        .bind(&tick.source)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn close(&self) {
         self.pool.close().await;
     }
@@ -371,6 +634,10 @@ mod tests {
             volume: Decimal::from_str("100.0")?,
             side: OrderSide::Buy,
             wallet: "wallet_1".to_string(),
+            slot: Some(42),
+            block_time: Some(1000),
+            maker: Some("maker_oo".to_string()),
+            taker: None,
         };
 
         db.save_trade(&trade).await?;
@@ -379,10 +646,119 @@ mod tests {
         assert_eq!(trades.len(), 1);
         assert_eq!(trades[0].id, "trade_1");
         assert_eq!(trades[0].price, Decimal::from_str("1.23456789")?);
+        assert_eq!(trades[0].slot, Some(42));
+        assert_eq!(trades[0].maker.as_deref(), Some("maker_oo"));
 
         let trades_none = db.get_recent_trades_impl(1500).await?;
         assert_eq!(trades_none.len(), 0);
 
+        let since_1000 = db.get_trade_volume(1000).await?;
+        assert!(since_1000.trade_count >= 1);
+        assert!(since_1000.volume >= Decimal::from(100));
+        assert_eq!(
+            db.get_trade_volume(i64::MAX).await?,
+            crate::domain::TradeVolume::default()
+        );
+
+        db.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_price_ticks_keyed_by_source() -> Result<()> {
+        let db = match get_test_db().await {
+            Some(db) => db,
+            None => return Ok(()), // Skip if no test DB
+        };
+
+        let tick = |source: &str| crate::domain::PriceTick {
+            timestamp: 2000,
+            asset_price: Decimal::from_str("0.000011").unwrap(),
+            sol_price: Decimal::from(150),
+            source: source.to_string(),
+        };
+        db.save_price_tick(&tick("aggregator")).await?;
+        db.save_price_tick(&tick("binance")).await?;
+        db.save_price_tick(&tick("binance")).await?;
+
+        let ticks = db.get_price_history(2000).await?;
+        let sources: Vec<_> = ticks
+            .iter()
+            .filter(|t| t.timestamp == 2000)
+            .map(|t| t.source.as_str())
+            .collect();
+        assert_eq!(sources, vec!["aggregator", "binance"]);
+
+        db.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_latest_fiat_quote_per_pair() -> Result<()> {
+        let db = match get_test_db().await {
+            Some(db) => db,
+            None => return Ok(()), // Skip if no test DB
+        };
+
+        let quote = |timestamp: i64, pair: &str, price: &str| crate::domain::FiatQuote {
+            timestamp,
+            provider: "test".to_string(),
+            pair: pair.to_string(),
+            price: Decimal::from_str(price).unwrap(),
+        };
+        db.save_fiat_quote(&quote(3000, "TEST/USD", "1.10")).await?;
+        db.save_fiat_quote(&quote(3100, "TEST/USD", "1.20")).await?;
+        db.save_fiat_quote(&quote(3200, "TEST/EUR", "0.90")).await?;
+
+        let latest = db.get_latest_fiat_quote("TEST/USD").await?.unwrap();
+        assert_eq!(latest.timestamp, 3100);
+        assert_eq!(latest.price, Decimal::from_str("1.20")?);
+        assert!(db.get_latest_fiat_quote("TEST/GBP").await?.is_none());
+
+        db.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cost_entries_round_trip() -> Result<()> {
+        let db = match get_test_db().await {
+            Some(db) => db,
+            None => return Ok(()), // Skip if no test DB
+        };
+
+        // The ledger is append-only, so keep this run's rows apart from earlier ones
+        let wallet = format!(
+            "cost_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_nanos()
+        );
+        let entry = |timestamp: i64, kind: crate::domain::CostKind, amount: &str| {
+            crate::domain::CostEntry {
+                timestamp,
+                signature: Some("sig".to_string()),
+                kind,
+                wallet: wallet.clone(),
+                amount_sol: Decimal::from_str(amount).unwrap(),
+            }
+        };
+        db.save_cost_entries(&[
+            entry(4000, crate::domain::CostKind::JitoTip, "0.001"),
+            entry(4100, crate::domain::CostKind::RentReclaimed, "-0.023"),
+            entry(4200, crate::domain::CostKind::NetworkFee, "0.000005"),
+        ])
+        .await?;
+
+        let entries: Vec<_> = db
+            .get_cost_entries(4000, 4100)
+            .await?
+            .into_iter()
+            .filter(|e| e.wallet == wallet)
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].kind, crate::domain::CostKind::RentReclaimed);
+        assert_eq!(entries[1].amount_sol, Decimal::from_str("-0.023")?);
+
         db.close().await;
         Ok(())
     }
@@ -4,7 +4,8 @@ use std::sync::Arc;
 use std::time::Instant;
 use tracing::info;
 
-use crate::infra::{DatabaseProvider, SolanaProvider};
+use crate::infra::http_client::HttpStatusError;
+use crate::infra::{DatabaseProvider, HttpClient, SolanaProvider};
 use crate::utils::BotSettings;
 
 use serde::{Deserialize, Serialize};
@@ -39,6 +40,7 @@ pub struct HealthReport {
 pub struct HealthChecker {
     solana: Arc<dyn SolanaProvider>,
     database: Arc<dyn DatabaseProvider>,
+    http: Arc<HttpClient>,
     settings: BotSettings,
 }
 
@@ -46,11 +48,13 @@ impl HealthChecker {
     pub fn new(
         solana: Arc<dyn SolanaProvider>,
         database: Arc<dyn DatabaseProvider>,
+        http: Arc<HttpClient>,
         settings: BotSettings,
     ) -> Self {
         Self {
             solana,
             database,
+            http,
             settings,
         }
     }
@@ -110,7 +114,6 @@ impl HealthChecker {
         }
 
         let start = Instant::now();
-        let client = reqwest::Client::new();
         let payload = serde_json::json!({
             "jsonrpc": "2.0",
             "id": 1,
@@ -118,31 +121,32 @@ impl HealthChecker {
             "params": []
         });
 
-        let result = client
-            .post(&self.settings.jito_bundle.bundler_url)
-            .json(&payload)
-            .send()
+        let result = self
+            .http
+            .post(&self.settings.jito_bundle.bundler_url, &payload)
             .await;
         let latency = start.elapsed().as_millis();
 
         match result {
-            Ok(resp) if resp.status().is_success() => HealthReport {
+            Ok(_) => HealthReport {
                 service_name: "Jito Bundler".to_string(),
                 status: ServiceStatus::Healthy,
                 latency_ms: latency,
                 message: None,
             },
-            Ok(resp) => HealthReport {
-                service_name: "Jito Bundler".to_string(),
-                status: ServiceStatus::Degraded,
-                latency_ms: latency,
-                message: Some(format!("Status: {}", resp.status())),
-            },
-            Err(e) => HealthReport {
-                service_name: "Jito Bundler".to_string(),
-                status: ServiceStatus::Failed,
-                latency_ms: latency,
-                message: Some(format!("Connection failed: {}", e)),
+            Err(e) => match e.downcast_ref::<HttpStatusError>() {
+                Some(status_err) => HealthReport {
+                    service_name: "Jito Bundler".to_string(),
+                    status: ServiceStatus::Degraded,
+                    latency_ms: latency,
+                    message: Some(format!("Status: {}", status_err.status)),
+                },
+                None => HealthReport {
+                    service_name: "Jito Bundler".to_string(),
+                    status: ServiceStatus::Failed,
+                    latency_ms: latency,
+                    message: Some(format!("Connection failed: {}", e)),
+                },
             },
         }
     }
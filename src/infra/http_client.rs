@@ -0,0 +1,403 @@
+use crate::utils::settings::{ExternalApiUrls, HttpSettings};
+use anyhow::{anyhow, Result};
+use metrics::{counter, histogram};
+use rand_core::{OsRng, RngCore};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Price and metadata reads are throttled and tripped separately from swap and bundle
+/// submissions, so a burst of 429s from a quote API cannot block order flow to the same host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum RequestKind {
+    Query,
+    Transaction,
+}
+
+impl RequestKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Transaction => "transaction",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HostState {
+    next_slot: Instant,
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// The host answered with a non-success status.
+#[derive(Debug)]
+pub struct HttpStatusError {
+    pub host: String,
+    pub status: reqwest::StatusCode,
+}
+
+impl std::fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} returned status {}", self.host, self.status)
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+enum Attempt {
+    Done(reqwest::Response),
+    /// Worth retrying and counts against the host's circuit (429, 5xx, timeouts).
+    Retryable(anyhow::Error),
+    /// The host answered; retrying will not help.
+    Fatal(anyhow::Error),
+}
+
+/// Shared client for all outbound calls to external HTTP APIs. Applies a per-host rate
+/// limit and circuit breaker, retries idempotent requests with jittered backoff and keeps
+/// GET responses for a short time so several callers in the same tick hit the API once.
+pub struct HttpClient {
+    client: reqwest::Client,
+    settings: HttpSettings,
+    hosts: Mutex<HashMap<(String, RequestKind), HostState>>,
+    cache: Mutex<HashMap<String, (Instant, serde_json::Value)>>,
+}
+
+impl HttpClient {
+    pub fn new(settings: &HttpSettings) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(settings.timeout_ms))
+            .build()
+            .unwrap_or_else(|e| {
+                warn!(error = %e, "http_client_build_failed");
+                reqwest::Client::new()
+            });
+        Self {
+            client,
+            settings: settings.clone(),
+            hosts: Mutex::new(HashMap::new()),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn urls(&self) -> &ExternalApiUrls {
+        &self.settings.base_urls
+    }
+
+    /// GET a JSON document; cached for `cache_ttl_ms` and retried on transient failures.
+    pub async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        if let Some(value) = self.cached(url) {
+            counter!("bot_http_cache_hits_total", 1, "host" => host_of(url));
+            return Ok(serde_json::from_value(value)?);
+        }
+
+        let mut attempt = 0;
+        let value: serde_json::Value = loop {
+            let err = match self
+                .execute(url, RequestKind::Query, || self.client.get(url))
+                .await
+            {
+                Attempt::Done(resp) => break resp.json().await?,
+                Attempt::Fatal(e) => return Err(e),
+                Attempt::Retryable(e) => e,
+            };
+            if attempt >= self.settings.max_retries {
+                return Err(err);
+            }
+            let delay = self.backoff(attempt);
+            debug!(url, attempt, delay_ms = delay.as_millis() as u64, error = %err, "http_retry");
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        };
+
+        if self.settings.cache_ttl_ms > 0 {
+            let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            cache.retain(|_, (expires, _)| *expires > now);
+            cache.insert(
+                url.to_string(),
+                (
+                    now + Duration::from_millis(self.settings.cache_ttl_ms),
+                    value.clone(),
+                ),
+            );
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// POST a JSON body and parse the JSON reply. Never retried: the request may not be
+    /// idempotent (swaps, bundles).
+    pub async fn post_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        body: &serde_json::Value,
+    ) -> Result<T> {
+        Ok(self.post(url, body).await?.json().await?)
+    }
+
+    /// POST a JSON body and return the successful response as-is.
+    pub async fn post(&self, url: &str, body: &serde_json::Value) -> Result<reqwest::Response> {
+        match self
+            .execute(url, RequestKind::Transaction, || {
+                self.client.post(url).json(body)
+            })
+            .await
+        {
+            Attempt::Done(resp) => Ok(resp),
+            Attempt::Retryable(e) | Attempt::Fatal(e) => Err(e),
+        }
+    }
+
+    async fn execute<F>(&self, url: &str, kind: RequestKind, build: F) -> Attempt
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        let host = host_of(url);
+        let wait = match self.acquire(&host, kind) {
+            Ok(wait) => wait,
+            Err(e) => {
+                counter!("bot_http_requests_total", 1, "host" => host, "kind" => kind.as_str(), "outcome" => "circuit_open");
+                return Attempt::Fatal(e);
+            }
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+
+        let start = Instant::now();
+        let result = build().send().await;
+        histogram!("bot_http_request_duration_seconds", start.elapsed().as_secs_f64(), "host" => host.clone());
+
+        let attempt = match result {
+            Ok(resp) if resp.status().is_success() => Attempt::Done(resp),
+            Ok(resp) => {
+                let status = resp.status();
+                let err = anyhow::Error::new(HttpStatusError {
+                    host: host.clone(),
+                    status,
+                });
+                if status.as_u16() == 429 || status.is_server_error() {
+                    Attempt::Retryable(err)
+                } else {
+                    Attempt::Fatal(err)
+                }
+            }
+            Err(e) if e.is_timeout() || e.is_connect() => {
+                Attempt::Retryable(anyhow!("{} request failed: {}", host, e))
+            }
+            Err(e) => Attempt::Fatal(anyhow!("{} request failed: {}", host, e)),
+        };
+
+        let outcome = match &attempt {
+            Attempt::Done(_) => "success",
+            Attempt::Retryable(_) => "retryable_error",
+            Attempt::Fatal(_) => "error",
+        };
+        counter!("bot_http_requests_total", 1, "host" => host.clone(), "kind" => kind.as_str(), "outcome" => outcome);
+        self.record(&host, kind, !matches!(attempt, Attempt::Retryable(_)));
+        attempt
+    }
+
+    /// Reserves the next rate-limit slot for `host` and returns how long to wait for it.
+    fn acquire(&self, host: &str, kind: RequestKind) -> Result<Duration> {
+        let now = Instant::now();
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        let state = hosts
+            .entry((host.to_string(), kind))
+            .or_insert_with(|| HostState {
+                next_slot: now,
+                consecutive_failures: 0,
+                open_until: None,
+            });
+
+        if let Some(open_until) = state.open_until {
+            if open_until > now {
+                return Err(anyhow!(
+                    "Circuit open for {} {} ({}s left)",
+                    host,
+                    kind.as_str(),
+                    (open_until - now).as_secs()
+                ));
+            }
+            // Half-open: let this request through, one more failure re-opens the circuit
+            state.open_until = None;
+        }
+
+        let rate = self
+            .settings
+            .host_rate_limits
+            .get(host)
+            .copied()
+            .unwrap_or(self.settings.default_rate_limit_per_sec);
+        let slot = state.next_slot.max(now);
+        if rate > 0.0 {
+            state.next_slot = slot + Duration::from_secs_f64(1.0 / rate);
+        }
+        Ok(slot - now)
+    }
+
+    fn record(&self, host: &str, kind: RequestKind, healthy: bool) {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = hosts.get_mut(&(host.to_string(), kind)) else {
+            return;
+        };
+        if healthy {
+            state.consecutive_failures = 0;
+            return;
+        }
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= self.settings.circuit_failure_threshold.max(1) {
+            state.open_until =
+                Some(Instant::now() + Duration::from_secs(self.settings.circuit_open_secs));
+            counter!("bot_http_circuit_opened_total", 1, "host" => host.to_string(), "kind" => kind.as_str());
+            warn!(
+                host,
+                kind = kind.as_str(),
+                failures = state.consecutive_failures,
+                open_secs = self.settings.circuit_open_secs,
+                "http_circuit_opened"
+            );
+        }
+    }
+
+    fn cached(&self, url: &str) -> Option<serde_json::Value> {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache
+            .get(url)
+            .filter(|(expires, _)| *expires > Instant::now())
+            .map(|(_, value)| value.clone())
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let base = self
+            .settings
+            .retry_base_delay_ms
+            .saturating_mul(1 << attempt.min(10));
+        let jitter = OsRng.next_u64() % (base / 2 + 1);
+        Duration::from_millis(base + jitter)
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(&HttpSettings::default())
+    }
+}
+
+fn host_of(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Local stub that fails the first `fail_first` requests with 503, then answers.
+    async fn stub_server(fail_first: usize) -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route(
+                "/price",
+                get(move |State(hits): State<Arc<AtomicUsize>>| async move {
+                    let n = hits.fetch_add(1, Ordering::SeqCst);
+                    if n < fail_first {
+                        Err(StatusCode::SERVICE_UNAVAILABLE)
+                    } else {
+                        Ok(Json(serde_json::json!({ "price": "1.5" })))
+                    }
+                }),
+            )
+            .with_state(hits.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{}/price", addr), hits)
+    }
+
+    fn settings() -> HttpSettings {
+        HttpSettings {
+            timeout_ms: 1_000,
+            max_retries: 2,
+            retry_base_delay_ms: 1,
+            cache_ttl_ms: 60_000,
+            default_rate_limit_per_sec: 0.0,
+            host_rate_limits: HashMap::new(),
+            circuit_failure_threshold: 3,
+            circuit_open_secs: 60,
+            base_urls: ExternalApiUrls::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors_then_caches() {
+        let (url, hits) = stub_server(2).await;
+        let http = HttpClient::new(&settings());
+
+        let first: serde_json::Value = http.get_json(&url).await.unwrap();
+        assert_eq!(first["price"], "1.5");
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        let second: serde_json::Value = http.get_json(&url).await.unwrap();
+        assert_eq!(second, first);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_opens_after_repeated_failures() {
+        let (url, hits) = stub_server(usize::MAX).await;
+        let http = HttpClient::new(&HttpSettings {
+            max_retries: 0,
+            ..settings()
+        });
+
+        for _ in 0..3 {
+            assert!(http.get_json::<serde_json::Value>(&url).await.is_err());
+        }
+        let err = http.get_json::<serde_json::Value>(&url).await.unwrap_err();
+        assert!(err.to_string().contains("Circuit open"));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // Submissions to the same host keep their own circuit and still go out
+        let post = http.post(&url, &serde_json::json!({})).await.unwrap_err();
+        assert!(!post.to_string().contains("Circuit open"));
+        assert!(post.to_string().contains("405"));
+    }
+
+    #[test]
+    fn test_rate_limit_spaces_requests_per_host() {
+        let mut s = settings();
+        s.host_rate_limits.insert("slow.example".to_string(), 2.0);
+        let http = HttpClient::new(&s);
+
+        assert!(http
+            .acquire("slow.example", RequestKind::Query)
+            .unwrap()
+            .is_zero());
+        let wait = http.acquire("slow.example", RequestKind::Query).unwrap();
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+        // Submissions have their own slots
+        assert!(http
+            .acquire("slow.example", RequestKind::Transaction)
+            .unwrap()
+            .is_zero());
+        // Hosts without a limit are not throttled
+        assert!(http
+            .acquire("fast.example", RequestKind::Query)
+            .unwrap()
+            .is_zero());
+        assert!(http
+            .acquire("fast.example", RequestKind::Query)
+            .unwrap()
+            .is_zero());
+    }
+}
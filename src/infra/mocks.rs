@@ -1,5 +1,8 @@
-use super::{DatabaseProvider, SolanaProvider};
-use crate::domain::{MarketUpdate, Orderbook, Trade};
+use super::{DatabaseProvider, PriceSource, SolanaProvider};
+use crate::domain::{
+    Candle, CandleInterval, MarketUpdate, Orderbook, PriceQuote, SignatureInfo, Trade,
+    TransactionLogs,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use mockall::mock;
@@ -89,12 +92,23 @@ mock! {
         async fn get_token_largest_accounts(&self, mint: &Pubkey) -> Result<Vec<(Pubkey, u64)>>;
         async fn get_token_supply(&self, mint: &Pubkey) -> Result<u64>;
         async fn get_open_orders_account_data(&self, oo_pubkey: &Pubkey) -> Result<Vec<u8>>;
+        async fn get_account_data(&self, pubkey: &Pubkey) -> Result<Vec<u8>>;
+        async fn get_account_data_with_slot(&self, pubkey: &Pubkey) -> Result<(u64, Vec<u8>)>;
         async fn create_market(
             &self,
             base_mint: &Pubkey,
             quote_mint: &Pubkey,
             market_authority: &Keypair,
         ) -> Result<Pubkey>;
+        async fn get_signatures_for_address(
+            &self,
+            address: &str,
+            before: Option<String>,
+            until: Option<String>,
+            limit: usize,
+        ) -> Result<Vec<SignatureInfo>>;
+        async fn get_transaction_logs(&self, signature: &str) -> Result<Option<TransactionLogs>>;
+        async fn get_block_time(&self, slot: u64) -> Result<i64>;
     }
 }
 
@@ -106,12 +120,30 @@ mock! {
         async fn set_state(&self, key: &str, value: &str) -> Result<()>;
         async fn get_recent_trades(&self, since_ts: i64) -> Result<Vec<Trade>>;
         async fn save_trade(&self, trade: &Trade) -> Result<()>;
-        async fn save_price_tick(&self, asset_price: rust_decimal::Decimal, sol_price: rust_decimal::Decimal) -> Result<()>;
-        async fn save_historical_price_ticks(&self, ticks: Vec<(i64, rust_decimal::Decimal, rust_decimal::Decimal)>) -> Result<()>;
+        async fn get_trade_volume(&self, since_ts: i64) -> Result<crate::domain::TradeVolume>;
+        async fn save_price_tick(&self, tick: &crate::domain::PriceTick) -> Result<()>;
+        async fn save_historical_price_ticks(&self, ticks: Vec<crate::domain::PriceTick>) -> Result<()>;
         async fn get_price_history(&self, since_ts: i64) -> Result<Vec<crate::domain::PriceTick>>;
+        async fn save_fiat_quote(&self, quote: &crate::domain::FiatQuote) -> Result<()>;
+        async fn get_latest_fiat_quote(&self, pair: &str) -> Result<Option<crate::domain::FiatQuote>>;
+        async fn save_cost_entries(&self, entries: &[crate::domain::CostEntry]) -> Result<()>;
+        async fn get_cost_entries(&self, from_ts: i64, to_ts: i64) -> Result<Vec<crate::domain::CostEntry>>;
         async fn save_latency_report(&self, report: &crate::infra::health::HealthReport) -> Result<()>;
         async fn get_latency_history(&self, service_name: &str, since_ts: i64) -> Result<Vec<crate::domain::LatencyTick>>;
         async fn save_wallet(&self, pubkey: &str, secret: &str) -> Result<()>;
         async fn get_wallets(&self) -> Result<Vec<(String, String)>>;
+        async fn save_candles(&self, candles: &[Candle]) -> Result<()>;
+        async fn get_candles(&self, interval: CandleInterval, from_ts: i64, to_ts: i64) -> Result<Vec<Candle>>;
+    }
+}
+
+mock! {
+    pub PriceSource {}
+    #[async_trait]
+    impl PriceSource for PriceSource {
+        fn name(&self) -> &str;
+        fn weight(&self) -> rust_decimal::Decimal;
+        fn reports_observation_time(&self) -> bool;
+        async fn fetch_price(&self) -> Result<PriceQuote>;
     }
 }
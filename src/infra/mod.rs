@@ -1,20 +1,27 @@
 pub mod database;
 pub mod health;
+pub mod http_client;
 pub mod kill_switch;
 pub mod mocks;
 pub mod observability;
 pub mod openbook;
 pub mod price_aggregator;
+pub mod price_sources;
+pub mod pyth;
+pub mod rpc_pool;
 pub mod solana_client;
 pub mod traits;
 pub mod wallet_manager;
+pub mod ws_stream;
 
-pub use traits::{DatabaseProvider, SolanaProvider};
+pub use traits::{DatabaseProvider, PriceSource, SolanaProvider};
 
 pub use database::Database;
 pub use health::HealthChecker;
+pub use http_client::HttpClient;
 pub use kill_switch::KillSwitch;
-pub use price_aggregator::PriceAggregator;
+pub use price_aggregator::{PriceAggregator, PriceFeed};
+pub use rpc_pool::RpcPool;
 pub use solana_client::SolanaClient;
 pub use wallet_manager::WalletManager;
 pub mod auth;
@@ -1,4 +1,4 @@
-use crate::domain::OrderbookLevel;
+use crate::domain::OrderbookOrder;
 use anyhow::{anyhow, Result};
 use rust_decimal::Decimal;
 use solana_sdk::pubkey::Pubkey;
@@ -125,6 +125,132 @@ impl MarketStateV1 {
     }
 }
 
+/// Market layout needed to decode either side of the book.
+#[derive(Debug, Clone)]
+pub enum BookLayout {
+    V1(MarketStateV1),
+    V2(MarketStateV2),
+}
+
+impl BookLayout {
+    pub fn from_market_data(data: &[u8]) -> Result<Self> {
+        if data.len() == 388 {
+            Ok(Self::V1(MarketStateV1::unpack(data)?))
+        } else {
+            Ok(Self::V2(MarketStateV2::unpack(data)?))
+        }
+    }
+
+    pub fn base_decimals(&self) -> u8 {
+        match self {
+            Self::V1(m) => m.base_decimals,
+            Self::V2(m) => m.base_decimals,
+        }
+    }
+
+    pub fn bids(&self) -> Pubkey {
+        match self {
+            Self::V1(m) => m.bids,
+            Self::V2(m) => m.bids,
+        }
+    }
+
+    pub fn asks(&self) -> Pubkey {
+        match self {
+            Self::V1(m) => m.asks,
+            Self::V2(m) => m.asks,
+        }
+    }
+
+    pub fn parse_side(&self, data: &[u8], is_bids: bool) -> Result<Vec<OrderbookOrder>> {
+        match self {
+            Self::V1(m) => parse_book_side_v1(
+                data,
+                is_bids,
+                m.base_decimals,
+                m.quote_decimals,
+                m.base_lot_size,
+                m.quote_lot_size,
+            ),
+            Self::V2(m) => parse_book_side_v2(
+                data,
+                is_bids,
+                m.base_decimals,
+                m.quote_decimals,
+                m.base_lot_size,
+                m.quote_lot_size,
+            ),
+        }
+    }
+}
+
+// Critbit node tags shared by the V1 slab and the V2 order tree
+const INNER_NODE_TAG: u32 = 1;
+const LEAF_NODE_TAG: u32 = 2;
+
+// V2 BookSide: discriminator, roots [fixed, oracle_pegged] (maybe_node u32, leaf_count u32),
+// reserved roots and padding, then OrderTreeNodes whose node array starts at byte 840
+const V2_FIXED_ROOT_OFFSET: usize = 8;
+const V2_NODES_START: usize = 840;
+const V2_NODE_SIZE: usize = 88;
+
+// V1 Slab: 5 bytes padding + 8 bytes account flags + SlabHeader
+// (bump_index u64, free_list_len u64, free_list_head u32, root_node u32, leaf_count u64)
+const V1_ROOT_NODE_OFFSET: usize = 33;
+const V1_LEAF_COUNT_OFFSET: usize = 37;
+const V1_NODES_START: usize = 45;
+const V1_NODE_SIZE: usize = 72;
+
+/// Walks the critbit tree from `root` and returns the byte offsets of its leaves, best
+/// price first. Nodes on the free list are never reached, so stale leaves are ignored.
+fn collect_leaves(
+    data: &[u8],
+    nodes_start: usize,
+    node_size: usize,
+    root: u32,
+    is_bids: bool,
+    read_tag: impl Fn(&[u8]) -> u32,
+) -> Result<Vec<usize>> {
+    let node_count = data.len().saturating_sub(nodes_start) / node_size;
+    let mut leaves = Vec::new();
+    let mut stack = vec![root];
+    let mut visited = 0;
+
+    while let Some(index) = stack.pop() {
+        visited += 1;
+        if visited > node_count {
+            return Err(anyhow!("Book side tree visits more nodes than exist"));
+        }
+
+        let index = index as usize;
+        if index >= node_count {
+            return Err(anyhow!("Book side node index {} out of range", index));
+        }
+
+        let offset = nodes_start + index * node_size;
+        match read_tag(&data[offset..offset + node_size]) {
+            INNER_NODE_TAG => {
+                // children[0] holds the lower keys, children[1] the higher ones
+                let low = u32::from_le_bytes(data[offset + 24..offset + 28].try_into()?);
+                let high = u32::from_le_bytes(data[offset + 28..offset + 32].try_into()?);
+                if is_bids {
+                    stack.push(low);
+                    stack.push(high);
+                } else {
+                    stack.push(high);
+                    stack.push(low);
+                }
+            }
+            LEAF_NODE_TAG => leaves.push(offset),
+            tag => return Err(anyhow!("Unexpected node tag {} in book side tree", tag)),
+        }
+    }
+
+    Ok(leaves)
+}
+
+/// Decodes the fixed-price order tree of a V2 BookSide. Oracle-pegged orders live in a
+/// separate tree whose prices depend on the oracle and are not included.
 pub fn parse_book_side_v2(
     data: &[u8],
     is_bids: bool,
@@ -132,8 +258,8 @@ pub fn parse_book_side_v2(
     quote_decimals: u8,
     base_lot_size: i64,
     quote_lot_size: i64,
-) -> Result<Vec<OrderbookLevel>> {
-    if data.len() < 8 + 128 {
+) -> Result<Vec<OrderbookOrder>> {
+    if data.len() < V2_NODES_START {
         return Err(anyhow!("BookSide data too short"));
     }
 
@@ -141,45 +267,59 @@ pub fn parse_book_side_v2(
         return Err(anyhow!("Invalid BookSide discriminator"));
     }
 
-    let nodes_start = 8 + 128; // Usually 128 bytes of header in V2
-    let node_size = 88; // LeafNode in V2
+    let root = u32::from_le_bytes(data[V2_FIXED_ROOT_OFFSET..V2_FIXED_ROOT_OFFSET + 4].try_into()?);
+    let leaf_count =
+        u32::from_le_bytes(data[V2_FIXED_ROOT_OFFSET + 4..V2_FIXED_ROOT_OFFSET + 8].try_into()?);
+    if leaf_count == 0 {
+        return Ok(vec![]);
+    }
 
-    let mut levels = Vec::new();
+    let leaves = collect_leaves(data, V2_NODES_START, V2_NODE_SIZE, root, is_bids, |node| {
+        node[0] as u32
+    })?;
 
-    for i in 0..((data.len() - nodes_start) / node_size).min(1024) {
-        let offset = nodes_start + i * node_size;
-        let tag = data[offset];
+    let base_pow = Decimal::from(10u64.pow(base_decimals as u32));
+    let quote_pow = Decimal::from(10u64.pow(quote_decimals as u32));
 
-        if tag == 2 {
-            // LeafNode tag in V2 is 2
-            let key = u128::from_le_bytes(data[offset + 16..offset + 32].try_into()?);
-            let price_raw = (key >> 64) as u64;
-            let quantity = i64::from_le_bytes(data[offset + 48..offset + 56].try_into()?);
+    let mut orders = Vec::with_capacity(leaves.len());
 
-            if quantity <= 0 {
-                continue;
-            }
+    for offset in leaves {
+        // LeafNode in V2:
+        // 0: tag, 1: owner_slot, 2..4: time_in_force, 4..8: padding
+        // 8..24: key (u128), 24..56: owner (OpenOrders account)
+        // 56..64: quantity, 64..72: timestamp, 72..80: peg_limit, 80..88: client_order_id
+        let key = u128::from_le_bytes(data[offset + 8..offset + 24].try_into()?);
+        let owner = Pubkey::new_from_array(data[offset + 24..offset + 56].try_into()?);
+        let quantity = i64::from_le_bytes(data[offset + 56..offset + 64].try_into()?);
+        let timestamp = u64::from_le_bytes(data[offset + 64..offset + 72].try_into()?);
+        let client_order_id = u64::from_le_bytes(data[offset + 80..offset + 88].try_into()?);
 
-            let base_pow = Decimal::from(10u64.pow(base_decimals as u32));
-            let quote_pow = Decimal::from(10u64.pow(quote_decimals as u32));
+        if quantity <= 0 {
+            continue;
+        }
 
-            // Price in V2 is quote_lots / base_lots
-            let price = (Decimal::from(price_raw) * Decimal::from(quote_lot_size) * base_pow)
-                / (Decimal::from(base_lot_size) * quote_pow);
+        let price_raw = (key >> 64) as u64;
 
-            let size = Decimal::from(quantity) * Decimal::from(base_lot_size) / base_pow;
+        // Price in V2 is quote_lots / base_lots
+        let price = (Decimal::from(price_raw) * Decimal::from(quote_lot_size) * base_pow)
+            / (Decimal::from(base_lot_size) * quote_pow);
 
-            levels.push(OrderbookLevel { price, size });
-        }
+        let size = Decimal::from(quantity) * Decimal::from(base_lot_size) / base_pow;
+
+        orders.push(OrderbookOrder {
+            order_id: key,
+            owner: owner.to_string(),
+            client_order_id,
+            timestamp: Some(timestamp),
+            price,
+            size,
+            is_own: false,
+        });
     }
 
-    if is_bids {
-        levels.sort_by(|a, b| b.price.cmp(&a.price));
-    } else {
-        levels.sort_by(|a, b| a.price.cmp(&b.price));
-    }
+    sort_orders(&mut orders, is_bids);
 
-    Ok(levels)
+    Ok(orders)
 }
 
 pub fn parse_book_side_v1(
@@ -189,58 +329,69 @@ pub fn parse_book_side_v1(
     quote_decimals: u8,
     base_lot_size: u64,
     quote_lot_size: u64,
-) -> Result<Vec<OrderbookLevel>> {
-    if data.len() < 5 + 8 {
+) -> Result<Vec<OrderbookOrder>> {
+    if data.len() < V1_NODES_START {
         return Err(anyhow!("Slab data too short"));
     }
 
-    let mut levels = Vec::new();
-    let node_size = 72;
-    let header_size = 45; // Serum V3 Slab header (5 bytes flags + 40 bytes Slab)
-
-    if data.len() < header_size {
+    let root = u32::from_le_bytes(data[V1_ROOT_NODE_OFFSET..V1_ROOT_NODE_OFFSET + 4].try_into()?);
+    let leaf_count =
+        u64::from_le_bytes(data[V1_LEAF_COUNT_OFFSET..V1_LEAF_COUNT_OFFSET + 8].try_into()?);
+    if leaf_count == 0 {
         return Ok(vec![]);
     }
 
-    let slot_count = (data.len() - header_size) / node_size;
-    for i in 0..slot_count.min(1024) {
-        let offset = header_size + i * node_size;
-        if offset + node_size > data.len() {
-            break;
-        }
-        let tag = u32::from_le_bytes(data[offset..offset + 4].try_into()?);
-
-        if tag == 2 {
-            // LeafNode in Serum V3
-            // Offsets within 72-byte node:
-            // 0..4: tag (2)
-            // 4..16: owner_slot + padding
-            // 16..32: key (u128)
-            // 32..64: owner (Pubkey)
-            // 64..72: quantity (u64)
-            let key = u128::from_le_bytes(data[offset + 16..offset + 32].try_into()?);
-            let price_raw = (key >> 64) as u64;
-            let quantity = u64::from_le_bytes(data[offset + 64..offset + 72].try_into()?);
-
-            let base_pow = Decimal::from(10u64.pow(base_decimals as u32));
-            let quote_pow = Decimal::from(10u64.pow(quote_decimals as u32));
-
-            // V1 Price math: (price_lots * quote_lot_size * base_pow) / (base_lot_size * quote_pow)
-            let price = (Decimal::from(price_raw) * Decimal::from(quote_lot_size) * base_pow)
-                / (Decimal::from(base_lot_size) * quote_pow);
-            let size = Decimal::from(quantity) * Decimal::from(base_lot_size) / base_pow;
-
-            levels.push(OrderbookLevel { price, size });
-        }
+    let leaves = collect_leaves(data, V1_NODES_START, V1_NODE_SIZE, root, is_bids, |node| {
+        u32::from_le_bytes([node[0], node[1], node[2], node[3]])
+    })?;
+
+    let base_pow = Decimal::from(10u64.pow(base_decimals as u32));
+    let quote_pow = Decimal::from(10u64.pow(quote_decimals as u32));
+
+    let mut orders = Vec::with_capacity(leaves.len());
+
+    for offset in leaves {
+        // LeafNode in Serum V3
+        // Offsets within 72-byte node:
+        // 0..4: tag (2)
+        // 4..8: owner_slot + fee_tier + padding
+        // 8..24: key (u128)
+        // 24..56: owner (OpenOrders account)
+        // 56..64: quantity (u64)
+        // 64..72: client_order_id (u64)
+        let key = u128::from_le_bytes(data[offset + 8..offset + 24].try_into()?);
+        let owner = Pubkey::new_from_array(data[offset + 24..offset + 56].try_into()?);
+        let quantity = u64::from_le_bytes(data[offset + 56..offset + 64].try_into()?);
+        let client_order_id = u64::from_le_bytes(data[offset + 64..offset + 72].try_into()?);
+        let price_raw = (key >> 64) as u64;
+
+        // V1 Price math: (price_lots * quote_lot_size * base_pow) / (base_lot_size * quote_pow)
+        let price = (Decimal::from(price_raw) * Decimal::from(quote_lot_size) * base_pow)
+            / (Decimal::from(base_lot_size) * quote_pow);
+        let size = Decimal::from(quantity) * Decimal::from(base_lot_size) / base_pow;
+
+        orders.push(OrderbookOrder {
+            order_id: key,
+            owner: owner.to_string(),
+            client_order_id,
+            timestamp: None,
+            price,
+            size,
+            is_own: false,
+        });
     }
 
+    sort_orders(&mut orders, is_bids);
+
+    Ok(orders)
+}
+
+fn sort_orders(orders: &mut [OrderbookOrder], is_bids: bool) {
     if is_bids {
-        levels.sort_by(|a, b| b.price.cmp(&a.price));
+        orders.sort_by_key(|o| std::cmp::Reverse(o.price));
     } else {
-        levels.sort_by(|a, b| a.price.cmp(&b.price));
+        orders.sort_by_key(|o| o.price);
     }
-
-    Ok(levels)
 }
 
 #[allow(dead_code, clippy::too_many_arguments)]
@@ -391,4 +542,74 @@ mod tests {
         let size = Decimal::from(quantity_lots) * Decimal::from(base_lot_size) / base_pow;
         assert_eq!(size, dec!(0.05));
     }
+
+    fn v2_leaf(data: &mut [u8], index: usize, price_lots: u64, seq: u64, quantity_lots: i64) {
+        let offset = super::V2_NODES_START + index * super::V2_NODE_SIZE;
+        data[offset] = 2;
+        let key = ((price_lots as u128) << 64) | seq as u128;
+        data[offset + 8..offset + 24].copy_from_slice(&key.to_le_bytes());
+        data[offset + 56..offset + 64].copy_from_slice(&quantity_lots.to_le_bytes());
+    }
+
+    fn v2_inner(data: &mut [u8], index: usize, low: u32, high: u32) {
+        let offset = super::V2_NODES_START + index * super::V2_NODE_SIZE;
+        data[offset] = 1;
+        data[offset + 24..offset + 28].copy_from_slice(&low.to_le_bytes());
+        data[offset + 28..offset + 32].copy_from_slice(&high.to_le_bytes());
+    }
+
+    #[test]
+    fn test_v2_tree_walk_aggregates_levels() {
+        let mut data = vec![0u8; super::V2_NODES_START + 8 * super::V2_NODE_SIZE];
+        data[0..8].copy_from_slice(&super::BOOK_SIDE_DISCRIMINATOR);
+        // root = node 4, three live leaves
+        data[8..12].copy_from_slice(&4u32.to_le_bytes());
+        data[12..16].copy_from_slice(&3u32.to_le_bytes());
+
+        //        4
+        //      /   \
+        //     0     5
+        //          / \
+        //         1   2
+        v2_inner(&mut data, 4, 0, 5);
+        v2_inner(&mut data, 5, 1, 2);
+        v2_leaf(&mut data, 0, 100, 1, 10);
+        v2_leaf(&mut data, 1, 120, 2, 5);
+        v2_leaf(&mut data, 2, 120, 3, 7);
+        // Stale leaf left on the free list; not reachable from the root
+        v2_leaf(&mut data, 3, 500, 4, 99);
+
+        let orders = super::parse_book_side_v2(&data, true, 0, 0, 1, 1).unwrap();
+        assert_eq!(orders.len(), 3);
+        assert_eq!(orders[0].price, dec!(120));
+
+        let book = crate::domain::Orderbook::from_orders("m", 0, orders, vec![]);
+        assert_eq!(book.bids.len(), 2);
+        assert_eq!(book.bids[0].price, dec!(120));
+        assert_eq!(book.bids[0].size, dec!(12));
+        assert_eq!(book.bids[0].order_count, 2);
+        assert_eq!(book.bids[1].price, dec!(100));
+        assert_eq!(book.bids[1].order_count, 1);
+    }
+
+    #[test]
+    fn test_v2_empty_and_corrupt_trees() {
+        let mut data = vec![0u8; super::V2_NODES_START + 2 * super::V2_NODE_SIZE];
+        data[0..8].copy_from_slice(&super::BOOK_SIDE_DISCRIMINATOR);
+        // leaf_count 0 means an empty side, whatever the node array holds
+        v2_leaf(&mut data, 0, 100, 1, 10);
+        assert!(super::parse_book_side_v2(&data, false, 0, 0, 1, 1)
+            .unwrap()
+            .is_empty());
+
+        // A root pointing outside the node array is rejected instead of read blindly
+        data[8..12].copy_from_slice(&7u32.to_le_bytes());
+        data[12..16].copy_from_slice(&1u32.to_le_bytes());
+        assert!(super::parse_book_side_v2(&data, false, 0, 0, 1, 1).is_err());
+
+        // An inner node pointing at itself is a cycle, not an infinite loop
+        v2_inner(&mut data, 1, 1, 1);
+        data[8..12].copy_from_slice(&1u32.to_le_bytes());
+        assert!(super::parse_book_side_v2(&data, false, 0, 0, 1, 1).is_err());
+    }
 }
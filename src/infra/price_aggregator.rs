@@ -1,84 +1,359 @@
+use crate::infra::price_sources::{
+    DexScreenerQuote, DexScreenerSource, FixedRateSource, FrankfurterSource, JupiterSource,
+    OpenBookMidSource, PythOracleSource,
+};
+use crate::infra::{HttpClient, PriceSource, SolanaProvider};
+use crate::utils::BotSettings;
 use anyhow::{anyhow, Result};
+use futures_util::future::join_all;
+use metrics::{counter, gauge};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
 
-#[derive(Debug, Deserialize)]
-struct DexScreenerResponse {
-    pairs: Option<Vec<DexPair>>,
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+/// The prices the bot needs, each quoted by its own set of sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceFeed {
+    /// Managed token priced in SOL
+    AssetSol,
+    SolUsd,
+    /// Euros per US dollar
+    UsdEur,
+}
+
+impl PriceFeed {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriceFeed::AssetSol => "asset_sol",
+            PriceFeed::SolUsd => "sol_usd",
+            PriceFeed::UsdEur => "usd_eur",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceStatus {
+    Accepted,
+    Stale,
+    Outlier,
+    Invalid,
+    Failed,
+}
+
+impl SourceStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SourceStatus::Accepted => "accepted",
+            SourceStatus::Stale => "stale",
+            SourceStatus::Outlier => "outlier",
+            SourceStatus::Invalid => "invalid",
+            SourceStatus::Failed => "failed",
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct DexPair {
-    #[serde(rename = "priceUsd")]
-    price_usd: Option<String>,
-    #[serde(rename = "priceNative")]
-    price_native: Option<String>,
+/// What one source returned and whether it made it into the median.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceReport {
+    pub source: String,
+    pub weight: Decimal,
+    pub price: Option<Decimal>,
+    pub timestamp: Option<i64>,
+    pub status: SourceStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregatedPrice {
+    pub feed: PriceFeed,
+    pub price: Decimal,
+    /// Newest timestamp among the accepted quotes.
+    pub timestamp: i64,
+    pub sources: Vec<SourceReport>,
+}
+
+impl AggregatedPrice {
+    pub fn contributors(&self) -> Vec<&str> {
+        self.sources
+            .iter()
+            .filter(|s| s.status == SourceStatus::Accepted)
+            .map(|s| s.source.as_str())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AggregationFilter {
+    pub max_age_secs: i64,
+    /// Maximum relative distance from the median, e.g. 0.05 for 5%.
+    pub max_deviation: Decimal,
+    pub min_sources: usize,
+}
+
+impl Default for AggregationFilter {
+    fn default() -> Self {
+        Self {
+            max_age_secs: 120,
+            max_deviation: Decimal::new(5, 2),
+            min_sources: 1,
+        }
+    }
 }
 
 pub struct PriceAggregator {
-    client: reqwest::Client,
+    http: Arc<HttpClient>,
+    sources: HashMap<PriceFeed, Vec<Arc<dyn PriceSource>>>,
+    filter: AggregationFilter,
 }
 
 impl PriceAggregator {
-    pub fn new() -> Self {
+    pub fn new(http: Arc<HttpClient>) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            http,
+            sources: HashMap::new(),
+            filter: AggregationFilter::default(),
         }
     }
 
-    pub async fn fetch_price_usd(&self, pair_address: &str) -> Result<Decimal> {
-        let url = format!(
-            "https://api.dexscreener.com/latest/dex/pairs/solana/{}",
-            pair_address
-        );
-        let resp = self
-            .client
-            .get(&url)
-            .send()
-            .await?
-            .json::<DexScreenerResponse>()
-            .await?;
+    pub fn with_filter(mut self, filter: AggregationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_source(mut self, feed: PriceFeed, source: Arc<dyn PriceSource>) -> Self {
+        self.sources.entry(feed).or_default().push(source);
+        self
+    }
+
+    pub fn from_settings(
+        settings: &BotSettings,
+        solana: Arc<dyn SolanaProvider>,
+        http: Arc<HttpClient>,
+    ) -> Self {
+        let cfg = &settings.price_sources;
+        let mut aggregator = Self::new(http.clone()).with_filter(AggregationFilter {
+            max_age_secs: cfg.max_age_secs,
+            max_deviation: cfg.max_deviation_percent / Decimal::from(100),
+            min_sources: cfg.min_sources.max(1),
+        });
+
+        if cfg.dexscreener.enabled {
+            let weight = cfg.dexscreener.weight;
+            aggregator = aggregator
+                .with_source(
+                    PriceFeed::AssetSol,
+                    Arc::new(DexScreenerSource::new(
+                        http.clone(),
+                        &settings.openbook_market_id,
+                        DexScreenerQuote::Native,
+                        weight,
+                    )),
+                )
+                .with_source(
+                    PriceFeed::SolUsd,
+                    Arc::new(DexScreenerSource::new(
+                        http.clone(),
+                        &settings.sol_usdc_market_id,
+                        DexScreenerQuote::Usd,
+                        weight,
+                    )),
+                );
+        }
+        if cfg.jupiter.enabled {
+            let weight = cfg.jupiter.weight;
+            aggregator = aggregator
+                .with_source(
+                    PriceFeed::AssetSol,
+                    Arc::new(JupiterSource::new(
+                        http.clone(),
+                        &settings.token_mint,
+                        SOL_MINT,
+                        weight,
+                    )),
+                )
+                .with_source(
+                    PriceFeed::SolUsd,
+                    Arc::new(JupiterSource::new(
+                        http.clone(),
+                        SOL_MINT,
+                        USDC_MINT,
+                        weight,
+                    )),
+                );
+        }
+        if cfg.openbook_mid.enabled {
+            let weight = cfg.openbook_mid.weight;
+            aggregator = aggregator
+                .with_source(
+                    PriceFeed::AssetSol,
+                    Arc::new(OpenBookMidSource::new(
+                        solana.clone(),
+                        &settings.openbook_market_id,
+                        weight,
+                    )),
+                )
+                .with_source(
+                    PriceFeed::SolUsd,
+                    Arc::new(OpenBookMidSource::new(
+                        solana.clone(),
+                        &settings.sol_usdc_market_id,
+                        weight,
+                    )),
+                );
+        }
+        if cfg.pyth.enabled {
+            match Pubkey::from_str(&cfg.pyth_sol_usd_account) {
+                Ok(account) => {
+                    aggregator = aggregator.with_source(
+                        PriceFeed::SolUsd,
+                        Arc::new(PythOracleSource::new(
+                            solana,
+                            account,
+                            cfg.pyth_max_confidence_percent / Decimal::from(100),
+                            cfg.pyth.weight,
+                        )),
+                    );
+                }
+                Err(e) => {
+                    warn!(error = %e, account = %cfg.pyth_sol_usd_account, "invalid_pyth_account_skipping_oracle")
+                }
+            }
+        }
 
-        let price_str = resp
-            .pairs
-            .and_then(|p| p.into_iter().next())
-            .and_then(|p| p.price_usd)
-            .ok_or_else(|| anyhow!("No price data found for pair {}", pair_address))?;
+        let fiat = &settings.fiat;
+        if fiat.frankfurter.enabled {
+            aggregator = aggregator.with_source(
+                PriceFeed::UsdEur,
+                Arc::new(FrankfurterSource::new(
+                    http.clone(),
+                    "USD",
+                    "EUR",
+                    fiat.frankfurter.weight,
+                )),
+            );
+        }
+        if let Some(rate) = fiat.fixed_usd_eur {
+            aggregator = aggregator.with_source(
+                PriceFeed::UsdEur,
+                Arc::new(FixedRateSource::new(rate, Decimal::ONE)),
+            );
+        }
 
-        Decimal::from_str(&price_str)
-            .map_err(|e| anyhow!("Failed to parse price '{}': {}", price_str, e))
+        aggregator
     }
 
-    pub async fn fetch_price_native(&self, pair_address: &str) -> Result<Decimal> {
-        let url = format!(
-            "https://api.dexscreener.com/latest/dex/pairs/solana/{}",
-            pair_address
-        );
-        let resp = self
-            .client
-            .get(&url)
-            .send()
-            .await?
-            .json::<DexScreenerResponse>()
-            .await?;
+    /// Weighted median across the feed's sources after dropping stale quotes and outliers.
+    pub async fn aggregate(&self, feed: PriceFeed) -> Result<AggregatedPrice> {
+        let sources = self.sources.get(&feed).map(Vec::as_slice).unwrap_or(&[]);
+        let results = join_all(sources.iter().map(|s| s.fetch_price())).await;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+
+        let mut reports: Vec<SourceReport> = sources
+            .iter()
+            .zip(results)
+            .map(|(source, result)| {
+                let mut report = SourceReport {
+                    source: source.name().to_string(),
+                    weight: source.weight(),
+                    price: None,
+                    timestamp: None,
+                    status: SourceStatus::Accepted,
+                    error: None,
+                };
+                match result {
+                    Ok(quote) => {
+                        report.price = Some(quote.price);
+                        report.timestamp = Some(quote.timestamp);
+                        if quote.price <= Decimal::ZERO || report.weight <= Decimal::ZERO {
+                            report.status = SourceStatus::Invalid;
+                        } else if source.reports_observation_time()
+                            && now - quote.timestamp > self.filter.max_age_secs
+                        {
+                            report.status = SourceStatus::Stale;
+                        }
+                    }
+                    Err(e) => {
+                        report.status = SourceStatus::Failed;
+                        report.error = Some(e.to_string());
+                    }
+                }
+                report
+            })
+            .collect();
+
+        let fresh = accepted_quotes(&reports);
+        if let Some(median) = weighted_median(&fresh) {
+            for report in reports
+                .iter_mut()
+                .filter(|r| r.status == SourceStatus::Accepted)
+            {
+                let price = report.price.unwrap_or_default();
+                if ((price - median) / median).abs() > self.filter.max_deviation {
+                    report.status = SourceStatus::Outlier;
+                }
+            }
+        }
+
+        for report in &reports {
+            if let Some(price) = report.price {
+                gauge!("bot_price_source_price", price.to_f64().unwrap_or(0.0), "feed" => feed.as_str(), "source" => report.source.clone());
+            }
+            if report.status != SourceStatus::Accepted {
+                counter!("bot_price_source_rejected_total", 1, "feed" => feed.as_str(), "source" => report.source.clone(), "reason" => report.status.as_str());
+            }
+        }
 
-        let price_str = resp
-            .pairs
-            .and_then(|p| p.into_iter().next())
-            .and_then(|p| p.price_native)
-            .ok_or_else(|| anyhow!("No native price data found for pair {}", pair_address))?;
+        let accepted = accepted_quotes(&reports);
+        gauge!("bot_price_sources_used", accepted.len() as f64, "feed" => feed.as_str());
+        if accepted.len() < self.filter.min_sources {
+            return Err(anyhow!(
+                "{} has {} usable sources, need {}: {:?}",
+                feed.as_str(),
+                accepted.len(),
+                self.filter.min_sources,
+                reports
+                    .iter()
+                    .map(|r| format!("{}={}", r.source, r.status.as_str()))
+                    .collect::<Vec<_>>()
+            ));
+        }
 
-        Decimal::from_str(&price_str)
-            .map_err(|e| anyhow!("Failed to parse native price '{}': {}", price_str, e))
+        let price = weighted_median(&accepted)
+            .ok_or_else(|| anyhow!("{} has no usable sources", feed.as_str()))?;
+        let timestamp = reports
+            .iter()
+            .filter(|r| r.status == SourceStatus::Accepted)
+            .filter_map(|r| r.timestamp)
+            .max()
+            .unwrap_or(now);
+        let aggregated = AggregatedPrice {
+            feed,
+            price,
+            timestamp,
+            sources: reports,
+        };
+        debug!(feed = feed.as_str(), %price, sources = ?aggregated.contributors(), "price_aggregated");
+        Ok(aggregated)
     }
 
     pub async fn fetch_sol_history(&self, limit: usize) -> Result<Vec<(i64, Decimal)>> {
         let url = format!(
-            "https://api.binance.com/api/v3/klines?symbol=SOLUSDT&interval=1h&limit={}",
+            "{}/api/v3/klines?symbol=SOLUSDT&interval=1h&limit={}",
+            self.http.urls().binance,
             limit
         );
-        let resp: Vec<Vec<serde_json::Value>> = self.client.get(&url).send().await?.json().await?;
+        let resp: Vec<Vec<serde_json::Value>> = self.http.get_json(&url).await?;
 
         let mut history = Vec::new();
         for kline in resp {
@@ -96,6 +371,166 @@ impl PriceAggregator {
 
 impl Default for PriceAggregator {
     fn default() -> Self {
-        Self::new()
+        Self::new(Arc::new(HttpClient::default()))
+    }
+}
+
+fn accepted_quotes(reports: &[SourceReport]) -> Vec<(Decimal, Decimal)> {
+    reports
+        .iter()
+        .filter(|r| r.status == SourceStatus::Accepted)
+        .filter_map(|r| r.price.map(|p| (p, r.weight)))
+        .collect()
+}
+
+/// Median of (price, weight) pairs; when the halfway mark falls between two prices they are averaged.
+fn weighted_median(quotes: &[(Decimal, Decimal)]) -> Option<Decimal> {
+    let mut sorted = quotes.to_vec();
+    sorted.sort_by_key(|(price, _)| *price);
+    let total: Decimal = sorted.iter().map(|(_, w)| *w).sum();
+    if total <= Decimal::ZERO {
+        return None;
+    }
+
+    let half = total / Decimal::from(2);
+    let mut cumulative = Decimal::ZERO;
+    for (i, (price, weight)) in sorted.iter().enumerate() {
+        cumulative += *weight;
+        if cumulative > half {
+            return Some(*price);
+        }
+        if cumulative == half {
+            let next = sorted.get(i + 1).map(|(p, _)| *p).unwrap_or(*price);
+            return Some((*price + next) / Decimal::from(2));
+        }
+    }
+    sorted.last().map(|(p, _)| *p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::PriceQuote;
+    use crate::infra::mocks::MockPriceSource;
+    use rust_decimal_macros::dec;
+
+    fn source(
+        name: &'static str,
+        weight: Decimal,
+        quote: Result<PriceQuote>,
+    ) -> Arc<dyn PriceSource> {
+        let mut mock = MockPriceSource::new();
+        mock.expect_name().return_const(name.to_string());
+        mock.expect_weight().return_const(weight);
+        // Only the "fetch_time" source stamps quotes with the time it was asked
+        mock.expect_reports_observation_time()
+            .return_const(name != "fetch_time");
+        let quote = std::sync::Mutex::new(Some(quote));
+        mock.expect_fetch_price()
+            .returning(move || quote.lock().unwrap().take().unwrap());
+        Arc::new(mock)
+    }
+
+    fn quote(price: Decimal, age_secs: i64) -> Result<PriceQuote> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        Ok(PriceQuote {
+            price,
+            timestamp: now - age_secs,
+        })
+    }
+
+    #[test]
+    fn test_weighted_median() {
+        assert_eq!(weighted_median(&[]), None);
+        assert_eq!(
+            weighted_median(&[(dec!(3), dec!(1)), (dec!(1), dec!(1)), (dec!(2), dec!(1))]),
+            Some(dec!(2))
+        );
+        // Heavier source pulls the median
+        assert_eq!(
+            weighted_median(&[(dec!(1), dec!(1)), (dec!(2), dec!(1)), (dec!(3), dec!(3))]),
+            Some(dec!(3))
+        );
+        assert_eq!(
+            weighted_median(&[(dec!(1), dec!(1)), (dec!(2), dec!(1))]),
+            Some(dec!(1.5))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_rejects_stale_outlier_and_failed_sources() {
+        let aggregator = PriceAggregator::default()
+            .with_source(
+                PriceFeed::SolUsd,
+                source("dexscreener", dec!(1), quote(dec!(150), 5)),
+            )
+            .with_source(
+                PriceFeed::SolUsd,
+                source("jupiter", dec!(1), quote(dec!(151), 5)),
+            )
+            .with_source(
+                PriceFeed::SolUsd,
+                source("pyth", dec!(2), quote(dec!(149), 1)),
+            )
+            .with_source(
+                PriceFeed::SolUsd,
+                source("openbook_mid", dec!(1), quote(dec!(300), 5)),
+            )
+            .with_source(
+                PriceFeed::SolUsd,
+                source("stale", dec!(1), quote(dec!(150), 600)),
+            )
+            .with_source(
+                PriceFeed::SolUsd,
+                source("broken", dec!(1), Err(anyhow!("HTTP 500"))),
+            )
+            .with_source(
+                PriceFeed::SolUsd,
+                source("fetch_time", dec!(0.5), quote(dec!(149.5), 600)),
+            );
+
+        let aggregated = aggregator.aggregate(PriceFeed::SolUsd).await.unwrap();
+        // Weights 2 (149) + 0.5 (149.5) + 1 (150) + 1 (151): the halfway mark is 149.5. The
+        // fetch-time quote is old but cannot be judged stale
+        assert_eq!(aggregated.price, dec!(149.5));
+        assert_eq!(
+            aggregated.contributors(),
+            vec!["dexscreener", "jupiter", "pyth", "fetch_time"]
+        );
+
+        let status = |name: &str| {
+            aggregated
+                .sources
+                .iter()
+                .find(|s| s.source == name)
+                .unwrap()
+                .status
+        };
+        assert_eq!(status("openbook_mid"), SourceStatus::Outlier);
+        assert_eq!(status("stale"), SourceStatus::Stale);
+        assert_eq!(status("broken"), SourceStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_requires_min_sources() {
+        let aggregator = PriceAggregator::default()
+            .with_filter(AggregationFilter {
+                min_sources: 2,
+                ..AggregationFilter::default()
+            })
+            .with_source(
+                PriceFeed::AssetSol,
+                source("jupiter", dec!(1), quote(dec!(0.00001), 1)),
+            )
+            .with_source(
+                PriceFeed::AssetSol,
+                source("dexscreener", dec!(1), Err(anyhow!("timeout"))),
+            );
+
+        assert!(aggregator.aggregate(PriceFeed::AssetSol).await.is_err());
+        assert!(aggregator.aggregate(PriceFeed::SolUsd).await.is_err());
     }
 }
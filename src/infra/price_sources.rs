@@ -0,0 +1,363 @@
+use crate::domain::PriceQuote;
+use crate::infra::{pyth, HttpClient, PriceSource, SolanaProvider};
+use anyhow::{anyhow, Result};
+use metrics::gauge;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DexScreenerQuote {
+    /// Price in the pair's quote token
+    Native,
+    Usd,
+}
+
+pub struct DexScreenerSource {
+    http: Arc<HttpClient>,
+    pair_address: String,
+    quote: DexScreenerQuote,
+    weight: Decimal,
+}
+
+impl DexScreenerSource {
+    pub fn new(
+        http: Arc<HttpClient>,
+        pair_address: &str,
+        quote: DexScreenerQuote,
+        weight: Decimal,
+    ) -> Self {
+        Self {
+            http,
+            pair_address: pair_address.to_string(),
+            quote,
+            weight,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DexScreenerResponse {
+    pairs: Option<Vec<DexPair>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DexPair {
+    #[serde(rename = "priceUsd")]
+    price_usd: Option<String>,
+    #[serde(rename = "priceNative")]
+    price_native: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl PriceSource for DexScreenerSource {
+    fn name(&self) -> &str {
+        "dexscreener"
+    }
+
+    fn weight(&self) -> Decimal {
+        self.weight
+    }
+
+    /// Pair responses carry no update time, so quotes are stamped when fetched.
+    fn reports_observation_time(&self) -> bool {
+        false
+    }
+
+    async fn fetch_price(&self) -> Result<PriceQuote> {
+        let url = format!(
+            "{}/latest/dex/pairs/solana/{}",
+            self.http.urls().dexscreener,
+            self.pair_address
+        );
+        let resp: DexScreenerResponse = self.http.get_json(&url).await?;
+
+        let pair = resp
+            .pairs
+            .and_then(|p| p.into_iter().next())
+            .ok_or_else(|| anyhow!("No DexScreener pair {}", self.pair_address))?;
+        let price_str = match self.quote {
+            DexScreenerQuote::Native => pair.price_native,
+            DexScreenerQuote::Usd => pair.price_usd,
+        }
+        .ok_or_else(|| anyhow!("No price data found for pair {}", self.pair_address))?;
+
+        Ok(PriceQuote {
+            price: Decimal::from_str(&price_str)
+                .map_err(|e| anyhow!("Failed to parse price '{}': {}", price_str, e))?,
+            timestamp: now_secs(),
+        })
+    }
+}
+
+pub struct JupiterSource {
+    http: Arc<HttpClient>,
+    mint: String,
+    vs_mint: String,
+    weight: Decimal,
+}
+
+impl JupiterSource {
+    pub fn new(http: Arc<HttpClient>, mint: &str, vs_mint: &str, weight: Decimal) -> Self {
+        Self {
+            http,
+            mint: mint.to_string(),
+            vs_mint: vs_mint.to_string(),
+            weight,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JupiterPriceResponse {
+    data: HashMap<String, Option<JupiterPrice>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JupiterPrice {
+    price: String,
+    #[serde(rename = "extraInfo")]
+    extra_info: Option<JupiterExtraInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JupiterExtraInfo {
+    #[serde(rename = "quotedPrice")]
+    quoted_price: Option<JupiterQuotedPrice>,
+}
+
+/// Unix times the buy and sell quotes behind the price were taken.
+#[derive(Debug, Deserialize)]
+struct JupiterQuotedPrice {
+    #[serde(rename = "buyAt")]
+    buy_at: Option<i64>,
+    #[serde(rename = "sellAt")]
+    sell_at: Option<i64>,
+}
+
+impl JupiterPrice {
+    fn quoted_at(&self) -> Option<i64> {
+        let quoted = self.extra_info.as_ref()?.quoted_price.as_ref()?;
+        quoted.buy_at.max(quoted.sell_at)
+    }
+}
+
+#[async_trait::async_trait]
+impl PriceSource for JupiterSource {
+    fn name(&self) -> &str {
+        "jupiter"
+    }
+
+    fn weight(&self) -> Decimal {
+        self.weight
+    }
+
+    async fn fetch_price(&self) -> Result<PriceQuote> {
+        let url = format!(
+            "{}?ids={}&vsToken={}&showExtraInfo=true",
+            self.http.urls().jupiter_price,
+            self.mint,
+            self.vs_mint
+        );
+        let resp: JupiterPriceResponse = self.http.get_json(&url).await?;
+
+        let price = resp
+            .data
+            .get(&self.mint)
+            .and_then(|p| p.as_ref())
+            .ok_or_else(|| anyhow!("Jupiter has no price for {}", self.mint))?;
+
+        Ok(PriceQuote {
+            price: Decimal::from_str(&price.price)
+                .map_err(|e| anyhow!("Failed to parse price '{}': {}", price.price, e))?,
+            // Stamped with when Jupiter quoted the price, so a cached quote can go stale
+            timestamp: price.quoted_at().unwrap_or_else(now_secs),
+        })
+    }
+}
+
+/// ECB reference rates via the Frankfurter API; updated once per business day.
+pub struct FrankfurterSource {
+    http: Arc<HttpClient>,
+    base: String,
+    quote: String,
+    weight: Decimal,
+}
+
+impl FrankfurterSource {
+    pub fn new(http: Arc<HttpClient>, base: &str, quote: &str, weight: Decimal) -> Self {
+        Self {
+            http,
+            base: base.to_string(),
+            quote: quote.to_string(),
+            weight,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FrankfurterResponse {
+    rates: HashMap<String, Decimal>,
+}
+
+#[async_trait::async_trait]
+impl PriceSource for FrankfurterSource {
+    fn name(&self) -> &str {
+        "frankfurter"
+    }
+
+    fn weight(&self) -> Decimal {
+        self.weight
+    }
+
+    async fn fetch_price(&self) -> Result<PriceQuote> {
+        let url = format!(
+            "{}/latest?from={}&to={}",
+            self.http.urls().frankfurter,
+            self.base,
+            self.quote
+        );
+        let resp: FrankfurterResponse = self.http.get_json(&url).await?;
+        let price = resp
+            .rates
+            .get(&self.quote)
+            .copied()
+            .ok_or_else(|| anyhow!("Frankfurter has no {}/{} rate", self.base, self.quote))?;
+
+        // The rate is the latest published one; it is current until the next publication
+        Ok(PriceQuote {
+            price,
+            timestamp: now_secs(),
+        })
+    }
+}
+
+/// Operator-configured rate, always fresh.
+pub struct FixedRateSource {
+    price: Decimal,
+    weight: Decimal,
+}
+
+impl FixedRateSource {
+    pub fn new(price: Decimal, weight: Decimal) -> Self {
+        Self { price, weight }
+    }
+}
+
+#[async_trait::async_trait]
+impl PriceSource for FixedRateSource {
+    fn name(&self) -> &str {
+        "fixed"
+    }
+
+    fn weight(&self) -> Decimal {
+        self.weight
+    }
+
+    async fn fetch_price(&self) -> Result<PriceQuote> {
+        Ok(PriceQuote {
+            price: self.price,
+            timestamp: now_secs(),
+        })
+    }
+}
+
+/// Mid price of the on-chain OpenBook book.
+pub struct OpenBookMidSource {
+    solana: Arc<dyn SolanaProvider>,
+    market_id: String,
+    weight: Decimal,
+}
+
+impl OpenBookMidSource {
+    pub fn new(solana: Arc<dyn SolanaProvider>, market_id: &str, weight: Decimal) -> Self {
+        Self {
+            solana,
+            market_id: market_id.to_string(),
+            weight,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PriceSource for OpenBookMidSource {
+    fn name(&self) -> &str {
+        "openbook_mid"
+    }
+
+    fn weight(&self) -> Decimal {
+        self.weight
+    }
+
+    async fn fetch_price(&self) -> Result<PriceQuote> {
+        let book = self.solana.get_orderbook(&self.market_id).await?;
+        let price = book
+            .get_mid_price()
+            .ok_or_else(|| anyhow!("OpenBook market {} has an empty side", self.market_id))?;
+        let timestamp = if book.timestamp > 0 {
+            book.timestamp
+        } else {
+            now_secs()
+        };
+        Ok(PriceQuote { price, timestamp })
+    }
+}
+
+/// Pyth price account read from chain; stamped with the oracle's publish time.
+pub struct PythOracleSource {
+    solana: Arc<dyn SolanaProvider>,
+    price_account: Pubkey,
+    max_confidence_ratio: Decimal,
+    weight: Decimal,
+}
+
+impl PythOracleSource {
+    pub fn new(
+        solana: Arc<dyn SolanaProvider>,
+        price_account: Pubkey,
+        max_confidence_ratio: Decimal,
+        weight: Decimal,
+    ) -> Self {
+        Self {
+            solana,
+            price_account,
+            max_confidence_ratio,
+            weight,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PriceSource for PythOracleSource {
+    fn name(&self) -> &str {
+        "pyth"
+    }
+
+    fn weight(&self) -> Decimal {
+        self.weight
+    }
+
+    async fn fetch_price(&self) -> Result<PriceQuote> {
+        let oracle = pyth::read_price(self.solana.as_ref(), &self.price_account).await?;
+        let price = oracle.validated(self.max_confidence_ratio)?;
+        gauge!(
+            "bot_sol_usd_oracle_publish_slot",
+            oracle.publish_slot as f64
+        );
+        Ok(PriceQuote {
+            price,
+            timestamp: oracle.publish_time,
+        })
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
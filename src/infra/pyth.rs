@@ -0,0 +1,199 @@
+use crate::infra::SolanaProvider;
+use anyhow::{anyhow, Result};
+use rust_decimal::Decimal;
+use solana_sdk::pubkey::Pubkey;
+
+const PYTH_MAGIC: u32 = 0xa1b2_c3d4;
+const PYTH_VERSION: u32 = 2;
+const PRICE_ACCOUNT_TYPE: u32 = 3;
+
+// Offsets into a v2 price account
+const EXPO_OFFSET: usize = 20;
+const TIMESTAMP_OFFSET: usize = 96;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+const AGG_PUB_SLOT_OFFSET: usize = 232;
+const MIN_ACCOUNT_LEN: usize = AGG_PUB_SLOT_OFFSET + 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PythStatus {
+    Unknown,
+    Trading,
+    Halted,
+    Auction,
+    Ignored,
+}
+
+impl From<u32> for PythStatus {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => PythStatus::Trading,
+            2 => PythStatus::Halted,
+            3 => PythStatus::Auction,
+            4 => PythStatus::Ignored,
+            _ => PythStatus::Unknown,
+        }
+    }
+}
+
+/// Aggregate price published in a Pyth price account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PythPrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub status: PythStatus,
+    pub publish_slot: u64,
+    pub publish_time: i64,
+}
+
+impl PythPrice {
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        scaled(self.price as i128, self.expo)
+    }
+
+    pub fn confidence(&self) -> Option<Decimal> {
+        scaled(self.conf as i128, self.expo)
+    }
+
+    /// Price if the feed is trading and its confidence interval is within
+    /// `max_confidence_ratio` of the price (e.g. 0.02 for 2%).
+    pub fn validated(&self, max_confidence_ratio: Decimal) -> Result<Decimal> {
+        if self.status != PythStatus::Trading {
+            return Err(anyhow!("Pyth feed is not trading: {:?}", self.status));
+        }
+        let price = self
+            .to_decimal()
+            .filter(|p| *p > Decimal::ZERO)
+            .ok_or_else(|| anyhow!("Pyth price out of range: {:?}", self))?;
+        let confidence = self.confidence().unwrap_or(Decimal::MAX);
+        if confidence / price > max_confidence_ratio {
+            return Err(anyhow!(
+                "Pyth confidence {} too wide for price {}",
+                confidence,
+                price
+            ));
+        }
+        Ok(price)
+    }
+}
+
+pub fn decode_price_account(data: &[u8]) -> Result<PythPrice> {
+    if data.len() < MIN_ACCOUNT_LEN {
+        return Err(anyhow!(
+            "Pyth price account too short: {} bytes",
+            data.len()
+        ));
+    }
+    if read_u32(data, 0) != PYTH_MAGIC {
+        return Err(anyhow!("Not a Pyth account (bad magic)"));
+    }
+    if read_u32(data, 4) != PYTH_VERSION {
+        return Err(anyhow!("Unsupported Pyth version {}", read_u32(data, 4)));
+    }
+    if read_u32(data, 8) != PRICE_ACCOUNT_TYPE {
+        return Err(anyhow!("Pyth account is not a price account"));
+    }
+
+    Ok(PythPrice {
+        price: read_i64(data, AGG_PRICE_OFFSET),
+        conf: read_u64(data, AGG_CONF_OFFSET),
+        expo: read_u32(data, EXPO_OFFSET) as i32,
+        status: PythStatus::from(read_u32(data, AGG_STATUS_OFFSET)),
+        publish_slot: read_u64(data, AGG_PUB_SLOT_OFFSET),
+        publish_time: read_i64(data, TIMESTAMP_OFFSET),
+    })
+}
+
+/// Fetches and decodes a price account; works against mainnet or a local validator
+/// started with the account loaded from a fixture.
+pub async fn read_price(solana: &dyn SolanaProvider, account: &Pubkey) -> Result<PythPrice> {
+    let data = solana.get_account_data(account).await?;
+    decode_price_account(&data)
+}
+
+fn scaled(value: i128, expo: i32) -> Option<Decimal> {
+    let scale = u32::try_from(-expo).ok()?;
+    Decimal::try_from_i128_with_scale(value, scale).ok()
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// SOL/USD price account from `tests/fixtures/pyth_sol_usd.json`, the file a local validator
+/// loads with `--account`, re-stamped with `publish_time`.
+#[cfg(test)]
+pub(crate) fn fixture_account_data(publish_time: i64) -> Vec<u8> {
+    use base64::Engine;
+
+    let fixture: serde_json::Value =
+        serde_json::from_str(include_str!("../../tests/fixtures/pyth_sol_usd.json")).unwrap();
+    let mut data = base64::engine::general_purpose::STANDARD
+        .decode(fixture["account"]["data"][0].as_str().unwrap())
+        .unwrap();
+    data[TIMESTAMP_OFFSET..TIMESTAMP_OFFSET + 8].copy_from_slice(&publish_time.to_le_bytes());
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::mocks::MockSolanaProvider;
+    use rust_decimal_macros::dec;
+
+    const FIXTURE_PUBLISH_TIME: i64 = 1_700_000_000;
+
+    #[test]
+    fn test_decode_price_account() {
+        let decoded = decode_price_account(&fixture_account_data(FIXTURE_PUBLISH_TIME)).unwrap();
+        assert_eq!(decoded.publish_time, 1_700_000_000);
+        assert_eq!(decoded.publish_slot, 250_000_000);
+        assert_eq!(decoded.status, PythStatus::Trading);
+        assert_eq!(decoded.to_decimal(), Some(dec!(145.23)));
+        assert_eq!(decoded.confidence(), Some(dec!(0.075)));
+
+        let mut not_price = fixture_account_data(FIXTURE_PUBLISH_TIME);
+        not_price[8] = 2;
+        assert!(decode_price_account(&not_price).is_err());
+        assert!(decode_price_account(&[0u8; 64]).is_err());
+    }
+
+    #[test]
+    fn test_validated_rejects_halted_and_wide_confidence() {
+        let price = decode_price_account(&fixture_account_data(FIXTURE_PUBLISH_TIME)).unwrap();
+        assert_eq!(price.validated(dec!(0.01)).unwrap(), dec!(145.23));
+
+        // 0.075 / 145.23 is about 0.05%
+        assert!(price.validated(dec!(0.0001)).is_err());
+
+        let halted = PythPrice {
+            status: PythStatus::Halted,
+            ..price
+        };
+        assert!(halted.validated(dec!(0.01)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_price_through_provider() {
+        let account = Pubkey::new_unique();
+        let mut solana = MockSolanaProvider::new();
+        solana
+            .expect_get_account_data()
+            .withf(move |pk| *pk == account)
+            .returning(|_| Ok(fixture_account_data(FIXTURE_PUBLISH_TIME)));
+
+        let price = read_price(&solana, &account).await.unwrap();
+        assert_eq!(price.price, 14_523_000_000);
+        assert_eq!(price.expo, -8);
+    }
+}
@@ -0,0 +1,683 @@
+use crate::domain::{MarketUpdate, Orderbook, SignatureInfo, TransactionLogs};
+use crate::infra::{HttpClient, SolanaClient, SolanaProvider};
+use crate::utils::settings::RpcSettings;
+use anyhow::{anyhow, Result};
+use metrics::{counter, gauge, histogram};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+// Consecutive failures before an endpoint is taken out of rotation
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+const COOLDOWN: Duration = Duration::from_secs(30);
+// Weight of the newest sample in the latency EWMA
+const LATENCY_ALPHA: f64 = 0.2;
+
+#[derive(Debug, Default)]
+struct EndpointStats {
+    ewma_latency_ms: f64,
+    requests: u64,
+    errors: u64,
+    consecutive_failures: u32,
+    cooldown_until: Option<Instant>,
+}
+
+impl EndpointStats {
+    fn in_cooldown(&self, now: Instant) -> bool {
+        self.cooldown_until.is_some_and(|until| until > now)
+    }
+
+    fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.requests as f64
+        }
+    }
+
+    /// Lower is better: latency inflated by the observed error rate.
+    fn score(&self) -> f64 {
+        self.ewma_latency_ms.max(1.0) * (1.0 + 10.0 * self.error_rate())
+    }
+}
+
+struct RpcEndpoint {
+    // Metric label; URLs can carry API keys so they are never exported
+    label: String,
+    provider: Arc<dyn SolanaProvider>,
+    stats: Mutex<EndpointStats>,
+}
+
+/// `SolanaProvider` over several RPC endpoints. Reads rotate across healthy endpoints and
+/// fail over on timeouts, rate limits and connection errors; writes are sent once, to the
+/// healthiest endpoint, so a transaction is never submitted twice.
+pub struct RpcPool {
+    endpoints: Vec<RpcEndpoint>,
+    next: AtomicUsize,
+    request_timeout: Duration,
+}
+
+impl RpcPool {
+    pub fn new(
+        endpoints: Vec<(String, Arc<dyn SolanaProvider>)>,
+        request_timeout: Duration,
+    ) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(anyhow!("RPC pool needs at least one endpoint"));
+        }
+        Ok(Self {
+            endpoints: endpoints
+                .into_iter()
+                .map(|(label, provider)| RpcEndpoint {
+                    label,
+                    provider,
+                    stats: Mutex::new(EndpointStats::default()),
+                })
+                .collect(),
+            next: AtomicUsize::new(0),
+            request_timeout,
+        })
+    }
+
+    pub fn from_settings(
+        settings: &RpcSettings,
+        commitment: CommitmentConfig,
+        http: Arc<HttpClient>,
+    ) -> Result<Self> {
+        let mut endpoints: Vec<(String, Arc<dyn SolanaProvider>)> = vec![(
+            "primary".to_string(),
+            Arc::new(SolanaClient::new(
+                &settings.primary_http,
+                commitment,
+                http.clone(),
+            )),
+        )];
+        for (i, url) in settings.secondary_http.iter().enumerate() {
+            endpoints.push((
+                format!("secondary_{}", i + 1),
+                Arc::new(SolanaClient::new(url, commitment, http.clone())),
+            ));
+        }
+        Self::new(
+            endpoints,
+            Duration::from_millis(settings.request_timeout_ms),
+        )
+    }
+
+    fn stats(&self, idx: usize) -> std::sync::MutexGuard<'_, EndpointStats> {
+        self.endpoints[idx]
+            .stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Healthy endpoints in round-robin order, followed by those cooling down as a last resort.
+    fn read_order(&self) -> Vec<usize> {
+        let n = self.endpoints.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % n;
+        let now = Instant::now();
+        let (mut healthy, cooling): (Vec<usize>, Vec<usize>) = (0..n)
+            .map(|i| (start + i) % n)
+            .partition(|&i| !self.stats(i).in_cooldown(now));
+        healthy.extend(cooling);
+        healthy
+    }
+
+    /// Endpoint with the best score outside cooldown; the primary if all are cooling down.
+    fn write_endpoint(&self) -> usize {
+        let now = Instant::now();
+        (0..self.endpoints.len())
+            .filter_map(|i| {
+                let stats = self.stats(i);
+                (!stats.in_cooldown(now)).then(|| (i, stats.score()))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    /// Errors the request itself caused (missing accounts, blocks not available yet, ...)
+    /// come back from a working endpoint, so they are counted without touching its health.
+    fn record_request_error(&self, idx: usize, method: &'static str) {
+        let label = self.endpoints[idx].label.clone();
+        counter!("bot_rpc_requests_total", 1, "endpoint" => label, "method" => method, "outcome" => "request_error");
+    }
+
+    /// Health and latency bookkeeping; `error` is an endpoint failure, see `is_endpoint_error`.
+    fn record(&self, idx: usize, method: &'static str, elapsed: Duration, error: Option<&str>) {
+        let endpoint = &self.endpoints[idx];
+        let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+        let mut stats = self.stats(idx);
+
+        stats.requests += 1;
+        stats.ewma_latency_ms = if stats.requests == 1 {
+            elapsed_ms
+        } else {
+            LATENCY_ALPHA * elapsed_ms + (1.0 - LATENCY_ALPHA) * stats.ewma_latency_ms
+        };
+
+        let outcome = match error {
+            None => {
+                stats.consecutive_failures = 0;
+                stats.cooldown_until = None;
+                "ok"
+            }
+            Some(message) => {
+                stats.errors += 1;
+                stats.consecutive_failures += 1;
+                let rate_limited = is_rate_limited(message);
+                if rate_limited || stats.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+                    stats.cooldown_until = Some(Instant::now() + COOLDOWN);
+                    warn!(
+                        endpoint = %endpoint.label,
+                        rate_limited,
+                        failures = stats.consecutive_failures,
+                        "rpc_endpoint_cooling_down"
+                    );
+                }
+                if rate_limited {
+                    "rate_limited"
+                } else {
+                    "error"
+                }
+            }
+        };
+
+        let healthy = !stats.in_cooldown(Instant::now());
+        let (error_rate, ewma) = (stats.error_rate(), stats.ewma_latency_ms);
+        drop(stats);
+
+        let label = endpoint.label.clone();
+        histogram!("bot_rpc_request_duration_seconds", elapsed.as_secs_f64(), "endpoint" => label.clone(), "method" => method);
+        counter!("bot_rpc_requests_total", 1, "endpoint" => label.clone(), "method" => method, "outcome" => outcome);
+        gauge!("bot_rpc_endpoint_latency_ms", ewma, "endpoint" => label.clone());
+        gauge!("bot_rpc_endpoint_error_rate", error_rate, "endpoint" => label.clone());
+        gauge!("bot_rpc_endpoint_healthy", if healthy { 1.0 } else { 0.0 }, "endpoint" => label);
+    }
+
+    async fn read<T, F, Fut>(&self, method: &'static str, call: F) -> Result<T>
+    where
+        F: Fn(Arc<dyn SolanaProvider>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut last_error = None;
+        for (attempt, idx) in self.read_order().into_iter().enumerate() {
+            if attempt > 0 {
+                counter!("bot_rpc_failovers_total", 1, "method" => method);
+            }
+
+            let provider = self.endpoints[idx].provider.clone();
+            let started = Instant::now();
+            let result = tokio::time::timeout(self.request_timeout, call(provider)).await;
+            let elapsed = started.elapsed();
+
+            match result {
+                Ok(Ok(value)) => {
+                    self.record(idx, method, elapsed, None);
+                    return Ok(value);
+                }
+                Ok(Err(e)) => {
+                    let message = e.to_string();
+                    if !is_endpoint_error(&message) {
+                        self.record_request_error(idx, method);
+                        return Err(e);
+                    }
+                    self.record(idx, method, elapsed, Some(&message));
+                    debug!(endpoint = %self.endpoints[idx].label, method, error = %message, "rpc_read_failing_over");
+                    last_error = Some(e);
+                }
+                Err(_) => {
+                    self.record(idx, method, elapsed, Some("request timed out"));
+                    debug!(endpoint = %self.endpoints[idx].label, method, "rpc_read_timed_out");
+                    last_error = Some(anyhow!(
+                        "{} timed out after {}ms",
+                        method,
+                        self.request_timeout.as_millis()
+                    ));
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| anyhow!("{} failed on every RPC endpoint", method)))
+    }
+
+    async fn write<T, F, Fut>(&self, method: &'static str, call: F) -> Result<T>
+    where
+        F: FnOnce(Arc<dyn SolanaProvider>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let idx = self.write_endpoint();
+        let started = Instant::now();
+        let result = call(self.endpoints[idx].provider.clone()).await;
+        match result.as_ref().err().map(|e| e.to_string()) {
+            Some(message) if !is_endpoint_error(&message) => self.record_request_error(idx, method),
+            error => self.record(idx, method, started.elapsed(), error.as_deref()),
+        }
+        result
+    }
+}
+
+fn is_rate_limited(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("429") || message.contains("too many requests")
+}
+
+/// Transport, timeout and rate-limit errors: the endpoint failed, not the request, so they
+/// count against its health and another endpoint may succeed.
+fn is_endpoint_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    is_rate_limited(&message)
+        || ["timed out", "timeout", "connection", "502", "503", "504"]
+            .iter()
+            .any(|marker| message.contains(marker))
+}
+
+#[async_trait::async_trait]
+impl SolanaProvider for RpcPool {
+    async fn get_market_data(&self, market_id: &str) -> Result<MarketUpdate> {
+        self.read("get_market_data", |p| async move {
+            p.get_market_data(market_id).await
+        })
+        .await
+    }
+
+    async fn cancel_all_orders(
+        &self,
+        market_id: &str,
+        wallet: &Keypair,
+        jito_url: &str,
+        tip_lamports: u64,
+    ) -> Result<String> {
+        self.write("cancel_all_orders", |p| async move {
+            p.cancel_all_orders(market_id, wallet, jito_url, tip_lamports)
+                .await
+        })
+        .await
+    }
+
+    async fn find_open_orders(&self, market_id: &str, owner: &Pubkey) -> Result<Option<Pubkey>> {
+        self.read("find_open_orders", |p| async move {
+            p.find_open_orders(market_id, owner).await
+        })
+        .await
+    }
+
+    async fn health(&self) -> bool {
+        for endpoint in &self.endpoints {
+            if tokio::time::timeout(self.request_timeout, endpoint.provider.health())
+                .await
+                .unwrap_or(false)
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    async fn get_orderbook(&self, market_id: &str) -> Result<Orderbook> {
+        self.read("get_orderbook", |p| async move {
+            p.get_orderbook(market_id).await
+        })
+        .await
+    }
+
+    async fn get_balance(&self, address: &str) -> Result<u64> {
+        self.read(
+            "get_balance",
+            |p| async move { p.get_balance(address).await },
+        )
+        .await
+    }
+
+    async fn get_token_balance(&self, wallet: &Pubkey, mint: &Pubkey) -> Result<u64> {
+        self.read("get_token_balance", |p| async move {
+            p.get_token_balance(wallet, mint).await
+        })
+        .await
+    }
+
+    async fn send_bundle(&self, txs: Vec<String>, jito_url: &str) -> Result<String> {
+        self.write("send_bundle", |p| async move {
+            p.send_bundle(txs, jito_url).await
+        })
+        .await
+    }
+
+    async fn jupiter_swap(
+        &self,
+        signer: &Keypair,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount_lamports: u64,
+        slippage_bps: u16,
+    ) -> Result<String> {
+        self.write("jupiter_swap", |p| async move {
+            p.jupiter_swap(
+                signer,
+                input_mint,
+                output_mint,
+                amount_lamports,
+                slippage_bps,
+            )
+            .await
+        })
+        .await
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<solana_sdk::hash::Hash> {
+        // Blockhashes feed transactions, so they come from the endpoint writes go to
+        self.write("get_latest_blockhash", |p| async move {
+            p.get_latest_blockhash().await
+        })
+        .await
+    }
+
+    async fn place_order(
+        &self,
+        market_id: &str,
+        signer: &Keypair,
+        side: u8,
+        price: i64,
+        size_lots: i64,
+        jito_api_url: &str,
+        tip_lamports: u64,
+        base_wallet: &Pubkey,
+        quote_wallet: &Pubkey,
+    ) -> Result<String> {
+        self.write("place_order", |p| async move {
+            p.place_order(
+                market_id,
+                signer,
+                side,
+                price,
+                size_lots,
+                jito_api_url,
+                tip_lamports,
+                base_wallet,
+                quote_wallet,
+            )
+            .await
+        })
+        .await
+    }
+
+    async fn cancel_order(
+        &self,
+        market_id: &str,
+        signer: &Keypair,
+        side: u8,
+        order_id: u128,
+        jito_api_url: &str,
+        tip_lamports: u64,
+    ) -> Result<String> {
+        self.write("cancel_order", |p| async move {
+            p.cancel_order(
+                market_id,
+                signer,
+                side,
+                order_id,
+                jito_api_url,
+                tip_lamports,
+            )
+            .await
+        })
+        .await
+    }
+
+    async fn place_and_cancel_bundle(
+        &self,
+        market_id: &str,
+        signer: &Keypair,
+        place_side: u8,
+        place_price: u64,
+        place_size: u64,
+        cancel_side: u8,
+        cancel_order_id: u128,
+        jito_api_url: &str,
+        tip_lamports: u64,
+        base_wallet: &Pubkey,
+        quote_wallet: &Pubkey,
+    ) -> Result<String> {
+        self.write("place_and_cancel_bundle", |p| async move {
+            p.place_and_cancel_bundle(
+                market_id,
+                signer,
+                place_side,
+                place_price,
+                place_size,
+                cancel_side,
+                cancel_order_id,
+                jito_api_url,
+                tip_lamports,
+                base_wallet,
+                quote_wallet,
+            )
+            .await
+        })
+        .await
+    }
+
+    async fn send_flash_volume_bundle(
+        &self,
+        market_id: &str,
+        wallet_a: &Keypair,
+        wallet_b: &Keypair,
+        price_lots: i64,
+        size_lots: i64,
+        tip_lamports: u64,
+        jito_url: &str,
+        base_mint: &Pubkey,
+        quote_mint: &Pubkey,
+    ) -> Result<String> {
+        self.write("send_flash_volume_bundle", |p| async move {
+            p.send_flash_volume_bundle(
+                market_id,
+                wallet_a,
+                wallet_b,
+                price_lots,
+                size_lots,
+                tip_lamports,
+                jito_url,
+                base_mint,
+                quote_mint,
+            )
+            .await
+        })
+        .await
+    }
+
+    async fn close_open_orders_account(
+        &self,
+        signer: &Keypair,
+        open_orders: &Pubkey,
+    ) -> Result<String> {
+        self.write("close_open_orders_account", |p| async move {
+            p.close_open_orders_account(signer, open_orders).await
+        })
+        .await
+    }
+
+    async fn get_open_orders_account_data(&self, oo_pubkey: &Pubkey) -> Result<Vec<u8>> {
+        self.read("get_open_orders_account_data", |p| async move {
+            p.get_open_orders_account_data(oo_pubkey).await
+        })
+        .await
+    }
+
+    async fn get_account_data(&self, pubkey: &Pubkey) -> Result<Vec<u8>> {
+        self.read("get_account_data", |p| async move {
+            p.get_account_data(pubkey).await
+        })
+        .await
+    }
+
+    async fn get_account_data_with_slot(&self, pubkey: &Pubkey) -> Result<(u64, Vec<u8>)> {
+        self.read("get_account_data_with_slot", |p| async move {
+            p.get_account_data_with_slot(pubkey).await
+        })
+        .await
+    }
+
+    async fn create_market(
+        &self,
+        base_mint: &Pubkey,
+        quote_mint: &Pubkey,
+        market_authority: &Keypair,
+    ) -> Result<Pubkey> {
+        self.write("create_market", |p| async move {
+            p.create_market(base_mint, quote_mint, market_authority)
+                .await
+        })
+        .await
+    }
+
+    async fn get_token_largest_accounts(&self, mint: &Pubkey) -> Result<Vec<(Pubkey, u64)>> {
+        self.read("get_token_largest_accounts", |p| async move {
+            p.get_token_largest_accounts(mint).await
+        })
+        .await
+    }
+
+    async fn get_token_supply(&self, mint: &Pubkey) -> Result<u64> {
+        self.read("get_token_supply", |p| async move {
+            p.get_token_supply(mint).await
+        })
+        .await
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<String>,
+        until: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>> {
+        self.read("get_signatures_for_address", |p| {
+            let (before, until) = (before.clone(), until.clone());
+            async move {
+                p.get_signatures_for_address(address, before, until, limit)
+                    .await
+            }
+        })
+        .await
+    }
+
+    async fn get_transaction_logs(&self, signature: &str) -> Result<Option<TransactionLogs>> {
+        self.read("get_transaction_logs", |p| async move {
+            p.get_transaction_logs(signature).await
+        })
+        .await
+    }
+
+    async fn get_block_time(&self, slot: u64) -> Result<i64> {
+        self.read(
+            "get_block_time",
+            |p| async move { p.get_block_time(slot).await },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::mocks::MockSolanaProvider;
+
+    fn pool(providers: Vec<MockSolanaProvider>) -> RpcPool {
+        RpcPool::new(
+            providers
+                .into_iter()
+                .enumerate()
+                .map(|(i, p)| (format!("ep_{}", i), Arc::new(p) as Arc<dyn SolanaProvider>))
+                .collect(),
+            Duration::from_millis(200),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_read_fails_over_on_rate_limit() {
+        let mut limited = MockSolanaProvider::new();
+        limited
+            .expect_get_balance()
+            .times(1)
+            .returning(|_| Err(anyhow!("HTTP status client error (429 Too Many Requests)")));
+        let mut healthy = MockSolanaProvider::new();
+        healthy.expect_get_balance().returning(|_| Ok(42));
+
+        let pool = pool(vec![limited, healthy]);
+        assert_eq!(pool.get_balance("addr").await.unwrap(), 42);
+
+        // The rate-limited endpoint is cooling down, so it is not tried again
+        assert_eq!(pool.get_balance("addr").await.unwrap(), 42);
+        assert!(pool.stats(0).in_cooldown(Instant::now()));
+    }
+
+    #[tokio::test]
+    async fn test_read_does_not_fail_over_on_request_errors() {
+        let mut first = MockSolanaProvider::new();
+        first
+            .expect_get_account_data()
+            .returning(|_| Err(anyhow!("AccountNotFound: pubkey=abc")));
+        let mut second = MockSolanaProvider::new();
+        first.expect_get_balance().returning(|_| Ok(1));
+        second.expect_get_balance().returning(|_| Ok(1));
+        second
+            .expect_get_account_data()
+            .returning(|_| Err(anyhow!("AccountNotFound: pubkey=abc")));
+
+        let pool = pool(vec![first, second]);
+        // Rotate so the next read starts at endpoint 0
+        pool.get_balance("addr").await.unwrap();
+        pool.get_balance("addr").await.unwrap();
+
+        let err = pool
+            .get_account_data(&Pubkey::new_unique())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("AccountNotFound"));
+
+        // Application errors leave the endpoints' health alone, however many there are
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            pool.get_account_data(&Pubkey::new_unique())
+                .await
+                .unwrap_err();
+        }
+        for idx in 0..2 {
+            let stats = pool.stats(idx);
+            assert_eq!(stats.errors, 0);
+            assert_eq!(stats.consecutive_failures, 0);
+            assert!(!stats.in_cooldown(Instant::now()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_writes_pin_to_healthiest_endpoint() {
+        let mut failing = MockSolanaProvider::new();
+        failing
+            .expect_get_balance()
+            .returning(|_| Err(anyhow!("connection refused")));
+        failing.expect_send_bundle().never();
+        let mut healthy = MockSolanaProvider::new();
+        healthy.expect_get_balance().returning(|_| Ok(7));
+        healthy
+            .expect_send_bundle()
+            .times(1)
+            .returning(|_, _| Ok("bundle".to_string()));
+
+        let pool = pool(vec![failing, healthy]);
+        for _ in 0..4 {
+            assert_eq!(pool.get_balance("addr").await.unwrap(), 7);
+        }
+
+        assert_eq!(pool.write_endpoint(), 1);
+        assert_eq!(
+            pool.send_bundle(vec![], "http://jito").await.unwrap(),
+            "bundle"
+        );
+    }
+}
@@ -1,22 +1,23 @@
-use crate::domain::{MarketUpdate, Orderbook};
-use crate::infra::openbook::{
-    parse_book_side_v1, parse_book_side_v2, MarketStateV1, MarketStateV2, OPENBOOK_V2_PROGRAM_ID,
-};
+use crate::domain::{MarketUpdate, Orderbook, SignatureInfo, TransactionLogs};
+use crate::infra::openbook::{BookLayout, MarketStateV2, OPENBOOK_V2_PROGRAM_ID};
+use crate::infra::HttpClient;
 use rust_decimal::Decimal;
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::commitment_config::CommitmentConfig;
 // use solana_sdk::hash::Hash; (unused since full path is used below)
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::signature::{Keypair, Signature, Signer};
 
 use anyhow::{anyhow, Result};
 use base64::Engine;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{error, info, warn};
 
 pub struct SolanaClient {
     client: RpcClient,
+    http: Arc<HttpClient>,
 }
 
 #[async_trait::async_trait]
@@ -201,6 +202,24 @@ impl crate::infra::SolanaProvider for SolanaClient {
         self.get_open_orders_account_data_impl(oo_pubkey).await
     }
 
+    async fn get_account_data(&self, pubkey: &Pubkey) -> Result<Vec<u8>> {
+        self.client
+            .get_account_data(pubkey)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn get_account_data_with_slot(&self, pubkey: &Pubkey) -> Result<(u64, Vec<u8>)> {
+        let response = self
+            .client
+            .get_account_with_commitment(pubkey, self.client.commitment())
+            .await?;
+        let account = response
+            .value
+            .ok_or_else(|| anyhow!("AccountNotFound: pubkey={}", pubkey))?;
+        Ok((response.context.slot, account.data))
+    }
+
     async fn create_market(
         &self,
         base_mint: &Pubkey,
@@ -230,12 +249,32 @@ impl crate::infra::SolanaProvider for SolanaClient {
     async fn get_token_supply(&self, mint: &Pubkey) -> Result<u64> {
         self.get_token_supply_impl(mint).await
     }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<String>,
+        until: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>> {
+        self.get_signatures_for_address_impl(address, before, until, limit)
+            .await
+    }
+
+    async fn get_transaction_logs(&self, signature: &str) -> Result<Option<TransactionLogs>> {
+        self.get_transaction_logs_impl(signature).await
+    }
+
+    async fn get_block_time(&self, slot: u64) -> Result<i64> {
+        Ok(self.client.get_block_time(slot).await?)
+    }
 }
 
 impl SolanaClient {
-    pub fn new(rpc_url: &str, commitment: CommitmentConfig) -> Self {
+    pub fn new(rpc_url: &str, commitment: CommitmentConfig, http: Arc<HttpClient>) -> Self {
         Self {
             client: RpcClient::new_with_commitment(rpc_url.to_string(), commitment),
+            http,
         }
     }
 
@@ -265,90 +304,31 @@ impl SolanaClient {
             .map_err(|e| anyhow!("Failed to parse market_id '{}': {}", market_id, e))?;
         let market_data = self.client.get_account_data(&market_pubkey).await?;
 
-        let (_bids_pubkey, _asks_pubkey, bids, asks) = if market_data.len() == 388 {
-            let market_state = MarketStateV1::unpack(&market_data)?;
-            let bids_pubkey = market_state.bids;
-            let asks_pubkey = market_state.asks;
-
-            let mut accounts = self
-                .client
-                .get_multiple_accounts(&[bids_pubkey, asks_pubkey])
-                .await?;
-
-            let asks_account = accounts
-                .pop()
-                .ok_or_else(|| anyhow!("Missing asks account"))?;
-            let bids_account = accounts
-                .pop()
-                .ok_or_else(|| anyhow!("Missing bids account"))?;
-
-            let bids_data = bids_account.map(|a| a.data).unwrap_or_default();
-            let asks_data = asks_account.map(|a| a.data).unwrap_or_default();
-
-            let bids = parse_book_side_v1(
-                &bids_data,
-                true,
-                market_state.base_decimals,
-                market_state.quote_decimals,
-                market_state.base_lot_size,
-                market_state.quote_lot_size,
-            )?;
-            let asks = parse_book_side_v1(
-                &asks_data,
-                false,
-                market_state.base_decimals,
-                market_state.quote_decimals,
-                market_state.base_lot_size,
-                market_state.quote_lot_size,
-            )?;
-            (bids_pubkey, asks_pubkey, bids, asks)
-        } else {
-            let market_state = MarketStateV2::unpack(&market_data)?;
-            let bids_pubkey = market_state.bids;
-            let asks_pubkey = market_state.asks;
-
-            let mut accounts = self
-                .client
-                .get_multiple_accounts(&[bids_pubkey, asks_pubkey])
-                .await?;
-
-            let asks_account = accounts
-                .pop()
-                .ok_or_else(|| anyhow!("Missing asks account"))?;
-            let bids_account = accounts
-                .pop()
-                .ok_or_else(|| anyhow!("Missing bids account"))?;
-
-            let bids_data = bids_account.map(|a| a.data).unwrap_or_default();
-            let asks_data = asks_account.map(|a| a.data).unwrap_or_default();
-
-            let bids = parse_book_side_v2(
-                &bids_data,
-                true,
-                market_state.base_decimals,
-                market_state.quote_decimals,
-                market_state.base_lot_size,
-                market_state.quote_lot_size,
-            )?;
-            let asks = parse_book_side_v2(
-                &asks_data,
-                false,
-                market_state.base_decimals,
-                market_state.quote_decimals,
-                market_state.base_lot_size,
-                market_state.quote_lot_size,
-            )?;
-            (bids_pubkey, asks_pubkey, bids, asks)
-        };
+        let layout = BookLayout::from_market_data(&market_data)?;
+
+        let mut accounts = self
+            .client
+            .get_multiple_accounts(&[layout.bids(), layout.asks()])
+            .await?;
+
+        let asks_account = accounts
+            .pop()
+            .ok_or_else(|| anyhow!("Missing asks account"))?;
+        let bids_account = accounts
+            .pop()
+            .ok_or_else(|| anyhow!("Missing bids account"))?;
+
+        let bids_data = bids_account.map(|a| a.data).unwrap_or_default();
+        let asks_data = asks_account.map(|a| a.data).unwrap_or_default();
+
+        let bid_orders = layout.parse_side(&bids_data, true)?;
+        let ask_orders = layout.parse_side(&asks_data, false)?;
 
         let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
 
-        Ok(Orderbook {
-            market_id: market_id.to_string(),
-            timestamp: now,
-            bids,
-            asks,
-        })
+        Ok(Orderbook::from_orders(
+            market_id, now, bid_orders, ask_orders,
+        ))
     }
 
     pub async fn get_market_data_impl(&self, market_id: &str) -> Result<MarketUpdate> {
@@ -361,7 +341,9 @@ impl SolanaClient {
         Ok(MarketUpdate {
             timestamp: ob.timestamp,
             price: mid_price,
-            volume_24h: Decimal::ZERO, // Will be accumulated from trades in DB or aggregator
+            // The book carries no volume; the trading loop fills both in from the trade store
+            volume_24h: Decimal::ZERO,
+            trade_count_24h: 0,
         })
     }
 
@@ -393,9 +375,7 @@ impl SolanaClient {
             "sending_jito_bundle"
         );
 
-        let client = reqwest::Client::new();
-        let response = client.post(jito_api_url).json(&payload).send().await?;
-        let result: serde_json::Value = response.json().await?;
+        let result: serde_json::Value = self.http.post_json(jito_api_url, &payload).await?;
 
         if let Some(error) = result.get("error") {
             error!(?error, "jito_bundle_error");
@@ -678,30 +658,28 @@ impl SolanaClient {
         amount_lamports: u64,
         slippage_bps: u16,
     ) -> Result<String> {
-        let client = reqwest::Client::new();
+        let jupiter_url = &self.http.urls().jupiter_swap;
 
         // 1. Get Quote
         let quote_url = format!(
-            "https://quote-api.jup.ag/v6/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}",
-            input_mint, output_mint, amount_lamports, slippage_bps
+            "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}",
+            jupiter_url, input_mint, output_mint, amount_lamports, slippage_bps
         );
-        let quote_resp = client.get(&quote_url).send().await?;
-        let quote: serde_json::Value = quote_resp.json().await?;
+        let quote: serde_json::Value = self.http.get_json(&quote_url).await?;
 
         if quote.get("error").is_some() {
             return Err(anyhow!("Jupiter quote error: {:?}", quote["error"]));
         }
 
         // 2. Get Swap Transaction
-        let swap_url = "https://quote-api.jup.ag/v6/swap";
+        let swap_url = format!("{}/swap", jupiter_url);
         let swap_payload = serde_json::json!({
             "quoteResponse": quote,
             "userPublicKey": signer.pubkey().to_string(),
             "wrapAndUnwrapSol": true
         });
 
-        let swap_resp = client.post(swap_url).json(&swap_payload).send().await?;
-        let swap_result: serde_json::Value = swap_resp.json().await?;
+        let swap_result: serde_json::Value = self.http.post_json(&swap_url, &swap_payload).await?;
 
         let tx_base64 = swap_result["swapTransaction"]
             .as_str()
@@ -859,4 +837,76 @@ impl SolanaClient {
         let amount = resp.amount.parse::<u64>().unwrap_or(0);
         Ok(amount)
     }
+
+    pub async fn get_signatures_for_address_impl(
+        &self,
+        address: &str,
+        before: Option<String>,
+        until: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>> {
+        let pubkey = Pubkey::from_str(address)
+            .map_err(|e| anyhow!("Failed to parse address '{}': {}", address, e))?;
+        let parse_sig = |s: Option<String>| -> Result<Option<Signature>> {
+            s.map(|s| {
+                Signature::from_str(&s).map_err(|e| anyhow!("Invalid signature '{}': {}", s, e))
+            })
+            .transpose()
+        };
+
+        let config = solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config {
+            before: parse_sig(before)?,
+            until: parse_sig(until)?,
+            limit: Some(limit),
+            commitment: Some(self.client.commitment()),
+        };
+
+        let statuses = self
+            .client
+            .get_signatures_for_address_with_config(&pubkey, config)
+            .await?;
+
+        Ok(statuses
+            .into_iter()
+            .map(|s| SignatureInfo {
+                signature: s.signature,
+                slot: s.slot,
+                block_time: s.block_time,
+                failed: s.err.is_some(),
+            })
+            .collect())
+    }
+
+    pub async fn get_transaction_logs_impl(
+        &self,
+        signature: &str,
+    ) -> Result<Option<TransactionLogs>> {
+        let sig = Signature::from_str(signature)
+            .map_err(|e| anyhow!("Invalid signature '{}': {}", signature, e))?;
+        let config = solana_client::rpc_config::RpcTransactionConfig {
+            encoding: Some(solana_transaction_status::UiTransactionEncoding::Json),
+            commitment: Some(self.client.commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+
+        let tx = match self.client.get_transaction_with_config(&sig, config).await {
+            Ok(tx) => tx,
+            Err(e) => {
+                warn!(error = ?e, %signature, "Transaction not available from RPC");
+                return Ok(None);
+            }
+        };
+
+        let logs: Option<Vec<String>> = tx
+            .transaction
+            .meta
+            .and_then(|meta| meta.log_messages.into());
+
+        Ok(Some(TransactionLogs {
+            signature: signature.to_string(),
+            slot: tx.slot,
+            block_time: tx.block_time,
+            logs: logs.unwrap_or_default(),
+        }))
+    }
 }
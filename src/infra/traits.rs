@@ -1,7 +1,8 @@
-use crate::domain::{MarketUpdate, Trade};
+use crate::domain::{
+    Candle, CandleInterval, MarketUpdate, PriceQuote, SignatureInfo, Trade, TransactionLogs,
+};
 use anyhow::Result;
 use async_trait::async_trait;
-use rust_decimal::Decimal;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signer::keypair::Keypair;
 
@@ -86,6 +87,9 @@ pub trait SolanaProvider: Send + Sync {
         open_orders: &Pubkey,
     ) -> Result<String>;
     async fn get_open_orders_account_data(&self, oo_pubkey: &Pubkey) -> Result<Vec<u8>>;
+    async fn get_account_data(&self, pubkey: &Pubkey) -> Result<Vec<u8>>;
+    /// Account data together with the slot it was read at.
+    async fn get_account_data_with_slot(&self, pubkey: &Pubkey) -> Result<(u64, Vec<u8>)>;
     async fn create_market(
         &self,
         base_mint: &Pubkey,
@@ -94,6 +98,15 @@ pub trait SolanaProvider: Send + Sync {
     ) -> Result<Pubkey>;
     async fn get_token_largest_accounts(&self, mint: &Pubkey) -> Result<Vec<(Pubkey, u64)>>;
     async fn get_token_supply(&self, mint: &Pubkey) -> Result<u64>;
+    async fn get_signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<String>,
+        until: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>>;
+    async fn get_transaction_logs(&self, signature: &str) -> Result<Option<TransactionLogs>>;
+    async fn get_block_time(&self, slot: u64) -> Result<i64>;
 }
 
 #[async_trait]
@@ -102,9 +115,19 @@ pub trait DatabaseProvider: Send + Sync {
     async fn set_state(&self, key: &str, value: &str) -> Result<()>;
     async fn get_recent_trades(&self, since_ts: i64) -> Result<Vec<Trade>>;
     async fn save_trade(&self, trade: &Trade) -> Result<()>;
-    async fn save_price_tick(&self, asset_price: Decimal, sol_price: Decimal) -> Result<()>;
-    async fn save_historical_price_ticks(&self, ticks: Vec<(i64, Decimal, Decimal)>) -> Result<()>;
+    async fn get_trade_volume(&self, since_ts: i64) -> Result<crate::domain::TradeVolume>;
+    async fn save_price_tick(&self, tick: &crate::domain::PriceTick) -> Result<()>;
+    async fn save_historical_price_ticks(&self, ticks: Vec<crate::domain::PriceTick>)
+        -> Result<()>;
     async fn get_price_history(&self, since_ts: i64) -> Result<Vec<crate::domain::PriceTick>>;
+    async fn save_fiat_quote(&self, quote: &crate::domain::FiatQuote) -> Result<()>;
+    async fn get_latest_fiat_quote(&self, pair: &str) -> Result<Option<crate::domain::FiatQuote>>;
+    async fn save_cost_entries(&self, entries: &[crate::domain::CostEntry]) -> Result<()>;
+    async fn get_cost_entries(
+        &self,
+        from_ts: i64,
+        to_ts: i64,
+    ) -> Result<Vec<crate::domain::CostEntry>>;
     async fn save_latency_report(&self, report: &crate::infra::health::HealthReport) -> Result<()>;
     async fn get_latency_history(
         &self,
@@ -113,4 +136,25 @@ pub trait DatabaseProvider: Send + Sync {
     ) -> Result<Vec<crate::domain::LatencyTick>>;
     async fn save_wallet(&self, pubkey: &str, secret: &str) -> Result<()>;
     async fn get_wallets(&self) -> Result<Vec<(String, String)>>;
+    async fn save_candles(&self, candles: &[Candle]) -> Result<()>;
+    async fn get_candles(
+        &self,
+        interval: CandleInterval,
+        from_ts: i64,
+        to_ts: i64,
+    ) -> Result<Vec<Candle>>;
+}
+
+/// One venue or oracle quoting a single price, e.g. BMV in SOL or SOL in USD.
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> &str;
+    /// Relative weight in the aggregated median.
+    fn weight(&self) -> rust_decimal::Decimal;
+    /// Whether quotes carry the time the price was observed. Sources that can only stamp
+    /// the fetch time are never treated as stale.
+    fn reports_observation_time(&self) -> bool {
+        true
+    }
+    async fn fetch_price(&self) -> Result<PriceQuote>;
 }
@@ -0,0 +1,55 @@
+use anyhow::{anyhow, Context, Result};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tracing::warn;
+
+pub const PING_INTERVAL: Duration = Duration::from_secs(20);
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(60);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// A connection that stayed up this long counts as healthy again
+const STABLE_CONNECTION: Duration = Duration::from_secs(60);
+
+pub type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+pub async fn connect(url: &str) -> Result<WsStream> {
+    let (ws_stream, _) = tokio::time::timeout(CONNECT_TIMEOUT, connect_async(url))
+        .await
+        .map_err(|_| anyhow!("websocket_connect_timeout"))?
+        .with_context(|| format!("failed_to_connect_ws {}", url))?;
+    Ok(ws_stream)
+}
+
+/// Runs `session` forever, reconnecting with exponential backoff whenever it ends.
+/// `on_disconnect` runs after every session, before the backoff sleep.
+pub async fn run_with_reconnect<F, Fut, D>(
+    stream: &'static str,
+    mut session: F,
+    mut on_disconnect: D,
+) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<()>>,
+    D: FnMut(),
+{
+    let mut backoff = Duration::from_secs(1);
+    loop {
+        let started = Instant::now();
+        let result = session().await;
+        on_disconnect();
+
+        match &result {
+            Ok(()) => warn!(stream, "ws_stream_ended_reconnecting"),
+            Err(e) => warn!(stream, error = ?e, "ws_stream_disconnected_retrying"),
+        }
+
+        if result.is_ok() || started.elapsed() >= STABLE_CONNECTION {
+            backoff = Duration::from_secs(1);
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff = std::cmp::min(backoff * 2, MAX_BACKOFF);
+    }
+}
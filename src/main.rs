@@ -1,8 +1,12 @@
+use solana_dex_bmv::domain::PriceTick;
 use solana_dex_bmv::infra::{
-    Database, DatabaseProvider, HealthChecker, PriceAggregator, SolanaClient, SolanaProvider,
-    WalletManager,
+    Database, DatabaseProvider, HealthChecker, HttpClient, PriceAggregator, PriceFeed, RpcPool,
+    SolanaProvider, WalletManager,
+};
+use solana_dex_bmv::services::{
+    CandleService, ChannelWidthController, MarketDataService, OrderbookService, PivotEngine,
+    TradingService,
 };
-use solana_dex_bmv::services::{MarketDataService, PivotEngine, TradingService};
 use solana_dex_bmv::utils::BotSettings;
 
 use anyhow::{Context, Result};
@@ -35,10 +39,12 @@ async fn main() -> Result<()> {
     let settings_read = settings.read().await;
 
     let commitment = CommitmentConfig::confirmed();
-    let solana = Arc::new(SolanaClient::new(
-        &settings_read.rpc_endpoints.primary_http,
+    let http = Arc::new(HttpClient::new(&settings_read.http));
+    let solana = Arc::new(RpcPool::from_settings(
+        &settings_read.rpc_endpoints,
         commitment,
-    ));
+        http.clone(),
+    )?);
     let database = Arc::new(Database::connect(&settings_read.database.url).await?);
     let wallet_manager = Arc::new(WalletManager::new(
         &settings_read.wallets.multi_wallet.keypairs,
@@ -49,11 +55,19 @@ async fn main() -> Result<()> {
         "WALLET_MANAGER_LOAD_FINISHED: {} wallets",
         wallet_manager.get_all_wallets().await.len()
     );
-    let price_aggregator = Arc::new(PriceAggregator::new());
+    let price_aggregator = Arc::new(PriceAggregator::from_settings(
+        &settings_read,
+        solana.clone(),
+        http.clone(),
+    ));
 
     // Perform connectivity health checks
-    let health_checker =
-        HealthChecker::new(solana.clone(), database.clone(), (*settings_read).clone());
+    let health_checker = HealthChecker::new(
+        solana.clone(),
+        database.clone(),
+        http.clone(),
+        (*settings_read).clone(),
+    );
     let health_reports = health_checker.run_all_checks().await;
     HealthChecker::display_reports(&health_reports);
     health_checker
@@ -84,33 +98,83 @@ async fn main() -> Result<()> {
         settings_read.pivot_vwap.jito_tip_sol,
         settings_read.pivot_vwap.fee_bps,
     ));
+    if let Err(e) = pivot_engine.load_state(database.as_ref()).await {
+        warn!(error = ?e, "Failed to restore pivot engine state");
+    }
+
+    // Candles are rebuilt after each backfill and refreshed periodically
+    let candle_service = Arc::new(CandleService::new(database.clone()));
+    let candle_service_task = candle_service.clone();
+    tokio::spawn(async move {
+        if let Err(e) = candle_service_task.run().await {
+            error!(error = ?e, "CandleService failed");
+        }
+    });
 
     // Initialize and spawn Market Data Service (WebSocket ingestion)
     let market_data_service = MarketDataService::new(
         &settings_read.rpc_endpoints.primary_ws,
         database.clone(),
+        solana.clone(),
         &settings_read.openbook_market_id,
         pivot_engine.clone(),
     );
+    let trade_backfill = settings_read.trade_backfill.clone();
+    let candle_service_backfill = candle_service.clone();
     tokio::spawn(async move {
+        if trade_backfill.enabled {
+            let horizon_secs = trade_backfill.horizon_hours as i64 * 3600;
+            match market_data_service
+                .backfill_history(horizon_secs, trade_backfill.max_signatures)
+                .await
+            {
+                Ok(_) => {
+                    let since = now_secs() - horizon_secs;
+                    if let Err(e) = candle_service_backfill.rebuild(since).await {
+                        warn!(error = ?e, "Candle rebuild after trade backfill failed");
+                    }
+                }
+                Err(e) => warn!(error = ?e, "Trade history backfill failed, continuing anyway"),
+            }
+        }
         if let Err(e) = market_data_service.run().await {
             error!(error = ?e, "MarketDataService failed");
         }
     });
 
+    // Initialize and spawn the local order book (account subscriptions)
+    let orderbook_service = OrderbookService::new(
+        &settings_read.rpc_endpoints.primary_ws,
+        solana.clone(),
+        wallet_manager.clone(),
+        &settings_read.openbook_market_id,
+    );
+    let orderbook_feed = orderbook_service.subscribe();
+    tokio::spawn(async move {
+        if let Err(e) = orderbook_service.run().await {
+            error!(error = ?e, "OrderbookService failed");
+        }
+    });
+
     // Initialize Auth
     let auth_secret = std::env::var("AUTH_SECRET")
         .context("AUTH_SECRET environment variable must be set for security")?;
     let auth = Arc::new(solana_dex_bmv::infra::Auth::new(auth_secret));
 
+    // Channel widths adapted by the trading loop, reported by the API
+    let channel_widths = Arc::new(ChannelWidthController::new());
+
     // Initialize and spawn API Server
     let api_server = solana_dex_bmv::infra::ApiServer::new(
         settings.clone(),
         database.clone() as Arc<dyn DatabaseProvider>,
         solana.clone() as Arc<dyn SolanaProvider>,
+        http.clone(),
         wallet_manager.clone(),
         pivot_engine.clone(),
         auth.clone(),
+        orderbook_feed.clone(),
+        channel_widths.clone(),
     );
 
     tokio::spawn(async move {
@@ -129,6 +193,9 @@ async fn main() -> Result<()> {
         wallet_manager,
         pivot_engine,
         price_aggregator.clone(),
+        http,
+        orderbook_feed,
+        channel_widths,
     )
     .await;
 
@@ -144,8 +211,13 @@ async fn main() -> Result<()> {
     info!("Bot is running. Press Ctrl+C to stop.");
 
     // Perform backfill if requested or needed
-    if let Err(e) = backfill_historical_data(database.clone(), price_aggregator.clone()).await {
-        warn!(error = ?e, "Historical backfill failed, continuing anyway");
+    match backfill_historical_data(database.clone(), price_aggregator.clone()).await {
+        Ok(()) => {
+            if let Err(e) = candle_service.rebuild(now_secs() - 48 * 3600).await {
+                warn!(error = ?e, "Candle rebuild after price backfill failed");
+            }
+        }
+        Err(e) => warn!(error = ?e, "Historical backfill failed, continuing anyway"),
     }
 
     // Run the trading loop with select for signal
@@ -191,17 +263,30 @@ async fn backfill_historical_data(
 
     // Use current BMV price for the backfill
     let bmv_price = aggregator
-        .fetch_price_native("B9coHrCxYv7xmPfSU7Z5VfugDqdTdZqZTpBGBdazq8AC")
+        .aggregate(PriceFeed::AssetSol)
         .await
+        .map(|p| p.price)
         .unwrap_or(Decimal::new(11, 6)); // Fallback to 0.000011
 
-    let mut ticks = Vec::new();
-    for (ts, sol_price) in sol_history {
-        ticks.push((ts, bmv_price, sol_price));
-    }
+    let ticks = sol_history
+        .into_iter()
+        .map(|(timestamp, sol_price)| PriceTick {
+            timestamp,
+            asset_price: bmv_price,
+            sol_price,
+            source: PriceTick::BINANCE_BACKFILL.to_string(),
+        })
+        .collect();
 
     db.save_historical_price_ticks(ticks).await?;
     info!("Successfully backfilled historical price data");
 
     Ok(())
 }
+
+fn now_secs() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
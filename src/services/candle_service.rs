@@ -0,0 +1,223 @@
+use crate::domain::{Candle, CandleInterval, PriceTick, Trade};
+use crate::infra::DatabaseProvider;
+use anyhow::Result;
+use metrics::{counter, gauge};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Rolls `trades_history` (and mid-price ticks for periods without trades) into
+/// persisted OHLCV candles for every supported interval.
+pub struct CandleService {
+    database: Arc<dyn DatabaseProvider>,
+}
+
+impl CandleService {
+    pub fn new(database: Arc<dyn DatabaseProvider>) -> Self {
+        Self { database }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        info!("starting_candle_service");
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            // The open daily bar spans the whole day, so refresh from its start
+            if let Err(e) = self.rebuild(now_secs()).await {
+                counter!("bot_candle_rebuild_errors_total", 1);
+                warn!(error = ?e, "candle_rebuild_failed");
+            }
+        }
+    }
+
+    /// Recomputes all candles from the day containing `since_ts` up to now and upserts
+    /// them. Used both for the periodic refresh and after history backfills.
+    pub async fn rebuild(&self, since_ts: i64) -> Result<usize> {
+        let start = CandleInterval::OneDay.bucket_start(since_ts);
+        let trades = self.database.get_recent_trades(start).await?;
+        let ticks = self.database.get_price_history(start).await?;
+
+        let mut total = 0;
+        for interval in CandleInterval::ALL {
+            let candles = build_candles(interval, &trades, &ticks);
+            self.database.save_candles(&candles).await?;
+            total += candles.len();
+        }
+
+        gauge!("bot_candles_last_rebuild_ts", now_secs() as f64);
+        info!(
+            since = start,
+            trades = trades.len(),
+            ticks = ticks.len(),
+            candles = total,
+            "candles_rebuilt"
+        );
+        Ok(total)
+    }
+}
+
+/// Buckets trades into OHLCV bars. Buckets without trades fall back to mid-price ticks
+/// with zero volume so quiet periods still have a price; backfilled ticks carry no asset
+/// price of their own and are skipped.
+pub fn build_candles(
+    interval: CandleInterval,
+    trades: &[Trade],
+    ticks: &[PriceTick],
+) -> Vec<Candle> {
+    let mut buckets: BTreeMap<i64, Candle> = BTreeMap::new();
+
+    let mut sorted_trades: Vec<&Trade> = trades.iter().collect();
+    sorted_trades.sort_by_key(|t| t.timestamp);
+    for trade in sorted_trades {
+        let start = interval.bucket_start(trade.timestamp);
+        let candle = buckets
+            .entry(start)
+            .or_insert_with(|| empty_candle(interval, start, trade.price));
+        apply_price(candle, trade.price);
+        candle.volume += trade.volume;
+        candle.trade_count += 1;
+    }
+
+    let mut sorted_ticks: Vec<&PriceTick> = ticks
+        .iter()
+        .filter(|t| t.asset_price > Decimal::ZERO && t.has_live_asset_price())
+        .collect();
+    sorted_ticks.sort_by_key(|t| t.timestamp);
+    for tick in sorted_ticks {
+        let start = interval.bucket_start(tick.timestamp);
+        let candle = buckets
+            .entry(start)
+            .or_insert_with(|| empty_candle(interval, start, tick.asset_price));
+        if candle.trade_count == 0 {
+            apply_price(candle, tick.asset_price);
+        }
+    }
+
+    buckets.into_values().collect()
+}
+
+fn empty_candle(interval: CandleInterval, start: i64, open: Decimal) -> Candle {
+    Candle {
+        interval,
+        start,
+        open,
+        high: open,
+        low: open,
+        close: open,
+        volume: Decimal::ZERO,
+        trade_count: 0,
+    }
+}
+
+fn apply_price(candle: &mut Candle, price: Decimal) {
+    candle.high = candle.high.max(price);
+    candle.low = candle.low.min(price);
+    candle.close = price;
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::OrderSide;
+    use crate::infra::mocks::MockDatabaseProvider;
+    use rust_decimal_macros::dec;
+
+    fn trade(id: &str, timestamp: i64, price: Decimal, volume: Decimal) -> Trade {
+        Trade {
+            id: id.to_string(),
+            timestamp,
+            price,
+            volume,
+            side: OrderSide::Buy,
+            wallet: "unknown".to_string(),
+            slot: None,
+            block_time: None,
+            maker: None,
+            taker: None,
+        }
+    }
+
+    fn tick(timestamp: i64, asset_price: Decimal) -> PriceTick {
+        PriceTick {
+            timestamp,
+            asset_price,
+            sol_price: dec!(150),
+            source: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_build_candles_from_trades_and_ticks() {
+        let trades = vec![
+            trade("b", 65, dec!(1.2), dec!(3)),
+            trade("a", 61, dec!(1.0), dec!(1)),
+            trade("c", 119, dec!(0.9), dec!(2)),
+        ];
+        // The first tick shares a bucket with trades and must not move its prices
+        let ticks = vec![
+            tick(70, dec!(5)),
+            tick(130, dec!(1.1)),
+            tick(150, dec!(1.3)),
+            PriceTick {
+                source: PriceTick::BINANCE_BACKFILL.to_string(),
+                ..tick(190, dec!(0.000011))
+            },
+        ];
+
+        let candles = build_candles(CandleInterval::OneMinute, &trades, &ticks);
+        assert_eq!(candles.len(), 2);
+
+        let traded = &candles[0];
+        assert_eq!(traded.start, 60);
+        assert_eq!(traded.open, dec!(1.0));
+        assert_eq!(traded.high, dec!(1.2));
+        assert_eq!(traded.low, dec!(0.9));
+        assert_eq!(traded.close, dec!(0.9));
+        assert_eq!(traded.volume, dec!(6));
+        assert_eq!(traded.trade_count, 3);
+
+        let quiet = &candles[1];
+        assert_eq!(quiet.start, 120);
+        assert_eq!(quiet.open, dec!(1.1));
+        assert_eq!(quiet.close, dec!(1.3));
+        assert_eq!(quiet.volume, Decimal::ZERO);
+        assert_eq!(quiet.trade_count, 0);
+
+        let hourly = build_candles(CandleInterval::OneHour, &trades, &ticks);
+        assert_eq!(hourly.len(), 1);
+        assert_eq!(hourly[0].trade_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_persists_every_interval() {
+        let mut mock_database = MockDatabaseProvider::new();
+        mock_database
+            .expect_get_recent_trades()
+            .withf(|since| *since == 86_400)
+            .returning(|_| Ok(vec![trade("a", 90_000, dec!(1), dec!(1))]));
+        mock_database
+            .expect_get_price_history()
+            .returning(|_| Ok(vec![]));
+        mock_database
+            .expect_save_candles()
+            .withf(|candles| candles.len() == 1)
+            .times(CandleInterval::ALL.len())
+            .returning(|_| Ok(()));
+
+        let service = CandleService::new(Arc::new(mock_database));
+        assert_eq!(service.rebuild(100_000).await.unwrap(), 4);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::domain::PriceTick;
+use crate::utils::settings::ChannelBoundsSettings;
+use metrics::gauge;
+use rust_decimal::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tracing::debug;
+
+/// Channel widths as fractions of the pivot.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ChannelWidths {
+    pub buy: Decimal,
+    pub sell: Decimal,
+}
+
+impl ChannelWidths {
+    pub fn configured(bounds: &ChannelBoundsSettings) -> Self {
+        Self {
+            buy: bounds.buy_percent,
+            sell: bounds.sell_percent,
+        }
+    }
+
+    /// Configured widths scaled by `volatility` against the reference volatility and held
+    /// within the floors and ceilings.
+    pub fn target(volatility: Decimal, bounds: &ChannelBoundsSettings) -> Self {
+        let adaptive = &bounds.adaptive;
+        let reference = adaptive.reference_volatility_percent / Decimal::from(100);
+        let scale = if reference > Decimal::ZERO {
+            volatility / reference
+        } else {
+            Decimal::ONE
+        };
+        let clamp = |width: Decimal, min: Decimal, max: Decimal| width.max(min).min(max);
+        Self {
+            buy: clamp(
+                bounds.buy_percent * scale,
+                adaptive.min_buy_percent,
+                adaptive.max_buy_percent,
+            ),
+            sell: clamp(
+                bounds.sell_percent * scale,
+                adaptive.min_sell_percent,
+                adaptive.max_sell_percent,
+            ),
+        }
+    }
+}
+
+/// Realised volatility of the asset price as a fraction: the square root of the summed
+/// squared log returns between consecutive trading-loop ticks. Other sources are skipped so
+/// returns are never taken across feeds; ticks sharing a timestamp are averaged first.
+/// `None` with fewer than two distinct timestamps.
+pub fn realized_volatility(history: &[PriceTick]) -> Option<Decimal> {
+    let mut by_ts: BTreeMap<i64, (f64, u32)> = BTreeMap::new();
+    for tick in history
+        .iter()
+        .filter(|t| t.source == PriceTick::TRADING_LOOP)
+    {
+        if let Some(price) = tick.asset_price.to_f64().filter(|p| *p > 0.0) {
+            let entry = by_ts.entry(tick.timestamp).or_default();
+            entry.0 += price;
+            entry.1 += 1;
+        }
+    }
+    if by_ts.len() < 2 {
+        return None;
+    }
+    let prices: Vec<f64> = by_ts.values().map(|(sum, n)| sum / *n as f64).collect();
+    let variance: f64 = prices.windows(2).map(|w| (w[1] / w[0]).ln().powi(2)).sum();
+    Decimal::from_f64(variance.sqrt())
+}
+
+/// Moves the channel widths towards a volatility-derived target with an EMA, so the grid
+/// follows the market inertially instead of jumping on every tick. Shared with the
+/// financial manager and the API so they see the widths the grid is built with.
+#[derive(Default)]
+pub struct ChannelWidthController {
+    widths: Mutex<Option<ChannelWidths>>,
+}
+
+impl ChannelWidthController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Widths of the last update, or the configured ones when adaptation is off or has
+    /// not run yet.
+    pub fn current(&self, bounds: &ChannelBoundsSettings) -> ChannelWidths {
+        let state = self.widths.lock().unwrap_or_else(|e| e.into_inner());
+        match *state {
+            Some(widths) if bounds.adaptive.enabled => widths,
+            _ => ChannelWidths::configured(bounds),
+        }
+    }
+
+    /// One smoothing step from the last widths, or from the configured ones on the first
+    /// call. Widths hold while there is not enough history; when adaptation is disabled the
+    /// configured widths are returned as-is.
+    pub fn update(&self, history: &[PriceTick], bounds: &ChannelBoundsSettings) -> ChannelWidths {
+        let mut state = self.widths.lock().unwrap_or_else(|e| e.into_inner());
+        let configured = ChannelWidths::configured(bounds);
+        if !bounds.adaptive.enabled {
+            *state = None;
+            return configured;
+        }
+
+        let current = state.unwrap_or(configured);
+        let next = match realized_volatility(history) {
+            Some(volatility) => {
+                gauge!(
+                    "bot_realized_volatility",
+                    volatility.to_f64().unwrap_or(0.0)
+                );
+                let target = ChannelWidths::target(volatility, bounds);
+                let alpha =
+                    Decimal::from(2) / Decimal::from(bounds.adaptive.smoothing_span.max(1) + 1);
+                debug!(
+                    %volatility,
+                    target_buy = %target.buy,
+                    target_sell = %target.sell,
+                    "channel_width_target"
+                );
+                ChannelWidths {
+                    buy: current.buy + alpha * (target.buy - current.buy),
+                    sell: current.sell + alpha * (target.sell - current.sell),
+                }
+            }
+            None => current,
+        };
+        *state = Some(next);
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::settings::AdaptiveChannelSettings;
+    use rust_decimal_macros::dec;
+
+    fn tick(timestamp: i64, asset_price: Decimal) -> PriceTick {
+        PriceTick {
+            timestamp,
+            asset_price,
+            sol_price: dec!(150),
+            source: "trading_loop".to_string(),
+        }
+    }
+
+    fn bounds() -> ChannelBoundsSettings {
+        ChannelBoundsSettings {
+            buy_percent: dec!(0.10),
+            sell_percent: dec!(0.20),
+            adaptive: AdaptiveChannelSettings {
+                enabled: true,
+                reference_volatility_percent: dec!(5),
+                min_buy_percent: dec!(0.05),
+                max_buy_percent: dec!(0.15),
+                min_sell_percent: dec!(0.10),
+                max_sell_percent: dec!(0.40),
+                smoothing_span: 3,
+                ..AdaptiveChannelSettings::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_realized_volatility_from_log_returns() {
+        let e = std::f64::consts::E;
+        let history = [
+            tick(1, dec!(1)),
+            tick(2, Decimal::from_f64(e.powf(0.03)).unwrap()),
+            tick(2, Decimal::from_f64(e.powf(0.03)).unwrap()),
+            tick(3, Decimal::from_f64(e.powf(-0.01)).unwrap()),
+            // Another feed's tick is not a return of this series
+            PriceTick {
+                source: PriceTick::BINANCE_BACKFILL.to_string(),
+                ..tick(4, dec!(5))
+            },
+        ];
+        // sqrt(0.03^2 + 0.04^2)
+        let vol = realized_volatility(&history).unwrap();
+        assert_eq!(vol.round_dp(6), dec!(0.05));
+        assert_eq!(realized_volatility(&history[..1]), None);
+    }
+
+    #[test]
+    fn test_widths_move_inertially_within_bounds() {
+        // 10% volatility doubles the configured widths; buy is capped at its ceiling
+        let target = ChannelWidths::target(dec!(0.10), &bounds());
+        assert_eq!(target.buy, dec!(0.15));
+        assert_eq!(target.sell, dec!(0.40));
+
+        let e = std::f64::consts::E;
+        let history = [
+            tick(1, dec!(1)),
+            tick(2, Decimal::from_f64(e.powf(0.10)).unwrap()),
+        ];
+        let controller = ChannelWidthController::new();
+        assert_eq!(
+            controller.current(&bounds()),
+            ChannelWidths::configured(&bounds())
+        );
+        // alpha = 2 / (3 + 1): half way from the configured widths per step
+        let first = controller.update(&history, &bounds());
+        assert_eq!(controller.current(&bounds()), first);
+        assert_eq!(first.buy.round_dp(6), dec!(0.125));
+        assert_eq!(first.sell.round_dp(6), dec!(0.30));
+        let second = controller.update(&history, &bounds());
+        assert_eq!(second.sell.round_dp(6), dec!(0.35));
+
+        // No history: widths hold
+        assert_eq!(controller.update(&[], &bounds()), second);
+
+        let mut disabled = bounds();
+        disabled.adaptive.enabled = false;
+        assert_eq!(
+            controller.update(&history, &disabled),
+            ChannelWidths::configured(&disabled)
+        );
+    }
+}
@@ -0,0 +1,413 @@
+use crate::domain::{CostEntry, CostKind, OrderSide, Trade};
+use crate::infra::DatabaseProvider;
+use crate::services::PnlTracker;
+use anyhow::Result;
+use metrics::counter;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+/// Base fee charged per transaction signature.
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// State key listing the OpenOrders accounts whose rent has been booked.
+const OPEN_ORDERS_RENT_KEY: &str = "open_orders_rent_booked";
+
+/// Fees paid by one submitted transaction or bundle.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TransactionCosts {
+    pub signatures: u64,
+    pub jito_tip_lamports: u64,
+    pub priority_fee_lamports: u64,
+}
+
+/// Books the swarm's operational costs so they can be checked against what the spread
+/// earns. Writes are best-effort: the transaction has already been sent by the time its
+/// costs are known, so a failed write is logged instead of failing the caller.
+pub struct CostLedger {
+    database: Arc<dyn DatabaseProvider>,
+}
+
+impl CostLedger {
+    pub fn new(database: Arc<dyn DatabaseProvider>) -> Self {
+        Self { database }
+    }
+
+    /// Network, priority and tip fees of a transaction signed by `wallet` (the fee payer).
+    pub async fn record_transaction(&self, signature: &str, wallet: &str, costs: TransactionCosts) {
+        let lamports = [
+            (
+                CostKind::NetworkFee,
+                costs.signatures.saturating_mul(LAMPORTS_PER_SIGNATURE),
+            ),
+            (CostKind::PriorityFee, costs.priority_fee_lamports),
+            (CostKind::JitoTip, costs.jito_tip_lamports),
+        ];
+        let entries = lamports
+            .into_iter()
+            .map(|(kind, amount)| entry(Some(signature), wallet, kind, lamports_to_sol(amount)))
+            .collect();
+        self.record(entries).await;
+    }
+
+    /// Rent locked up in a newly created account.
+    pub async fn record_rent_paid(
+        &self,
+        signature: Option<&str>,
+        wallet: &str,
+        amount_sol: Decimal,
+    ) {
+        self.record(vec![entry(
+            signature,
+            wallet,
+            CostKind::RentPaid,
+            amount_sol,
+        )])
+        .await;
+    }
+
+    /// Rent locked in a swarm OpenOrders account, booked once per account. The accounts
+    /// already booked are kept in the state table so a restart does not book them again.
+    pub async fn record_open_orders_rent(
+        &self,
+        wallet: &str,
+        open_orders: &str,
+        amount_sol: Decimal,
+    ) {
+        let mut booked: HashSet<String> = match self.database.get_state(OPEN_ORDERS_RENT_KEY).await
+        {
+            Ok(value) => value
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or_default(),
+            Err(e) => {
+                warn!(error = %e, open_orders, "open_orders_rent_state_unavailable");
+                return;
+            }
+        };
+        if !booked.insert(open_orders.to_string()) {
+            return;
+        }
+        self.record_rent_paid(None, wallet, amount_sol).await;
+        let value = serde_json::to_string(&booked).unwrap_or_default();
+        if let Err(e) = self.database.set_state(OPEN_ORDERS_RENT_KEY, &value).await {
+            warn!(error = %e, open_orders, "open_orders_rent_state_write_failed");
+        }
+    }
+
+    /// Rent returned by closing an account; booked as a negative cost.
+    pub async fn record_rent_reclaimed(&self, signature: &str, wallet: &str, amount_sol: Decimal) {
+        self.record(vec![entry(
+            Some(signature),
+            wallet,
+            CostKind::RentReclaimed,
+            -amount_sol,
+        )])
+        .await;
+    }
+
+    /// Shortfall of a swap against the reference price, valued in SOL. `expected_out` and
+    /// `actual_out` are in output units and `out_price_sol` is SOL per output unit.
+    pub async fn record_swap_slippage(
+        &self,
+        signature: &str,
+        wallet: &str,
+        expected_out: Decimal,
+        actual_out: Decimal,
+        out_price_sol: Decimal,
+    ) {
+        let amount_sol = (expected_out - actual_out) * out_price_sol;
+        self.record(vec![entry(
+            Some(signature),
+            wallet,
+            CostKind::SwapSlippage,
+            amount_sol,
+        )])
+        .await;
+    }
+
+    async fn record(&self, entries: Vec<CostEntry>) {
+        let entries: Vec<CostEntry> = entries
+            .into_iter()
+            .filter(|e| !e.amount_sol.is_zero())
+            .collect();
+        if entries.is_empty() {
+            return;
+        }
+        if let Err(e) = self.database.save_cost_entries(&entries).await {
+            warn!(error = %e, count = entries.len(), "cost_ledger_write_failed");
+            return;
+        }
+        for e in &entries {
+            counter!("bot_cost_entries_total", 1, "kind" => e.kind.as_str());
+            debug!(
+                kind = e.kind.as_str(),
+                amount_sol = %e.amount_sol,
+                signature = ?e.signature,
+                "cost_recorded"
+            );
+        }
+    }
+}
+
+/// Cumulative costs over a period next to the spread PnL realised by the trades of the
+/// same period, all in SOL.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostReport {
+    pub from: i64,
+    pub to: i64,
+    pub costs_by_kind: BTreeMap<CostKind, Decimal>,
+    pub total_costs_sol: Decimal,
+    /// Distinct transactions that booked a cost.
+    pub transaction_count: usize,
+    /// Fills between the swarm and the rest of the market.
+    pub trade_count: usize,
+    pub realized_spread_pnl_sol: Decimal,
+    pub net_sol: Decimal,
+    /// Realised PnL per unit of cost; absent when nothing was spent.
+    pub coverage_ratio: Option<Decimal>,
+    pub covered_by_spread: bool,
+}
+
+impl CostReport {
+    /// Trades are replayed from a flat position, so only round trips completed inside the
+    /// period count towards the realised PnL. Only fills with a swarm account (`accounts`)
+    /// on one side are replayed, signed by the side the swarm took; fills between two swarm
+    /// wallets leave the position unchanged and are skipped.
+    pub fn build(
+        from: i64,
+        to: i64,
+        entries: &[CostEntry],
+        trades: &[Trade],
+        accounts: &HashSet<String>,
+    ) -> Self {
+        let mut costs_by_kind: BTreeMap<CostKind, Decimal> = BTreeMap::new();
+        let mut signatures = HashSet::new();
+        for e in entries
+            .iter()
+            .filter(|e| e.timestamp >= from && e.timestamp <= to)
+        {
+            *costs_by_kind.entry(e.kind).or_default() += e.amount_sol;
+            if let Some(sig) = &e.signature {
+                signatures.insert(sig.as_str());
+            }
+        }
+        let total_costs_sol: Decimal = costs_by_kind.values().copied().sum();
+
+        let mut in_period: Vec<(&Trade, OrderSide)> = trades
+            .iter()
+            .filter(|t| t.timestamp >= from && t.timestamp <= to)
+            .filter_map(|t| Some((t, t.swarm_side(accounts)?)))
+            .collect();
+        in_period
+            .sort_by(|(a, _), (b, _)| a.timestamp.cmp(&b.timestamp).then_with(|| a.id.cmp(&b.id)));
+        in_period.dedup_by(|(a, _), (b, _)| a.id == b.id);
+
+        let mut tracker = PnlTracker::default();
+        for (t, side) in &in_period {
+            tracker.record_trade(*side, t.price, t.volume);
+        }
+        let realized_spread_pnl_sol = tracker.snapshot(Decimal::ZERO).realized_pnl;
+
+        let net_sol = realized_spread_pnl_sol - total_costs_sol;
+        Self {
+            from,
+            to,
+            costs_by_kind,
+            total_costs_sol,
+            transaction_count: signatures.len(),
+            trade_count: in_period.len(),
+            realized_spread_pnl_sol,
+            net_sol,
+            coverage_ratio: (total_costs_sol > Decimal::ZERO)
+                .then(|| realized_spread_pnl_sol / total_costs_sol),
+            covered_by_spread: net_sol >= Decimal::ZERO,
+        }
+    }
+
+    pub async fn load(
+        database: &dyn DatabaseProvider,
+        from: i64,
+        to: i64,
+        accounts: &HashSet<String>,
+    ) -> Result<Self> {
+        let entries = database.get_cost_entries(from, to).await?;
+        let trades = database.get_recent_trades(from).await?;
+        Ok(Self::build(from, to, &entries, &trades, accounts))
+    }
+}
+
+fn entry(signature: Option<&str>, wallet: &str, kind: CostKind, amount_sol: Decimal) -> CostEntry {
+    CostEntry {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64,
+        signature: signature.map(str::to_string),
+        kind,
+        wallet: wallet.to_string(),
+        amount_sol,
+    }
+}
+
+fn lamports_to_sol(lamports: u64) -> Decimal {
+    Decimal::from(lamports) / Decimal::from(1_000_000_000u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::mocks::MockDatabaseProvider;
+    use rust_decimal_macros::dec;
+    use std::sync::Mutex;
+
+    fn cost(timestamp: i64, signature: &str, kind: CostKind, amount_sol: Decimal) -> CostEntry {
+        CostEntry {
+            timestamp,
+            signature: Some(signature.to_string()),
+            kind,
+            wallet: "w1".to_string(),
+            amount_sol,
+        }
+    }
+
+    /// Taker-side fill between `maker` and `taker`.
+    fn trade(
+        id: &str,
+        timestamp: i64,
+        side: OrderSide,
+        price: Decimal,
+        maker: &str,
+        taker: &str,
+    ) -> Trade {
+        Trade {
+            id: id.to_string(),
+            timestamp,
+            price,
+            volume: dec!(100),
+            side,
+            wallet: "w1".to_string(),
+            slot: None,
+            block_time: None,
+            maker: Some(maker.to_string()),
+            taker: Some(taker.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transaction_costs_skip_zero_amounts() {
+        let saved = Arc::new(Mutex::new(Vec::new()));
+        let sink = saved.clone();
+        let mut database = MockDatabaseProvider::new();
+        database
+            .expect_save_cost_entries()
+            .returning(move |entries| {
+                sink.lock().unwrap().extend(entries.iter().cloned());
+                Ok(())
+            });
+        let ledger = CostLedger::new(Arc::new(database));
+
+        ledger
+            .record_transaction(
+                "sig1",
+                "w1",
+                TransactionCosts {
+                    signatures: 2,
+                    jito_tip_lamports: 1_000_000,
+                    priority_fee_lamports: 0,
+                },
+            )
+            .await;
+        ledger
+            .record_rent_reclaimed("sig2", "w1", dec!(0.023))
+            .await;
+
+        let saved = saved.lock().unwrap();
+        let amounts: Vec<(CostKind, Decimal)> =
+            saved.iter().map(|e| (e.kind, e.amount_sol)).collect();
+        assert_eq!(
+            amounts,
+            vec![
+                (CostKind::NetworkFee, dec!(0.00001)),
+                (CostKind::JitoTip, dec!(0.001)),
+                (CostKind::RentReclaimed, dec!(-0.023)),
+            ]
+        );
+        assert_eq!(saved[0].signature.as_deref(), Some("sig1"));
+    }
+
+    #[test]
+    fn test_report_compares_costs_with_realized_spread() {
+        let entries = [
+            cost(100, "a", CostKind::JitoTip, dec!(0.002)),
+            cost(100, "a", CostKind::NetworkFee, dec!(0.00001)),
+            cost(200, "b", CostKind::RentReclaimed, dec!(-0.001)),
+            cost(900, "c", CostKind::JitoTip, dec!(5)),
+        ];
+        // The swarm bought 100 at 0.0010 as taker and sold at 0.0011 as the maker of a
+        // taker buy: 0.01 SOL of spread. Third-party and internal fills do not count and
+        // the last trade is outside the period.
+        let trades = [
+            trade("t2", 150, OrderSide::Buy, dec!(0.0011), "oo1", "ext"),
+            trade("t1", 120, OrderSide::Buy, dec!(0.0010), "ext", "oo2"),
+            trade("x1", 130, OrderSide::Sell, dec!(0.5), "ext", "ext2"),
+            trade("i1", 140, OrderSide::Sell, dec!(0.5), "oo1", "oo2"),
+            trade("t3", 950, OrderSide::Sell, dec!(1), "ext", "oo1"),
+        ];
+        let accounts: HashSet<String> = ["oo1", "oo2"].map(String::from).into();
+
+        let report = CostReport::build(0, 500, &entries, &trades, &accounts);
+        assert_eq!(report.total_costs_sol, dec!(0.00101));
+        assert_eq!(report.costs_by_kind[&CostKind::RentReclaimed], dec!(-0.001));
+        assert_eq!(report.transaction_count, 2);
+        assert_eq!(report.trade_count, 2);
+        assert_eq!(report.realized_spread_pnl_sol, dec!(0.01));
+        assert_eq!(report.net_sol, dec!(0.00899));
+        assert!(report.covered_by_spread);
+        assert!(report.coverage_ratio.unwrap() > dec!(9.9));
+
+        let uncovered = CostReport::build(0, 1_000, &entries, &trades[..2], &accounts);
+        assert!(!uncovered.covered_by_spread);
+    }
+
+    #[tokio::test]
+    async fn test_open_orders_rent_is_booked_once() {
+        let state = Arc::new(Mutex::new(None::<String>));
+        let saved = Arc::new(Mutex::new(Vec::new()));
+        let mut database = MockDatabaseProvider::new();
+        let read = state.clone();
+        database
+            .expect_get_state()
+            .returning(move |_| Ok(read.lock().unwrap().clone()));
+        let write = state.clone();
+        database.expect_set_state().returning(move |_, value| {
+            *write.lock().unwrap() = Some(value.to_string());
+            Ok(())
+        });
+        let sink = saved.clone();
+        database
+            .expect_save_cost_entries()
+            .returning(move |entries| {
+                sink.lock().unwrap().extend(entries.iter().cloned());
+                Ok(())
+            });
+        let ledger = CostLedger::new(Arc::new(database));
+
+        ledger
+            .record_open_orders_rent("w1", "oo1", dec!(0.023))
+            .await;
+        ledger
+            .record_open_orders_rent("w1", "oo1", dec!(0.023))
+            .await;
+        ledger
+            .record_open_orders_rent("w2", "oo2", dec!(0.023))
+            .await;
+
+        let saved = saved.lock().unwrap();
+        assert_eq!(saved.len(), 2);
+        assert!(saved.iter().all(|e| e.kind == CostKind::RentPaid));
+        assert_eq!(saved[1].wallet, "w2");
+    }
+}
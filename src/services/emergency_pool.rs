@@ -1,7 +1,9 @@
 use crate::infra::{SolanaProvider, WalletManager};
+use crate::services::CostLedger;
 use crate::utils::BotSettings;
 use anyhow::{anyhow, Result};
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -11,6 +13,7 @@ pub struct EmergencyPoolService {
     solana: Arc<dyn SolanaProvider>,
     wallet_manager: Arc<WalletManager>,
     settings: Arc<RwLock<BotSettings>>,
+    cost_ledger: Arc<CostLedger>,
 }
 
 impl EmergencyPoolService {
@@ -18,11 +21,13 @@ impl EmergencyPoolService {
         solana: Arc<dyn SolanaProvider>,
         wallet_manager: Arc<WalletManager>,
         settings: Arc<RwLock<BotSettings>>,
+        cost_ledger: Arc<CostLedger>,
     ) -> Self {
         Self {
             solana,
             wallet_manager,
             settings,
+            cost_ledger,
         }
     }
 
@@ -33,9 +38,13 @@ impl EmergencyPoolService {
     pub async fn restart_pool(&self) -> Result<String> {
         info!("Emergency Pool Restart initiated");
 
-        let (token_mint, usdc_mint_str) = {
+        let (token_mint, usdc_mint_str, market_rent_sol) = {
             let s = self.settings.read().await;
-            (s.token_mint.clone(), s.wallets.usdc_wallet_3.clone())
+            (
+                s.token_mint.clone(),
+                s.wallets.usdc_wallet_3.clone(),
+                s.pivot_vwap.market_id_rent_sol,
+            )
         };
 
         let base_mint =
@@ -60,6 +69,13 @@ impl EmergencyPoolService {
             .create_market(&base_mint, &quote_mint, &market_authority)
             .await?;
         info!(%new_market_id, "Market created successfully");
+        self.cost_ledger
+            .record_rent_paid(
+                None,
+                &market_authority.pubkey().to_string(),
+                market_rent_sol,
+            )
+            .await;
 
         // 2. Seed Orders (Atomic injection)
         info!("Step 2: Seeding initial liquidity via Jito Bundle");
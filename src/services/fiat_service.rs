@@ -0,0 +1,257 @@
+use crate::domain::{FiatCurrency, FiatQuote};
+use crate::infra::{DatabaseProvider, PriceAggregator, PriceFeed};
+use crate::utils::settings::FiatSettings;
+use anyhow::Result;
+use metrics::gauge;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+/// Latest known cross rates; either may be missing until first fetched.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FiatRates {
+    pub sol_usd: Option<Decimal>,
+    pub usd_eur: Option<Decimal>,
+}
+
+impl FiatRates {
+    /// Units of `currency` per US dollar.
+    pub fn usd_to(&self, currency: FiatCurrency) -> Option<Decimal> {
+        match currency {
+            FiatCurrency::Usd => Some(Decimal::ONE),
+            FiatCurrency::Eur => self.usd_eur,
+        }
+    }
+
+    /// Units of `currency` per SOL.
+    pub fn sol_to(&self, currency: FiatCurrency) -> Option<Decimal> {
+        Some(self.sol_usd? * self.usd_to(currency)?)
+    }
+
+    /// Reads the newest persisted rates, for readers without a running `FiatService`.
+    pub async fn load(database: &dyn DatabaseProvider) -> Result<Self> {
+        Ok(Self {
+            sol_usd: database
+                .get_latest_fiat_quote(FiatQuote::SOL_USD)
+                .await?
+                .map(|q| q.price),
+            usd_eur: database
+                .get_latest_fiat_quote(FiatQuote::USD_EUR)
+                .await?
+                .map(|q| q.price),
+        })
+    }
+}
+
+#[derive(Default)]
+struct FiatState {
+    quotes: HashMap<&'static str, FiatQuote>,
+    /// Timestamp of the newest quote written per pair.
+    persisted: HashMap<&'static str, i64>,
+    last_usd_eur_fetch: Option<i64>,
+}
+
+/// Keeps SOL/USD and USD/EUR current so figures computed in SOL can be reported in fiat,
+/// and writes them to `fiat_rates` once per refresh interval.
+pub struct FiatService {
+    price_aggregator: Arc<PriceAggregator>,
+    database: Arc<dyn DatabaseProvider>,
+    state: Mutex<FiatState>,
+}
+
+impl FiatService {
+    pub fn new(
+        price_aggregator: Arc<PriceAggregator>,
+        database: Arc<dyn DatabaseProvider>,
+    ) -> Self {
+        Self {
+            price_aggregator,
+            database,
+            state: Mutex::new(FiatState::default()),
+        }
+    }
+
+    /// Seeds the rates from the database so reporting works before the first refresh.
+    pub async fn load_latest(&self) -> Result<()> {
+        for pair in [FiatQuote::SOL_USD, FiatQuote::USD_EUR] {
+            if let Some(quote) = self.database.get_latest_fiat_quote(pair).await? {
+                let mut state = self.lock();
+                state.persisted.insert(pair, quote.timestamp);
+                state.quotes.insert(pair, quote);
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes the SOL/USD rate the trading loop settled on, re-fetches USD/EUR when due and
+    /// persists whichever rate has moved on by a refresh interval since it was last written.
+    /// `None` means the price guard holds SOL/USD stale; the last rate is then dropped so
+    /// fiat figures go unreported instead of being converted at an old price.
+    pub async fn refresh(
+        &self,
+        sol_usd: Option<Decimal>,
+        now: i64,
+        settings: &FiatSettings,
+    ) -> Result<()> {
+        let interval = settings.refresh_interval_secs.max(1);
+        let fetch_usd_eur = {
+            let mut state = self.lock();
+            match sol_usd.filter(|p| *p > Decimal::ZERO) {
+                Some(price) => {
+                    state.quotes.insert(
+                        FiatQuote::SOL_USD,
+                        quote(FiatQuote::SOL_USD, "trading_loop", price, now),
+                    );
+                }
+                None => {
+                    if state.quotes.remove(FiatQuote::SOL_USD).is_some() {
+                        debug!("sol_usd_rate_stale_dropped");
+                    }
+                }
+            }
+            let due = state
+                .last_usd_eur_fetch
+                .is_none_or(|last| now - last >= interval);
+            if due {
+                state.last_usd_eur_fetch = Some(now);
+            }
+            due
+        };
+
+        if fetch_usd_eur {
+            match self.price_aggregator.aggregate(PriceFeed::UsdEur).await {
+                Ok(agg) => {
+                    let provider = agg.contributors().join("+");
+                    self.lock().quotes.insert(
+                        FiatQuote::USD_EUR,
+                        quote(FiatQuote::USD_EUR, &provider, agg.price, now),
+                    );
+                }
+                Err(e) => warn!(error = %e, "usd_eur_refresh_failed"),
+            }
+        }
+
+        let to_persist: Vec<(&'static str, FiatQuote)> = {
+            let state = self.lock();
+            state
+                .quotes
+                .iter()
+                .filter(|(pair, q)| {
+                    state
+                        .persisted
+                        .get(*pair)
+                        .is_none_or(|ts| q.timestamp - ts >= interval)
+                })
+                .map(|(pair, q)| (*pair, q.clone()))
+                .collect()
+        };
+        for (pair, quote) in to_persist {
+            gauge!("bot_fiat_rate", quote.price.to_f64().unwrap_or(0.0), "pair" => pair);
+            self.database.save_fiat_quote(&quote).await?;
+            debug!(pair, price = %quote.price, provider = %quote.provider, "fiat_rate_saved");
+            self.lock().persisted.insert(pair, quote.timestamp);
+        }
+        Ok(())
+    }
+
+    pub fn rates(&self) -> FiatRates {
+        let state = self.lock();
+        FiatRates {
+            sol_usd: state.quotes.get(FiatQuote::SOL_USD).map(|q| q.price),
+            usd_eur: state.quotes.get(FiatQuote::USD_EUR).map(|q| q.price),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FiatState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn quote(pair: &str, provider: &str, price: Decimal, timestamp: i64) -> FiatQuote {
+    FiatQuote {
+        timestamp,
+        provider: provider.to_string(),
+        pair: pair.to_string(),
+        price,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::mocks::MockDatabaseProvider;
+    use crate::infra::price_sources::FixedRateSource;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_cross_rates() {
+        let rates = FiatRates {
+            sol_usd: Some(dec!(150)),
+            usd_eur: Some(dec!(0.9)),
+        };
+        assert_eq!(rates.sol_to(FiatCurrency::Usd), Some(dec!(150)));
+        assert_eq!(rates.sol_to(FiatCurrency::Eur), Some(dec!(135.0)));
+
+        let no_eur = FiatRates {
+            usd_eur: None,
+            ..rates
+        };
+        assert_eq!(no_eur.sol_to(FiatCurrency::Eur), None);
+        assert_eq!(no_eur.usd_to(FiatCurrency::Usd), Some(Decimal::ONE));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_persists_once_per_interval() {
+        let saved = Arc::new(Mutex::new(Vec::new()));
+        let mut database = MockDatabaseProvider::new();
+        let sink = saved.clone();
+        database.expect_save_fiat_quote().returning(move |q| {
+            sink.lock().unwrap().push((q.pair.clone(), q.timestamp));
+            Ok(())
+        });
+
+        let aggregator = PriceAggregator::default().with_source(
+            PriceFeed::UsdEur,
+            Arc::new(FixedRateSource::new(dec!(0.92), Decimal::ONE)),
+        );
+        let service = FiatService::new(Arc::new(aggregator), Arc::new(database));
+        let settings = FiatSettings {
+            refresh_interval_secs: 300,
+            ..FiatSettings::default()
+        };
+
+        service
+            .refresh(Some(dec!(150)), 1_000, &settings)
+            .await
+            .unwrap();
+        assert_eq!(
+            service.rates().sol_to(FiatCurrency::Eur),
+            Some(dec!(138.00))
+        );
+
+        // Within the interval the in-memory rate follows the loop but nothing is written
+        service
+            .refresh(Some(dec!(151)), 1_060, &settings)
+            .await
+            .unwrap();
+        assert_eq!(service.rates().sol_usd, Some(dec!(151)));
+        assert_eq!(saved.lock().unwrap().len(), 2);
+
+        service
+            .refresh(Some(dec!(152)), 1_300, &settings)
+            .await
+            .unwrap();
+        let mut saved = saved.lock().unwrap().clone();
+        saved.sort();
+        assert_eq!(saved.len(), 4);
+        assert_eq!(saved[1], (FiatQuote::SOL_USD.to_string(), 1_300));
+        assert_eq!(saved[3], (FiatQuote::USD_EUR.to_string(), 1_300));
+
+        // A stale SOL/USD is not carried forward into fiat figures
+        service.refresh(None, 1_310, &settings).await.unwrap();
+        assert_eq!(service.rates().sol_usd, None);
+        assert_eq!(service.rates().sol_to(FiatCurrency::Eur), None);
+    }
+}
@@ -1,8 +1,12 @@
 use crate::infra::{SolanaProvider, WalletManager};
+use crate::services::cost_ledger::LAMPORTS_PER_SIGNATURE;
+use crate::services::{ChannelWidths, CostLedger, TransactionCosts};
 use crate::utils::BotSettings;
 use anyhow::{anyhow, Result};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer;
 use std::str::FromStr;
 use tracing::{info, warn};
@@ -11,18 +15,88 @@ pub struct FinancialManager {
     solana: std::sync::Arc<dyn SolanaProvider>,
     wallet_manager: std::sync::Arc<WalletManager>,
     settings: std::sync::Arc<tokio::sync::RwLock<BotSettings>>,
+    cost_ledger: std::sync::Arc<CostLedger>,
 }
 
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const SWAP_SLIPPAGE_BPS: u16 = 50; // 0.5%
+
 impl FinancialManager {
     pub fn new(
         solana: std::sync::Arc<dyn SolanaProvider>,
         wallet_manager: std::sync::Arc<WalletManager>,
         settings: std::sync::Arc<tokio::sync::RwLock<BotSettings>>,
+        cost_ledger: std::sync::Arc<CostLedger>,
     ) -> Self {
         Self {
             solana,
             wallet_manager,
             settings,
+            cost_ledger,
+        }
+    }
+
+    /// Swaps via Jupiter and books the network fee plus the shortfall of what arrived
+    /// against `expected_out` (output units at the reference price, worth `out_price_sol`
+    /// SOL each). Jupiter's own priority fee is not reported back, so on SOL outputs it
+    /// shows up as slippage.
+    async fn swap(
+        &self,
+        wallet: &Keypair,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount: u64,
+        expected_out: Decimal,
+        out_price_sol: Decimal,
+    ) -> Result<String> {
+        let before = self.output_balance(wallet, output_mint).await?;
+        let sig = self
+            .solana
+            .jupiter_swap(wallet, input_mint, output_mint, amount, SWAP_SLIPPAGE_BPS)
+            .await?;
+
+        let owner = wallet.pubkey().to_string();
+        self.cost_ledger
+            .record_transaction(
+                &sig,
+                &owner,
+                TransactionCosts {
+                    signatures: 1,
+                    ..TransactionCosts::default()
+                },
+            )
+            .await;
+        match self.output_balance(wallet, output_mint).await {
+            Ok(after) => {
+                let mut received = after - before;
+                if output_mint.to_string() == SOL_MINT {
+                    // The fee came out of the same balance
+                    received +=
+                        Decimal::from(LAMPORTS_PER_SIGNATURE) / Decimal::from(1_000_000_000u64);
+                }
+                self.cost_ledger
+                    .record_swap_slippage(&sig, &owner, expected_out, received, out_price_sol)
+                    .await;
+            }
+            Err(e) => warn!(error = %e, %sig, "swap_slippage_unmeasured"),
+        }
+        Ok(sig)
+    }
+
+    /// Balance of `mint` in whole units: SOL for the native mint, USDC otherwise.
+    async fn output_balance(&self, wallet: &Keypair, mint: &Pubkey) -> Result<Decimal> {
+        if mint.to_string() == SOL_MINT {
+            let lamports = self
+                .solana
+                .get_balance(&wallet.pubkey().to_string())
+                .await?;
+            Ok(Decimal::from(lamports) / Decimal::from(1_000_000_000u64))
+        } else {
+            let units = self
+                .solana
+                .get_token_balance(&wallet.pubkey(), mint)
+                .await?;
+            Ok(Decimal::from(units) / Decimal::from(1_000_000u64))
         }
     }
 
@@ -103,10 +177,7 @@ impl FinancialManager {
                     .to_u64()
                     .unwrap_or(0);
                 if usdc_units > 0 {
-                    let sol_mint = solana_sdk::pubkey::Pubkey::from_str(
-                        "So11111111111111111111111111111111111111112",
-                    )
-                    .unwrap();
+                    let sol_mint = Pubkey::from_str(SOL_MINT).unwrap();
                     let main_wallet = self.wallet_manager.get_main_wallet().await?;
 
                     // Check if main wallet has enough USDC
@@ -117,13 +188,13 @@ impl FinancialManager {
                     if main_usdc >= usdc_units {
                         info!(%usd_to_buy, "SOL Auto-injection: executing USDC -> SOL swap");
                         let sig = self
-                            .solana
-                            .jupiter_swap(
+                            .swap(
                                 &main_wallet,
                                 &usdc_mint,
                                 &sol_mint,
                                 usdc_units,
-                                50, // 0.5% slippage
+                                usd_to_buy / current_price,
+                                Decimal::ONE,
                             )
                             .await?;
                         info!(%sig, "Auto-injection swap successful");
@@ -139,17 +210,15 @@ impl FinancialManager {
         Ok(())
     }
 
-    pub async fn rebalance_fiat(&self, current_price: Decimal, pivot: Decimal) -> Result<()> {
-        let (buy_percent, sell_percent, _usdc_wallet_3) = {
-            let s = self.settings.read().await;
-            (
-                s.channel_bounds.buy_percent,
-                s.channel_bounds.sell_percent,
-                s.wallets.usdc_wallet_3.clone(),
-            )
-        };
-        let buy_bound = pivot * (Decimal::ONE - buy_percent);
-        let sell_bound = pivot * (Decimal::ONE + sell_percent);
+    /// `widths` are the channel widths the grid is currently built with.
+    pub async fn rebalance_fiat(
+        &self,
+        current_price: Decimal,
+        pivot: Decimal,
+        widths: ChannelWidths,
+    ) -> Result<()> {
+        let buy_bound = pivot * (Decimal::ONE - widths.buy);
+        let sell_bound = pivot * (Decimal::ONE + widths.sell);
 
         info!(
             %current_price,
@@ -159,9 +228,8 @@ impl FinancialManager {
             "Financial Manager: evaluating fiat/sol ratio"
         );
 
-        let sol_mint =
-            solana_sdk::pubkey::Pubkey::from_str("So11111111111111111111111111111111111111112")
-                .map_err(|e| anyhow::anyhow!("Failed to parse SOL mint: {}", e))?;
+        let sol_mint = Pubkey::from_str(SOL_MINT)
+            .map_err(|e| anyhow::anyhow!("Failed to parse SOL mint: {}", e))?;
         let usdc_mint = {
             let s = self.settings.read().await;
             solana_sdk::pubkey::Pubkey::from_str(&s.wallets.usdc_wallet_3)
@@ -197,13 +265,14 @@ impl FinancialManager {
                     if amount_lamports > 0 {
                         info!(%amount_usd, "Executing SELL: SOL -> USDC via Jupiter");
                         let sig = self
-                            .solana
-                            .jupiter_swap(
+                            .swap(
                                 &main_wallet,
                                 &sol_mint,
                                 &usdc_mint,
                                 amount_lamports,
-                                50, // 0.5% slippage
+                                Decimal::from(amount_lamports) / Decimal::from(1_000_000_000u64)
+                                    * current_price,
+                                Decimal::ONE / current_price,
                             )
                             .await?;
                         info!(%sig, "Swap successful");
@@ -230,8 +299,14 @@ impl FinancialManager {
                 if amount_usdc_units > 0 {
                     info!(%amount_usd, "Executing BUY: USDC -> SOL via Jupiter");
                     let sig = self
-                        .solana
-                        .jupiter_swap(&main_wallet, &usdc_mint, &sol_mint, amount_usdc_units, 50)
+                        .swap(
+                            &main_wallet,
+                            &usdc_mint,
+                            &sol_mint,
+                            amount_usdc_units,
+                            amount_usd / current_price,
+                            Decimal::ONE,
+                        )
                         .await?;
                     info!(%sig, "Swap successful");
                 }
@@ -245,15 +320,15 @@ impl FinancialManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::infra::mocks::MockSolanaProvider;
+    use crate::domain::CostKind;
+    use crate::infra::mocks::{MockDatabaseProvider, MockSolanaProvider};
     use rust_decimal_macros::dec;
-    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
 
     #[tokio::test]
     async fn test_rebalance_fiat_math_precision() {
         let mut settings = BotSettings::default();
-        settings.channel_bounds.buy_percent = dec!(0.10); // 10%
-        settings.channel_bounds.sell_percent = dec!(0.20); // 20%
         settings.financial_manager.upper_usdc_ratio_max_percent = dec!(30);
         settings.financial_manager.min_conversion_barrier_usd = dec!(10);
 
@@ -262,6 +337,23 @@ mod tests {
         mock_solana
             .expect_jupiter_swap()
             .returning(|_, _, _, _, _| Ok("sig".to_string()));
+        // 49.5 USDC arrives where 50 was expected at the reference price
+        let usdc_reads = AtomicU64::new(0);
+        mock_solana
+            .expect_get_token_balance()
+            .returning(move |_, _| {
+                Ok(usdc_reads.fetch_add(1, Ordering::SeqCst).min(1) * 49_500_000)
+            });
+
+        let costs = Arc::new(Mutex::new(Vec::new()));
+        let sink = costs.clone();
+        let mut mock_database = MockDatabaseProvider::new();
+        mock_database
+            .expect_save_cost_entries()
+            .returning(move |entries| {
+                sink.lock().unwrap().extend(entries.iter().cloned());
+                Ok(())
+            });
 
         let solana: Arc<dyn SolanaProvider> = Arc::new(mock_solana);
         let wallet_manager = Arc::new(
@@ -276,16 +368,33 @@ mod tests {
             solana,
             wallet_manager,
             Arc::new(tokio::sync::RwLock::new(settings)),
+            Arc::new(CostLedger::new(Arc::new(mock_database))),
         );
 
         // Price at pivot - no swap expected
-        let result = manager.rebalance_fiat(dec!(100), dec!(100)).await;
+        let widths = ChannelWidths {
+            buy: dec!(0.10),  // 10%
+            sell: dec!(0.20), // 20%
+        };
+        let result = manager.rebalance_fiat(dec!(100), dec!(100), widths).await;
         assert!(result.is_ok());
 
         // Price in deep SELL zone (progress = 1.0)
         // pivot 100, sell_bound 120. current 125
-        let result = manager.rebalance_fiat(dec!(125), dec!(100)).await;
+        let result = manager.rebalance_fiat(dec!(125), dec!(100), widths).await;
         assert!(result.is_ok());
+
+        // 0.5 USDC short at 125 USDC per SOL
+        let costs = costs.lock().unwrap();
+        let kinds: Vec<(CostKind, Decimal)> =
+            costs.iter().map(|e| (e.kind, e.amount_sol)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (CostKind::NetworkFee, dec!(0.000005)),
+                (CostKind::SwapSlippage, dec!(0.004)),
+            ]
+        );
     }
 
     #[test]
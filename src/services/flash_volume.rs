@@ -1,4 +1,5 @@
 use crate::infra::{SolanaProvider, WalletManager};
+use crate::services::{CostLedger, TransactionCosts};
 use crate::utils::BotSettings;
 use anyhow::{anyhow, Result};
 use rust_decimal::prelude::ToPrimitive;
@@ -11,6 +12,7 @@ pub struct FlashVolumeModule {
     solana: std::sync::Arc<dyn SolanaProvider>,
     wallet_manager: std::sync::Arc<WalletManager>,
     settings: std::sync::Arc<tokio::sync::RwLock<BotSettings>>,
+    cost_ledger: std::sync::Arc<CostLedger>,
 }
 
 impl FlashVolumeModule {
@@ -18,11 +20,13 @@ impl FlashVolumeModule {
         solana: std::sync::Arc<dyn SolanaProvider>,
         wallet_manager: std::sync::Arc<WalletManager>,
         settings: std::sync::Arc<tokio::sync::RwLock<BotSettings>>,
+        cost_ledger: std::sync::Arc<CostLedger>,
     ) -> Self {
         Self {
             solana,
             wallet_manager,
             settings,
+            cost_ledger,
         }
     }
 
@@ -127,6 +131,18 @@ impl FlashVolumeModule {
             .await?;
 
         info!(%sig, "Flash Volume: Wash trade bundle submitted successfully");
+        // Wallet A pays the fee and the tip; both wallets sign
+        self.cost_ledger
+            .record_transaction(
+                &sig,
+                &wallet_a.pubkey().to_string(),
+                TransactionCosts {
+                    signatures: 2,
+                    jito_tip_lamports: tip_lamports,
+                    ..TransactionCosts::default()
+                },
+            )
+            .await;
 
         Ok(())
     }
@@ -136,7 +152,7 @@ impl FlashVolumeModule {
 mod tests {
     use super::*;
     use crate::domain::MarketUpdate;
-    use crate::infra::mocks::MockSolanaProvider;
+    use crate::infra::mocks::{MockDatabaseProvider, MockSolanaProvider};
     use rust_decimal_macros::dec;
     use solana_sdk::pubkey::Pubkey;
     use std::sync::Arc;
@@ -159,7 +175,8 @@ mod tests {
         );
 
         let settings_arc = Arc::new(tokio::sync::RwLock::new(settings));
-        let module = FlashVolumeModule::new(solana, wallet_manager, settings_arc);
+        let cost_ledger = Arc::new(CostLedger::new(Arc::new(MockDatabaseProvider::new())));
+        let module = FlashVolumeModule::new(solana, wallet_manager, settings_arc, cost_ledger);
         let result = module.execute_cycle().await;
         assert!(result.is_ok());
     }
@@ -177,6 +194,7 @@ mod tests {
             Ok(MarketUpdate {
                 price: dec!(150.0),
                 volume_24h: dec!(1000),
+                trade_count_24h: 0,
                 timestamp: 0,
             })
         });
@@ -197,8 +215,23 @@ mod tests {
             .unwrap(),
         );
 
+        // Two signatures plus the default 0.001 SOL tip
+        let mut mock_database = MockDatabaseProvider::new();
+        mock_database
+            .expect_save_cost_entries()
+            .withf(|entries| {
+                entries.len() == 2
+                    && entries
+                        .iter()
+                        .all(|e| e.signature.as_deref() == Some("sig"))
+                    && entries[0].amount_sol == dec!(0.00001)
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+        let cost_ledger = Arc::new(CostLedger::new(Arc::new(mock_database)));
+
         let settings_arc = Arc::new(tokio::sync::RwLock::new(settings));
-        let module = FlashVolumeModule::new(solana, wallet_manager, settings_arc);
+        let module = FlashVolumeModule::new(solana, wallet_manager, settings_arc, cost_ledger);
         let result = module.execute_cycle().await;
         result.expect("Flash volume cycle failed");
     }
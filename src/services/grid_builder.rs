@@ -1,7 +1,84 @@
 use crate::domain::{GridLevel, OrderSide};
+use crate::infra::openbook::BookLayout;
+use crate::services::grid_sizing::SideBudgets;
+use crate::utils::settings::{ChannelBoundsSettings, GridSizing, GridSpacing, OrderGridSettings};
+use anyhow::{bail, Result};
 use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
 use tracing::info;
 
+/// Most levels a ladder may carry on one side.
+pub const MAX_ORDERS_PER_SIDE: u32 = 256;
+
+/// Price and size increments the market accepts, in whole SOL and tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MarketConstraints {
+    pub tick_size: Decimal,
+    /// Size increment and smallest order.
+    pub lot_size: Decimal,
+}
+
+impl MarketConstraints {
+    /// `None` when the layout carries zero lot sizes.
+    pub fn from_layout(layout: &BookLayout) -> Option<Self> {
+        let (base_decimals, quote_decimals, base_lot, quote_lot) = match layout {
+            BookLayout::V1(m) => (
+                m.base_decimals,
+                m.quote_decimals,
+                Decimal::from(m.base_lot_size),
+                Decimal::from(m.quote_lot_size),
+            ),
+            BookLayout::V2(m) => (
+                m.base_decimals,
+                m.quote_decimals,
+                Decimal::from(m.base_lot_size),
+                Decimal::from(m.quote_lot_size),
+            ),
+        };
+        if base_lot <= Decimal::ZERO || quote_lot <= Decimal::ZERO {
+            return None;
+        }
+        let base_pow = Decimal::from(10u64.pow(base_decimals as u32));
+        let quote_pow = Decimal::from(10u64.pow(quote_decimals as u32));
+        Some(Self {
+            // One price lot, as in `parse_book_side_v2`
+            tick_size: (quote_lot * base_pow) / (base_lot * quote_pow),
+            lot_size: base_lot / base_pow,
+        })
+    }
+}
+
+/// What fitting one side of a ladder to the market did to it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SideLadderReport {
+    /// Levels laid out before fitting.
+    pub levels_in: usize,
+    /// Levels left to place.
+    pub levels_out: usize,
+    /// Levels folded into a neighbour that rounded to the same price.
+    pub merged: usize,
+    /// Levels below one lot, or priced at zero, after rounding.
+    pub dropped: usize,
+    /// Levels cut back to the per-order cap, mostly after a merge.
+    pub capped: usize,
+}
+
+/// What fitting a ladder to the market did to each side.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LadderReport {
+    pub buy: SideLadderReport,
+    pub sell: SideLadderReport,
+}
+
+impl LadderReport {
+    fn side_mut(&mut self, side: OrderSide) -> &mut SideLadderReport {
+        match side {
+            OrderSide::Buy => &mut self.buy,
+            OrderSide::Sell => &mut self.sell,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GridBuilder {
     pub orders_per_side: u32,
@@ -9,6 +86,10 @@ pub struct GridBuilder {
     pub sell_channel_width: Decimal,
     pub buy_volume_multiplier: Decimal,
     pub sell_volume_multiplier: Decimal,
+    pub spacing: GridSpacing,
+    pub sizing: GridSizing,
+    /// Tick and lot sizes levels are rounded to; raw levels without them.
+    pub constraints: Option<MarketConstraints>,
 }
 
 impl Default for GridBuilder {
@@ -19,89 +100,380 @@ impl Default for GridBuilder {
             sell_channel_width: Decimal::from_str_radix("0.30", 10).unwrap(),
             buy_volume_multiplier: Decimal::new(12, 1), // 1.2
             sell_volume_multiplier: Decimal::ONE,       // 1.0
+            spacing: GridSpacing::default(),
+            sizing: GridSizing::default(),
+            constraints: None,
         }
     }
 }
 
 impl GridBuilder {
-    pub async fn build(&self, mid_price: Decimal, total_size: Decimal) -> Vec<GridLevel> {
-        if self.orders_per_side == 0 {
-            return Vec::new();
+    pub fn from_settings(grid: &OrderGridSettings, bounds: &ChannelBoundsSettings) -> Self {
+        Self {
+            orders_per_side: grid.orders_per_side,
+            buy_channel_width: bounds.buy_percent,
+            sell_channel_width: bounds.sell_percent,
+            buy_volume_multiplier: grid.buy_volume_multiplier,
+            sell_volume_multiplier: grid.sell_volume_multiplier,
+            spacing: grid.spacing.clone(),
+            sizing: grid.sizing.clone(),
+            constraints: None,
         }
+    }
 
-        let mut grid = Vec::with_capacity((self.orders_per_side * 2) as usize);
-        let orders_per_side_dec = Decimal::from(self.orders_per_side);
+    /// Rejects ladders that would cross the pivot, go below zero, carry no size or have more
+    /// than `MAX_ORDERS_PER_SIDE` levels a side.
+    pub fn validate(&self) -> Result<()> {
+        if self.orders_per_side > MAX_ORDERS_PER_SIDE {
+            bail!(
+                "orders per side must be at most {}, got {}",
+                MAX_ORDERS_PER_SIDE,
+                self.orders_per_side
+            );
+        }
+        let n = self.orders_per_side as usize;
+        if self.buy_channel_width <= Decimal::ZERO || self.buy_channel_width >= Decimal::ONE {
+            bail!(
+                "buy channel width must be in (0, 1), got {}",
+                self.buy_channel_width
+            );
+        }
+        if self.sell_channel_width <= Decimal::ZERO {
+            bail!(
+                "sell channel width must be positive, got {}",
+                self.sell_channel_width
+            );
+        }
 
-        // 1. BUY orders (Support)
-        let buy_step = (mid_price * self.buy_channel_width) / orders_per_side_dec;
-        let buy_side_total = total_size / Decimal::from(2);
+        if let GridSpacing::Custom {
+            buy_offsets,
+            sell_offsets,
+        } = &self.spacing
+        {
+            for (side, offsets) in [("buy", buy_offsets), ("sell", sell_offsets)] {
+                if offsets.len() != n {
+                    bail!(
+                        "{} offsets: expected {} levels, got {}",
+                        side,
+                        n,
+                        offsets.len()
+                    );
+                }
+                if offsets.first().is_some_and(|o| *o <= Decimal::ZERO) {
+                    bail!("{} offsets must be positive", side);
+                }
+                if offsets.windows(2).any(|w| w[1] <= w[0]) {
+                    bail!("{} offsets must be strictly increasing", side);
+                }
+            }
+            if buy_offsets.last().is_some_and(|o| *o >= Decimal::ONE) {
+                bail!("buy offsets must stay below 1 to keep prices positive");
+            }
+        }
 
-        // Calculate weights for exponential distribution
-        let mut buy_weights = Vec::with_capacity(self.orders_per_side as usize);
-        let mut buy_total_weight = Decimal::ZERO;
-        let mut current_buy_weight = Decimal::ONE;
-        for _ in 0..self.orders_per_side {
-            buy_weights.push(current_buy_weight);
-            buy_total_weight += current_buy_weight;
-            current_buy_weight *= self.buy_volume_multiplier;
+        match &self.sizing {
+            GridSizing::Flat => {}
+            GridSizing::Exponential | GridSizing::LinearRamp => {
+                for (side, mult) in [
+                    ("buy", self.buy_volume_multiplier),
+                    ("sell", self.sell_volume_multiplier),
+                ] {
+                    if mult <= Decimal::ZERO {
+                        bail!("{} volume multiplier must be positive, got {}", side, mult);
+                    }
+                    if matches!(self.sizing, GridSizing::LinearRamp)
+                        && ramp_weight(mult, n.saturating_sub(1)) <= Decimal::ZERO
+                    {
+                        bail!(
+                            "{} volume multiplier {} ramps the outer levels below zero",
+                            side,
+                            mult
+                        );
+                    }
+                }
+            }
+            GridSizing::Custom {
+                buy_weights,
+                sell_weights,
+            } => {
+                for (side, weights) in [("buy", buy_weights), ("sell", sell_weights)] {
+                    if weights.len() != n {
+                        bail!(
+                            "{} weights: expected {} levels, got {}",
+                            side,
+                            n,
+                            weights.len()
+                        );
+                    }
+                    if weights.iter().any(|w| *w < Decimal::ZERO) {
+                        bail!("{} weights must not be negative", side);
+                    }
+                    if n > 0 && weights.iter().all(|w| w.is_zero()) {
+                        bail!("{} weights must not all be zero", side);
+                    }
+                }
+            }
         }
+        Ok(())
+    }
 
-        for (idx, weight) in buy_weights.into_iter().enumerate() {
-            let i = (idx + 1) as u32;
-            let price = mid_price - (buy_step * Decimal::from(i));
-            let size = if buy_total_weight.is_zero() {
-                Decimal::ZERO
-            } else {
-                (buy_side_total / buy_total_weight) * weight
-            };
-            grid.push(GridLevel {
-                price,
-                size,
-                side: OrderSide::Buy,
-            });
-        }
-
-        // 2. SELL orders (Growth)
-        let sell_step = (mid_price * self.sell_channel_width) / orders_per_side_dec;
-        let sell_side_total = total_size / Decimal::from(2);
-
-        // Calculate weights for exponential distribution
-        let mut sell_weights = Vec::with_capacity(self.orders_per_side as usize);
-        let mut sell_total_weight = Decimal::ZERO;
-        let mut current_sell_weight = Decimal::ONE;
-        for _ in 0..self.orders_per_side {
-            sell_weights.push(current_sell_weight);
-            sell_total_weight += current_sell_weight;
-            current_sell_weight *= self.sell_volume_multiplier;
-        }
-
-        for (idx, weight) in sell_weights.into_iter().enumerate() {
-            let i = (idx + 1) as u32;
-            let price = mid_price + (sell_step * Decimal::from(i));
-            let size = if sell_total_weight.is_zero() {
-                Decimal::ZERO
-            } else {
-                (sell_side_total / sell_total_weight) * weight
+    /// Splits `total_size` tokens evenly between the sides.
+    pub async fn build(&self, mid_price: Decimal, total_size: Decimal) -> Vec<GridLevel> {
+        self.build_with_report(mid_price, total_size).await.0
+    }
+
+    /// `build`, along with what fitting the ladder to the market did to it.
+    pub async fn build_with_report(
+        &self,
+        mid_price: Decimal,
+        total_size: Decimal,
+    ) -> (Vec<GridLevel>, LadderReport) {
+        let side_total = total_size / Decimal::from(2);
+        self.assemble(mid_price, None, |_, _, share| share.of(side_total))
+    }
+
+    /// Sizes each side from what the swarm can fund: the bids spend `budgets.buy_sol` and
+    /// the asks sell `budgets.sell_tokens`. No level is worth more than `max_order_sol`.
+    pub async fn build_funded(
+        &self,
+        mid_price: Decimal,
+        budgets: SideBudgets,
+        max_order_sol: Option<Decimal>,
+    ) -> Vec<GridLevel> {
+        let (grid, _) = self.assemble(mid_price, max_order_sol, |side, price, share| {
+            if price <= Decimal::ZERO {
+                return Decimal::ZERO;
+            }
+            let size = match side {
+                OrderSide::Buy => share.of(budgets.buy_sol) / price,
+                OrderSide::Sell => share.of(budgets.sell_tokens),
             };
-            grid.push(GridLevel {
-                price,
-                size,
-                side: OrderSide::Sell,
-            });
+            match max_order_sol {
+                Some(cap) => size.min(cap / price),
+                None => size,
+            }
+        });
+        grid
+    }
+
+    /// Lays out both ladders; `size_of` gets the side, the level price and the level's
+    /// share of its side's weight.
+    fn assemble<F>(
+        &self,
+        mid_price: Decimal,
+        max_order_sol: Option<Decimal>,
+        size_of: F,
+    ) -> (Vec<GridLevel>, LadderReport)
+    where
+        F: Fn(OrderSide, Decimal, LevelShare) -> Decimal,
+    {
+        if self.orders_per_side == 0 {
+            return (Vec::new(), LadderReport::default());
+        }
+
+        let mut grid = Vec::with_capacity(self.orders_per_side as usize * 2);
+
+        // 1. BUY orders (Support), 2. SELL orders (Growth)
+        for side in [OrderSide::Buy, OrderSide::Sell] {
+            let offsets = self.offsets(side);
+            let weights = self.weights(side);
+            let total_weight: Decimal = weights.iter().sum();
+
+            for (offset, weight) in offsets.into_iter().zip(weights) {
+                let distance = mid_price * offset;
+                let price = match side {
+                    OrderSide::Buy => mid_price - distance,
+                    OrderSide::Sell => mid_price + distance,
+                };
+                let size = if total_weight.is_zero() {
+                    Decimal::ZERO
+                } else {
+                    size_of(
+                        side,
+                        price,
+                        LevelShare {
+                            weight,
+                            total_weight,
+                        },
+                    )
+                };
+                grid.push(GridLevel { price, size, side });
+            }
         }
+        let (grid, report) = self.fit_to_market(grid, max_order_sol);
 
         info!(
             ?mid_price,
-            buy_levels = self.orders_per_side,
-            sell_levels = self.orders_per_side,
+            buy_levels = report.buy.levels_out,
+            sell_levels = report.sell.levels_out,
+            merged = report.buy.merged + report.sell.merged,
+            dropped = report.buy.dropped + report.sell.dropped,
+            capped = report.buy.capped + report.sell.capped,
             buy_width = ?self.buy_channel_width,
             sell_width = ?self.sell_channel_width,
             buy_mult = ?self.buy_volume_multiplier,
             sell_mult = ?self.sell_volume_multiplier,
+            spacing = ?self.spacing,
+            sizing = ?self.sizing,
             "grid_built"
         );
 
-        grid
+        (grid, report)
+    }
+
+    /// Rounds prices outward to the tick (bids down, asks up), merges levels that land on
+    /// the same price, cuts each level back to `max_order_sol`, rounds sizes down to whole
+    /// lots and drops what is left under one lot. Without market constraints only the cap
+    /// is applied.
+    pub fn fit_to_market(
+        &self,
+        levels: Vec<GridLevel>,
+        max_order_sol: Option<Decimal>,
+    ) -> (Vec<GridLevel>, LadderReport) {
+        let mut report = LadderReport::default();
+        for level in &levels {
+            report.side_mut(level.side).levels_in += 1;
+        }
+        let cap = |level: &mut GridLevel, report: &mut LadderReport| {
+            if let Some(max) = max_order_sol.filter(|_| level.price > Decimal::ZERO) {
+                let max_size = max / level.price;
+                if level.size > max_size {
+                    level.size = max_size;
+                    report.side_mut(level.side).capped += 1;
+                }
+            }
+        };
+        let fitted: Vec<GridLevel> = match self.constraints {
+            None => levels
+                .into_iter()
+                .map(|mut level| {
+                    cap(&mut level, &mut report);
+                    level
+                })
+                .collect(),
+            Some(c) => {
+                let mut merged: Vec<GridLevel> = Vec::with_capacity(levels.len());
+                for level in levels {
+                    let ticks = level.price / c.tick_size;
+                    let price = match level.side {
+                        OrderSide::Buy => ticks.floor(),
+                        OrderSide::Sell => ticks.ceil(),
+                    } * c.tick_size;
+                    match merged
+                        .iter_mut()
+                        .find(|l| l.side == level.side && l.price == price)
+                    {
+                        Some(existing) => {
+                            existing.size += level.size;
+                            report.side_mut(level.side).merged += 1;
+                        }
+                        None => merged.push(GridLevel { price, ..level }),
+                    }
+                }
+
+                let mut fitted = Vec::with_capacity(merged.len());
+                for mut level in merged {
+                    cap(&mut level, &mut report);
+                    level.size = (level.size / c.lot_size).floor() * c.lot_size;
+                    if level.price <= Decimal::ZERO || level.size < c.lot_size {
+                        report.side_mut(level.side).dropped += 1;
+                    } else {
+                        fitted.push(level);
+                    }
+                }
+                fitted
+            }
+        };
+        for level in &fitted {
+            report.side_mut(level.side).levels_out += 1;
+        }
+        (fitted, report)
+    }
+
+    /// Distance of each level from the pivot as a fraction of it, nearest first.
+    fn offsets(&self, side: OrderSide) -> Vec<Decimal> {
+        let n = self.orders_per_side;
+        let width = match side {
+            OrderSide::Buy => self.buy_channel_width,
+            OrderSide::Sell => self.sell_channel_width,
+        };
+        match &self.spacing {
+            GridSpacing::Linear => {
+                let step = width / Decimal::from(n);
+                (1..=n).map(|i| step * Decimal::from(i)).collect()
+            }
+            GridSpacing::Geometric => {
+                // Constant ratio between neighbouring prices; the last level lands on the
+                // channel edge
+                let edge = match side {
+                    OrderSide::Buy => Decimal::ONE - width,
+                    OrderSide::Sell => Decimal::ONE + width,
+                };
+                let ratio = edge
+                    .to_f64()
+                    .map(|e| e.powf(1.0 / n as f64))
+                    .and_then(Decimal::from_f64)
+                    .unwrap_or(Decimal::ONE);
+                let mut factor = Decimal::ONE;
+                (1..=n)
+                    .map(|i| {
+                        factor = if i == n { edge } else { factor * ratio };
+                        (factor - Decimal::ONE).abs()
+                    })
+                    .collect()
+            }
+            GridSpacing::Fibonacci => {
+                let (mut a, mut b) = (Decimal::ZERO, Decimal::ONE);
+                let mut cumulative = Decimal::ZERO;
+                let steps: Vec<Decimal> = (0..n)
+                    .map(|_| {
+                        cumulative += b;
+                        (a, b) = (b, a + b);
+                        cumulative
+                    })
+                    .collect();
+                steps.into_iter().map(|c| width * c / cumulative).collect()
+            }
+            GridSpacing::Custom {
+                buy_offsets,
+                sell_offsets,
+            } => match side {
+                OrderSide::Buy => buy_offsets.clone(),
+                OrderSide::Sell => sell_offsets.clone(),
+            },
+        }
+    }
+
+    /// Relative size of each level, nearest first.
+    fn weights(&self, side: OrderSide) -> Vec<Decimal> {
+        let n = self.orders_per_side as usize;
+        let mult = match side {
+            OrderSide::Buy => self.buy_volume_multiplier,
+            OrderSide::Sell => self.sell_volume_multiplier,
+        };
+        match &self.sizing {
+            GridSizing::Flat => vec![Decimal::ONE; n],
+            GridSizing::Exponential => {
+                let mut weight = Decimal::ONE;
+                (0..n)
+                    .map(|_| {
+                        let current = weight;
+                        weight *= mult;
+                        current
+                    })
+                    .collect()
+            }
+            GridSizing::LinearRamp => (0..n)
+                .map(|i| ramp_weight(mult, i).max(Decimal::ZERO))
+                .collect(),
+            GridSizing::Custom {
+                buy_weights,
+                sell_weights,
+            } => match side {
+                OrderSide::Buy => buy_weights.clone(),
+                OrderSide::Sell => sell_weights.clone(),
+            },
+        }
     }
 
     pub fn apply_front_running_protection(
@@ -111,12 +483,21 @@ impl GridBuilder {
         large_order_threshold: Decimal,
         tick_size: Decimal,
     ) {
+        // Step in whole market ticks, or `fit_to_market` rounds the level back behind
+        let tick_size = match self.constraints {
+            Some(c) => (tick_size / c.tick_size).ceil().max(Decimal::ONE) * c.tick_size,
+            None => tick_size,
+        };
+        // Only external liquidity counts as competition; our own orders must not be chased
+        let external_bids = orderbook.external_bids();
+        let external_asks = orderbook.external_asks();
+
         for level in levels.iter_mut() {
             match level.side {
                 OrderSide::Buy => {
                     // Find the best bid that is >= large_order_threshold and slightly above or near our price
                     // We want to be 1-tick ahead of the highest large competitor that is BELOW the mid-price
-                    if let Some(competitor) = orderbook.bids.iter().find(|b| {
+                    if let Some(competitor) = external_bids.iter().find(|b| {
                         b.size >= large_order_threshold
                             && b.price <= level.price * Decimal::from_str_radix("1.05", 10).unwrap()
                     }) {
@@ -128,7 +509,7 @@ impl GridBuilder {
                 }
                 OrderSide::Sell => {
                     // Find the best ask that is >= large_order_threshold and slightly below or near our price
-                    if let Some(competitor) = orderbook.asks.iter().find(|a| {
+                    if let Some(competitor) = external_asks.iter().find(|a| {
                         a.size >= large_order_threshold
                             && a.price >= level.price * Decimal::from_str_radix("0.95", 10).unwrap()
                     }) {
@@ -143,6 +524,24 @@ impl GridBuilder {
     }
 }
 
+#[derive(Clone, Copy)]
+struct LevelShare {
+    weight: Decimal,
+    total_weight: Decimal,
+}
+
+impl LevelShare {
+    fn of(self, amount: Decimal) -> Decimal {
+        (amount / self.total_weight) * self.weight
+    }
+}
+
+/// Weight of level `i` (0-based) on a linear ramp: the first level is 1 and each further
+/// level adds `mult - 1`.
+fn ramp_weight(mult: Decimal, i: usize) -> Decimal {
+    Decimal::ONE + Decimal::from(i) * (mult - Decimal::ONE)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,6 +554,7 @@ mod tests {
             sell_channel_width: Decimal::from_str_radix("0.1", 10).unwrap(),
             buy_volume_multiplier: Decimal::ONE,
             sell_volume_multiplier: Decimal::ONE,
+            ..Default::default()
         };
 
         // Using tokio::test would be better if build was truly async,
@@ -185,6 +585,7 @@ mod tests {
             sell_channel_width: Decimal::from_str_radix("0.20", 10).unwrap(),
             buy_volume_multiplier: Decimal::ONE,
             sell_volume_multiplier: Decimal::ONE,
+            ..Default::default()
         };
 
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -216,6 +617,7 @@ mod tests {
             sell_channel_width: Decimal::from_str_radix("0.20", 10).unwrap(), // 20%
             buy_volume_multiplier: Decimal::ONE,
             sell_volume_multiplier: Decimal::ONE,
+            ..Default::default()
         };
         let rt = tokio::runtime::Runtime::new().unwrap();
         let grid = rt.block_on(builder.build(Decimal::from(100), Decimal::from(10)));
@@ -238,4 +640,335 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_geometric_and_fibonacci_spacing() {
+        let builder = GridBuilder {
+            orders_per_side: 2,
+            buy_channel_width: Decimal::from_str_radix("0.19", 10).unwrap(),
+            sell_channel_width: Decimal::from_str_radix("0.21", 10).unwrap(),
+            sizing: GridSizing::Flat,
+            spacing: GridSpacing::Geometric,
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let grid = rt.block_on(builder.build(Decimal::from(100), Decimal::from(10)));
+
+        // Buy ratio 0.9: 90, 81; sell ratio 1.1: 110, 121
+        let prices: Vec<Decimal> = grid.iter().map(|l| l.price.round_dp(6)).collect();
+        assert_eq!(
+            prices,
+            vec![
+                Decimal::from(90),
+                Decimal::from(81),
+                Decimal::from(110),
+                Decimal::from(121)
+            ]
+        );
+
+        let fib = GridBuilder {
+            orders_per_side: 4,
+            buy_channel_width: Decimal::from_str_radix("0.07", 10).unwrap(),
+            spacing: GridSpacing::Fibonacci,
+            ..builder
+        };
+        let grid = rt.block_on(fib.build(Decimal::from(100), Decimal::from(10)));
+        // Steps 1, 1, 2, 3 scaled to the 7% channel
+        let buys: Vec<Decimal> = grid
+            .iter()
+            .filter(|l| l.side == OrderSide::Buy)
+            .map(|l| l.price)
+            .collect();
+        assert_eq!(
+            buys,
+            vec![
+                Decimal::from(99),
+                Decimal::from(98),
+                Decimal::from(96),
+                Decimal::from(93)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_ladder_and_sizing_modes() {
+        let offsets = |v: &[&str]| -> Vec<Decimal> {
+            v.iter()
+                .map(|s| Decimal::from_str_radix(s, 10).unwrap())
+                .collect()
+        };
+        let builder = GridBuilder {
+            orders_per_side: 3,
+            spacing: GridSpacing::Custom {
+                buy_offsets: offsets(&["0.01", "0.05", "0.2"]),
+                sell_offsets: offsets(&["0.02", "0.1", "0.5"]),
+            },
+            sizing: GridSizing::Custom {
+                buy_weights: offsets(&["1", "1", "2"]),
+                sell_weights: offsets(&["0", "1", "3"]),
+            },
+            ..Default::default()
+        };
+        builder.validate().unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let grid = rt.block_on(builder.build(Decimal::from(100), Decimal::from(16)));
+        let levels: Vec<(Decimal, Decimal)> = grid.iter().map(|l| (l.price, l.size)).collect();
+        assert_eq!(
+            levels,
+            vec![
+                (Decimal::from(99), Decimal::from(2)),
+                (Decimal::from(95), Decimal::from(2)),
+                (Decimal::from(80), Decimal::from(4)),
+                (Decimal::from(102), Decimal::ZERO),
+                (Decimal::from(110), Decimal::from(2)),
+                (Decimal::from(150), Decimal::from(6)),
+            ]
+        );
+
+        // Linear ramp with multiplier 2: weights 1, 2, 3
+        let ramp = GridBuilder {
+            orders_per_side: 3,
+            buy_volume_multiplier: Decimal::from(2),
+            sizing: GridSizing::LinearRamp,
+            ..Default::default()
+        };
+        let grid = rt.block_on(ramp.build(Decimal::from(100), Decimal::from(12)));
+        let sizes: Vec<Decimal> = grid.iter().take(3).map(|l| l.size).collect();
+        assert_eq!(
+            sizes,
+            vec![Decimal::from(1), Decimal::from(2), Decimal::from(3)]
+        );
+    }
+
+    #[test]
+    fn test_funded_grid_spends_side_budgets() {
+        let builder = GridBuilder {
+            orders_per_side: 2,
+            buy_channel_width: Decimal::from_str_radix("0.5", 10).unwrap(),
+            sell_channel_width: Decimal::from_str_radix("1", 10).unwrap(),
+            sizing: GridSizing::Flat,
+            ..Default::default()
+        };
+        let budgets = SideBudgets {
+            buy_sol: Decimal::from(10),
+            sell_tokens: Decimal::from(8),
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let grid = rt.block_on(builder.build_funded(Decimal::from(4), budgets, None));
+
+        // Bids at 3 and 2 spend 5 SOL each; asks at 6 and 8 sell 4 tokens each
+        let sizes: Vec<Decimal> = grid.iter().map(|l| l.size.round_dp(6)).collect();
+        let expected: Vec<Decimal> = ["1.666667", "2.5", "4", "4"]
+            .iter()
+            .map(|s| Decimal::from_str_radix(s, 10).unwrap())
+            .collect();
+        assert_eq!(sizes, expected);
+        let spent: Decimal = grid
+            .iter()
+            .filter(|l| l.side == OrderSide::Buy)
+            .map(|l| l.size * l.price)
+            .sum();
+        assert_eq!(spent.round_dp(6), Decimal::from(10));
+
+        // At most 12 SOL per order: the 4-token ask at 8 is cut to 1.5 tokens
+        let capped =
+            rt.block_on(builder.build_funded(Decimal::from(4), budgets, Some(Decimal::from(12))));
+        assert_eq!(capped[2].size, Decimal::from(2));
+        assert_eq!(capped[3].size, Decimal::from_str_radix("1.5", 10).unwrap());
+    }
+
+    #[test]
+    fn test_fit_to_market_rounds_merges_and_drops_dust() {
+        use crate::infra::openbook::MarketStateV2;
+        use solana_sdk::pubkey::Pubkey;
+
+        let layout = BookLayout::V2(MarketStateV2 {
+            bump: 0,
+            base_decimals: 6,
+            quote_decimals: 9,
+            market_authority: Pubkey::new_unique(),
+            bids: Pubkey::new_unique(),
+            asks: Pubkey::new_unique(),
+            event_heap: Pubkey::new_unique(),
+            market_base_vault: Pubkey::new_unique(),
+            market_quote_vault: Pubkey::new_unique(),
+            base_lot_size: 1_000,
+            quote_lot_size: 1,
+        });
+        let constraints = MarketConstraints::from_layout(&layout).unwrap();
+        assert_eq!(constraints.tick_size, Decimal::new(1, 6));
+        assert_eq!(constraints.lot_size, Decimal::new(1, 3));
+
+        let d = |s: &str| Decimal::from_str_radix(s, 10).unwrap();
+        let level = |price: &str, size: &str, side| GridLevel {
+            price: d(price),
+            size: d(size),
+            side,
+        };
+        let builder = GridBuilder {
+            constraints: Some(MarketConstraints {
+                tick_size: d("0.5"),
+                lot_size: Decimal::ONE,
+            }),
+            ..Default::default()
+        };
+        // Neither bid alone is worth 25 SOL, but the merged one is
+        let max_order_sol = Some(d("25"));
+        let (levels, report) = builder.fit_to_market(
+            vec![
+                level("9.7", "2.4", OrderSide::Buy),
+                // Rounds onto the level above and is merged into it
+                level("9.6", "0.8", OrderSide::Buy),
+                // Rounds to a zero price
+                level("0.3", "5", OrderSide::Buy),
+                // Less than a lot
+                level("10.2", "0.4", OrderSide::Sell),
+                level("10.9", "1", OrderSide::Sell),
+            ],
+            max_order_sol,
+        );
+
+        assert_eq!(
+            levels,
+            vec![
+                level("9.5", "2", OrderSide::Buy),
+                level("11", "1", OrderSide::Sell)
+            ]
+        );
+        assert_eq!(
+            report,
+            LadderReport {
+                buy: SideLadderReport {
+                    levels_in: 3,
+                    levels_out: 1,
+                    merged: 1,
+                    dropped: 1,
+                    capped: 1,
+                },
+                sell: SideLadderReport {
+                    levels_in: 2,
+                    levels_out: 1,
+                    merged: 0,
+                    dropped: 1,
+                    capped: 0,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_ladders() {
+        let custom = |buy: Vec<i64>, sell: Vec<i64>| GridBuilder {
+            orders_per_side: 2,
+            spacing: GridSpacing::Custom {
+                buy_offsets: buy.into_iter().map(|p| Decimal::new(p, 2)).collect(),
+                sell_offsets: sell.into_iter().map(|p| Decimal::new(p, 2)).collect(),
+            },
+            ..Default::default()
+        };
+        assert!(custom(vec![1, 5], vec![2, 10]).validate().is_ok());
+        // Wrong length, not increasing, buy price at or below zero
+        assert!(custom(vec![1], vec![2, 10]).validate().is_err());
+        assert!(custom(vec![5, 1], vec![2, 10]).validate().is_err());
+        assert!(custom(vec![1, 100], vec![2, 10]).validate().is_err());
+
+        let ramp = GridBuilder {
+            orders_per_side: 4,
+            sell_volume_multiplier: Decimal::new(5, 1),
+            sizing: GridSizing::LinearRamp,
+            ..Default::default()
+        };
+        // 1, 0.5, 0, -0.5
+        assert!(ramp.validate().is_err());
+
+        let zero_weights = GridBuilder {
+            orders_per_side: 1,
+            sizing: GridSizing::Custom {
+                buy_weights: vec![Decimal::ONE],
+                sell_weights: vec![Decimal::ZERO],
+            },
+            ..Default::default()
+        };
+        assert!(zero_weights.validate().is_err());
+
+        let too_many = GridBuilder {
+            orders_per_side: MAX_ORDERS_PER_SIDE + 1,
+            ..Default::default()
+        };
+        assert!(too_many.validate().is_err());
+        assert!(GridBuilder {
+            orders_per_side: u32::MAX,
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+        assert!(GridBuilder::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_front_running_ignores_own_orders() {
+        use crate::domain::{Orderbook, OrderbookOrder};
+        use std::collections::HashSet;
+
+        let order = |owner: &str, price: Decimal| OrderbookOrder {
+            order_id: 0,
+            owner: owner.to_string(),
+            client_order_id: 0,
+            timestamp: None,
+            price,
+            size: Decimal::from(50),
+            is_own: false,
+        };
+        let mut orderbook = Orderbook::from_orders(
+            "TEST_MARKET",
+            0,
+            vec![
+                order("swarm_oo", Decimal::from(99)),
+                order("other_oo", Decimal::from(97)),
+            ],
+            vec![],
+        );
+        orderbook.mark_own_orders(&HashSet::from(["swarm_oo".to_string()]));
+
+        let builder = GridBuilder::default();
+        let mut levels = vec![GridLevel {
+            price: Decimal::from(98),
+            size: Decimal::ONE,
+            side: OrderSide::Buy,
+        }];
+        builder.apply_front_running_protection(
+            &mut levels,
+            &orderbook,
+            Decimal::from(10),
+            Decimal::ONE,
+        );
+
+        // Steps ahead of the external 97 bid, not our own 99 bid
+        assert_eq!(levels[0].price, Decimal::from(98));
+        assert_eq!(orderbook.external_bids().len(), 1);
+        assert_eq!(orderbook.bids[0].own_size, Decimal::from(50));
+
+        // A 0.1 step on a 0.5-tick market still lands a tick ahead once fitted
+        let d = |s: &str| Decimal::from_str_radix(s, 10).unwrap();
+        let builder = GridBuilder {
+            constraints: Some(MarketConstraints {
+                tick_size: d("0.5"),
+                lot_size: d("0.1"),
+            }),
+            ..Default::default()
+        };
+        let mut levels = vec![GridLevel {
+            price: Decimal::from(98),
+            size: Decimal::ONE,
+            side: OrderSide::Buy,
+        }];
+        builder.apply_front_running_protection(
+            &mut levels,
+            &orderbook,
+            Decimal::from(10),
+            d("0.1"),
+        );
+        let (levels, _) = builder.fit_to_market(levels, None);
+        assert_eq!(levels[0].price, d("97.5"));
+    }
 }
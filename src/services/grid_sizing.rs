@@ -0,0 +1,94 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+
+/// Balances of one swarm wallet, in whole SOL and tokens.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletBalance {
+    pub wallet: Pubkey,
+    pub sol: Decimal,
+    pub token: Decimal,
+    /// Whether the wallet already has an OpenOrders account on the market; if not, its
+    /// rent has to be kept back.
+    pub has_open_orders: bool,
+}
+
+/// SOL every wallet keeps out of the grid.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SolReserves {
+    pub fee_reserve_sol: Decimal,
+    /// Kept back by wallets that still have to open an OpenOrders account.
+    pub account_rent_sol: Decimal,
+    /// Share of the remaining SOL the financial manager keeps out of the market.
+    pub min_sol_reserve_percent: Decimal,
+}
+
+impl WalletBalance {
+    /// SOL this wallet may post to the bids.
+    pub fn deployable_sol(&self, reserves: &SolReserves) -> Decimal {
+        let rent = if self.has_open_orders {
+            Decimal::ZERO
+        } else {
+            reserves.account_rent_sol
+        };
+        let free = (self.sol - reserves.fee_reserve_sol - rent).max(Decimal::ZERO);
+        let deployable = (Decimal::ONE - reserves.min_sol_reserve_percent / Decimal::from(100))
+            .clamp(Decimal::ZERO, Decimal::ONE);
+        free * deployable
+    }
+}
+
+/// What each side of the grid can be funded with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct SideBudgets {
+    /// SOL the bids may spend.
+    pub buy_sol: Decimal,
+    /// Tokens the asks may sell.
+    pub sell_tokens: Decimal,
+}
+
+impl SideBudgets {
+    /// Deployable SOL across the swarm against every token it holds.
+    pub fn from_balances(balances: &[WalletBalance], reserves: &SolReserves) -> Self {
+        Self {
+            buy_sol: balances.iter().map(|b| b.deployable_sol(reserves)).sum(),
+            sell_tokens: balances.iter().map(|b| b.token.max(Decimal::ZERO)).sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn balance(sol: Decimal, token: Decimal, has_open_orders: bool) -> WalletBalance {
+        WalletBalance {
+            wallet: Pubkey::new_unique(),
+            sol,
+            token,
+            has_open_orders,
+        }
+    }
+
+    #[test]
+    fn test_budgets_keep_fee_rent_and_sol_reserves() {
+        let balances = [
+            balance(dec!(2), dec!(1000), true),
+            // Still has to pay OpenOrders rent
+            balance(dec!(1.1), dec!(500), false),
+            // Not even enough for fees
+            balance(dec!(0.01), Decimal::ZERO, true),
+        ];
+
+        let reserves = SolReserves {
+            fee_reserve_sol: dec!(0.05),
+            account_rent_sol: dec!(0.05),
+            min_sol_reserve_percent: dec!(70),
+        };
+        let budgets = SideBudgets::from_balances(&balances, &reserves);
+        // (1.95 + 1.0) * 30%
+        assert_eq!(budgets.buy_sol, dec!(0.885));
+        assert_eq!(budgets.sell_tokens, dec!(1500));
+    }
+}
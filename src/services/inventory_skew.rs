@@ -0,0 +1,174 @@
+use crate::services::grid_sizing::SideBudgets;
+use crate::utils::settings::InventorySkewSettings;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+/// How far the grid leans to shed inventory. The neutral value leaves the grid symmetric
+/// around the pivot.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct InventorySkew {
+    /// Deviation from the target token share: -1 when all value sits in SOL, 1 when all of
+    /// it sits in the token.
+    pub deviation: Decimal,
+    /// Signed shift of the grid centre as a fraction of the pivot.
+    pub centre_shift: Decimal,
+    pub buy_size_factor: Decimal,
+    pub sell_size_factor: Decimal,
+    /// Largest factor either side can get; neutral sizes are kept this far below the
+    /// budgets so the shedding side has room to grow.
+    pub max_size_factor: Decimal,
+}
+
+impl Default for InventorySkew {
+    fn default() -> Self {
+        Self {
+            deviation: Decimal::ZERO,
+            centre_shift: Decimal::ZERO,
+            buy_size_factor: Decimal::ONE,
+            sell_size_factor: Decimal::ONE,
+            max_size_factor: Decimal::ONE,
+        }
+    }
+}
+
+impl InventorySkew {
+    /// `token_balance` and `sol_balance` are what the swarm's wallets hold, in whole units;
+    /// the token is valued at `pivot`.
+    pub fn compute(
+        token_balance: Decimal,
+        sol_balance: Decimal,
+        pivot: Decimal,
+        settings: &InventorySkewSettings,
+    ) -> Self {
+        let token_value = token_balance.max(Decimal::ZERO) * pivot;
+        let total_value = token_value + sol_balance.max(Decimal::ZERO);
+        let target = settings
+            .target_token_share
+            .clamp(Decimal::ZERO, Decimal::ONE);
+        if total_value <= Decimal::ZERO {
+            return Self::default();
+        }
+
+        // Normalised so that holding only one asset is a full deviation either way
+        let share = token_value / total_value;
+        let room = if share >= target {
+            Decimal::ONE - target
+        } else {
+            target
+        };
+        let deviation = if room.is_zero() {
+            Decimal::ZERO
+        } else {
+            ((share - target) / room).clamp(-Decimal::ONE, Decimal::ONE)
+        };
+
+        let hundred = Decimal::from(100);
+        let max_shift = settings.max_centre_shift_percent.max(Decimal::ZERO) / hundred;
+        let centre_shift =
+            (-deviation * settings.risk_aversion_percent / hundred).clamp(-max_shift, max_shift);
+        let max_size_skew =
+            (settings.max_size_skew_percent / hundred).clamp(Decimal::ZERO, Decimal::ONE);
+        let size_skew = deviation * max_size_skew;
+
+        Self {
+            deviation,
+            centre_shift,
+            buy_size_factor: Decimal::ONE - size_skew,
+            sell_size_factor: Decimal::ONE + size_skew,
+            max_size_factor: Decimal::ONE + max_size_skew,
+        }
+    }
+
+    /// Price the grid is centred on instead of the pivot.
+    pub fn reservation_price(&self, pivot: Decimal) -> Decimal {
+        pivot * (Decimal::ONE + self.centre_shift)
+    }
+
+    /// Moves size from the accumulating side to the shedding side. The budgets are all the
+    /// swarm can fund, so neutral sizes sit at `1 / max_size_factor` of them and a side only
+    /// reaches its full budget at the largest skew.
+    pub fn apply_budgets(&self, budgets: SideBudgets) -> SideBudgets {
+        let scale = |budget: Decimal, factor: Decimal| {
+            if self.max_size_factor > Decimal::ZERO {
+                (budget * factor.max(Decimal::ZERO) / self.max_size_factor).min(budget)
+            } else {
+                budget
+            }
+        };
+        SideBudgets {
+            buy_sol: scale(budgets.buy_sol, self.buy_size_factor),
+            sell_tokens: scale(budgets.sell_tokens, self.sell_size_factor),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn settings() -> InventorySkewSettings {
+        InventorySkewSettings {
+            enabled: true,
+            ..InventorySkewSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_balanced_inventory_keeps_grid_symmetric() {
+        // 1000 tokens at 0.01 against 10 SOL
+        let skew = InventorySkew::compute(dec!(1000), dec!(10), dec!(0.01), &settings());
+        assert_eq!(skew.deviation, Decimal::ZERO);
+        assert_eq!(skew.buy_size_factor, Decimal::ONE);
+        assert_eq!(skew.sell_size_factor, Decimal::ONE);
+        assert_eq!(skew.reservation_price(dec!(0.01)), dec!(0.01));
+        // Neutral sizes leave room for the 50% size skew
+        let budgets = skew.apply_budgets(SideBudgets {
+            buy_sol: dec!(15),
+            sell_tokens: dec!(1500),
+        });
+        assert_eq!(budgets.buy_sol, dec!(10));
+        assert_eq!(budgets.sell_tokens, dec!(1000));
+
+        // Without skew the budgets are used in full
+        let unskewed = SideBudgets {
+            buy_sol: dec!(15),
+            sell_tokens: dec!(1500),
+        };
+        assert_eq!(InventorySkew::default().apply_budgets(unskewed), unskewed);
+
+        let empty = InventorySkew::compute(Decimal::ZERO, Decimal::ZERO, dec!(0.01), &settings());
+        assert_eq!(empty, InventorySkew::default());
+    }
+
+    #[test]
+    fn test_excess_token_lowers_centre_and_favours_sells() {
+        // 75% of the value in the token: half of the room above the 50% target
+        let skew = InventorySkew::compute(dec!(3000), dec!(10), dec!(0.01), &settings());
+        assert_eq!(skew.deviation, dec!(0.5));
+        assert_eq!(skew.centre_shift, dec!(-0.025));
+        assert_eq!(skew.reservation_price(dec!(100)), dec!(97.5));
+
+        // Against neutral sizes of 10 SOL and 1000 tokens, a quarter moves to the asks
+        assert_eq!(skew.sell_size_factor, dec!(1.25));
+        let budgets = skew.apply_budgets(SideBudgets {
+            buy_sol: dec!(15),
+            sell_tokens: dec!(1500),
+        });
+        assert_eq!(budgets.buy_sol, dec!(7.5));
+        assert_eq!(budgets.sell_tokens, dec!(1250));
+
+        // Only SOL left: the shift is capped
+        let all_sol = InventorySkew::compute(Decimal::ZERO, dec!(10), dec!(0.01), &settings());
+        assert_eq!(all_sol.deviation, dec!(-1));
+        assert_eq!(all_sol.centre_shift, dec!(0.03));
+        assert_eq!(all_sol.buy_size_factor, dec!(1.5));
+        // At full deviation the shedding side gets its whole budget and no more
+        let budgets = all_sol.apply_budgets(SideBudgets {
+            buy_sol: dec!(15),
+            sell_tokens: dec!(1500),
+        });
+        assert_eq!(budgets.buy_sol, dec!(15));
+        assert_eq!(budgets.sell_tokens, dec!(500));
+    }
+}
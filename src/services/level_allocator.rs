@@ -0,0 +1,438 @@
+use crate::domain::{GridLevel, OrderSide};
+use crate::services::grid_sizing::{SolReserves, WalletBalance};
+use crate::utils::settings::{AllocationSettings, AllocationStrategy};
+use rust_decimal::Decimal;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Grid levels paired with the wallet that places them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LevelAllocation {
+    pub assignments: Vec<(Pubkey, GridLevel)>,
+    /// Levels no wallet could take.
+    pub unallocated: usize,
+    /// Levels no single wallet could fund, spread over several.
+    pub split: usize,
+}
+
+/// What a wallet can still take on while levels are handed out.
+struct WalletCapacity {
+    wallet: Pubkey,
+    sol: Decimal,
+    tokens: Decimal,
+    orders: u32,
+    exposure: Option<Decimal>,
+}
+
+impl WalletCapacity {
+    fn can_fund(&self, level: &GridLevel) -> bool {
+        let value = level.size * level.price;
+        self.orders > 0
+            && self.exposure.is_none_or(|e| e >= value)
+            && match level.side {
+                OrderSide::Buy => self.sol >= value,
+                OrderSide::Sell => self.tokens >= level.size,
+            }
+    }
+
+    fn take(&mut self, level: &GridLevel) {
+        let value = level.size * level.price;
+        self.orders -= 1;
+        if let Some(e) = self.exposure.as_mut() {
+            *e -= value;
+        }
+        match level.side {
+            OrderSide::Buy => self.sol -= value,
+            OrderSide::Sell => self.tokens -= level.size,
+        }
+    }
+
+    /// Largest part of `level` the wallet can fund, in whole lots when `lot_size` is known.
+    fn fundable_size(&self, level: &GridLevel, lot_size: Option<Decimal>) -> Decimal {
+        if self.orders == 0 || level.price <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        let value = match level.side {
+            OrderSide::Buy => self.sol,
+            OrderSide::Sell => self.tokens * level.price,
+        };
+        let value = self.exposure.map_or(value, |e| value.min(e));
+        let size = (value / level.price).min(level.size).max(Decimal::ZERO);
+        match lot_size.filter(|lot| *lot > Decimal::ZERO) {
+            Some(lot) => (size / lot).floor() * lot,
+            None => size,
+        }
+    }
+
+    /// Balance the level would draw on; the wallet with the most of it is preferred.
+    fn headroom(&self, side: OrderSide) -> Decimal {
+        match side {
+            OrderSide::Buy => self.sol,
+            OrderSide::Sell => self.tokens,
+        }
+    }
+}
+
+/// Assigns grid levels to swarm wallets. Remembers which wallet took each ladder slot
+/// (side and distance rank from the centre) so a rebuild keeps orders where they were.
+#[derive(Default)]
+pub struct LevelAllocator {
+    previous: Mutex<HashMap<(OrderSide, usize), Pubkey>>,
+}
+
+impl LevelAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `levels` are nearest first per side, as `GridBuilder` lays them out; `balances` list
+    /// the wallets in swarm order. Parts of split levels are whole `lot_size` lots. `now`
+    /// drives the rotation strategy.
+    pub fn allocate(
+        &self,
+        levels: &[GridLevel],
+        balances: &[WalletBalance],
+        reserves: &SolReserves,
+        settings: &AllocationSettings,
+        lot_size: Option<Decimal>,
+        now: i64,
+    ) -> LevelAllocation {
+        match settings.strategy {
+            AllocationStrategy::Rotation => {
+                rotate(levels, balances, settings.max_orders_per_wallet, now)
+            }
+            AllocationStrategy::BalanceAware => {
+                self.allocate_by_balance(levels, balances, reserves, settings, lot_size)
+            }
+        }
+    }
+
+    fn allocate_by_balance(
+        &self,
+        levels: &[GridLevel],
+        balances: &[WalletBalance],
+        reserves: &SolReserves,
+        settings: &AllocationSettings,
+        lot_size: Option<Decimal>,
+    ) -> LevelAllocation {
+        let mut capacities: Vec<WalletCapacity> = balances
+            .iter()
+            .map(|b| WalletCapacity {
+                wallet: b.wallet,
+                sol: b.deployable_sol(reserves),
+                tokens: b.token.max(Decimal::ZERO),
+                orders: settings.max_orders_per_wallet,
+                exposure: (settings.max_wallet_exposure_sol > Decimal::ZERO)
+                    .then_some(settings.max_wallet_exposure_sol),
+            })
+            .collect();
+
+        let mut previous = self.previous.lock().unwrap_or_else(|e| e.into_inner());
+        let mut next = HashMap::new();
+        let mut ranks: HashMap<OrderSide, usize> = HashMap::new();
+        let mut allocation = LevelAllocation::default();
+
+        for level in levels {
+            let rank = ranks.entry(level.side).or_default();
+            let slot = (level.side, *rank);
+            *rank += 1;
+
+            let sticky = previous
+                .get(&slot)
+                .filter(|_| settings.sticky)
+                .and_then(|wallet| {
+                    capacities
+                        .iter()
+                        .position(|c| c.wallet == *wallet && c.can_fund(level))
+                });
+            let chosen = sticky.or_else(|| {
+                capacities
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| c.can_fund(level))
+                    .max_by_key(|(_, c)| c.headroom(level.side))
+                    .map(|(i, _)| i)
+            });
+
+            match chosen {
+                Some(i) => {
+                    let capacity = &mut capacities[i];
+                    capacity.take(level);
+                    next.insert(slot, capacity.wallet);
+                    allocation
+                        .assignments
+                        .push((capacity.wallet, level.clone()));
+                }
+                // The grid is sized from the whole swarm's balances, so a level can be
+                // larger than any one wallet holds
+                None => {
+                    let parts = split(&mut capacities, level, lot_size);
+                    match parts.first() {
+                        Some((wallet, _)) => {
+                            next.insert(slot, *wallet);
+                            allocation.split += 1;
+                            allocation.assignments.extend(parts);
+                        }
+                        None => allocation.unallocated += 1,
+                    }
+                }
+            }
+        }
+
+        *previous = next;
+        allocation
+    }
+}
+
+/// Spreads `level` over the wallets with the most headroom, largest part first. Whatever
+/// the wallets cannot fund between them is left out.
+fn split(
+    capacities: &mut [WalletCapacity],
+    level: &GridLevel,
+    lot_size: Option<Decimal>,
+) -> Vec<(Pubkey, GridLevel)> {
+    let mut order: Vec<usize> = (0..capacities.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(capacities[i].headroom(level.side)));
+
+    let mut parts = Vec::new();
+    let mut remaining = level.clone();
+    for i in order {
+        if remaining.size <= Decimal::ZERO {
+            break;
+        }
+        let size = capacities[i].fundable_size(&remaining, lot_size);
+        if size <= Decimal::ZERO {
+            continue;
+        }
+        let part = GridLevel {
+            size,
+            ..level.clone()
+        };
+        capacities[i].take(&part);
+        remaining.size -= size;
+        parts.push((capacities[i].wallet, part));
+    }
+    parts
+}
+
+/// Consecutive chunks of `per_wallet` levels, starting at a wallet that moves every tick.
+fn rotate(
+    levels: &[GridLevel],
+    balances: &[WalletBalance],
+    per_wallet: u32,
+    now: i64,
+) -> LevelAllocation {
+    let per_wallet = per_wallet.max(1) as usize;
+    if balances.is_empty() {
+        return LevelAllocation {
+            assignments: Vec::new(),
+            unallocated: levels.len(),
+            split: 0,
+        };
+    }
+
+    let max_orders = balances.len() * per_wallet;
+    let offset = now.rem_euclid(balances.len() as i64) as usize;
+    let assignments = levels
+        .iter()
+        .take(max_orders)
+        .enumerate()
+        .map(|(i, level)| {
+            let wallet = balances[(i / per_wallet + offset) % balances.len()].wallet;
+            (wallet, level.clone())
+        })
+        .collect();
+    LevelAllocation {
+        assignments,
+        unallocated: levels.len().saturating_sub(max_orders),
+        split: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn balance(sol: Decimal, token: Decimal) -> WalletBalance {
+        WalletBalance {
+            wallet: Pubkey::new_unique(),
+            sol,
+            token,
+            has_open_orders: true,
+        }
+    }
+
+    fn level(price: Decimal, size: Decimal, side: OrderSide) -> GridLevel {
+        GridLevel { price, size, side }
+    }
+
+    fn settings() -> AllocationSettings {
+        AllocationSettings {
+            max_orders_per_wallet: 2,
+            ..AllocationSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_levels_go_to_wallets_holding_their_side() {
+        let sol_wallet = balance(dec!(10), Decimal::ZERO);
+        let token_wallet = balance(Decimal::ZERO, dec!(1000));
+        let balances = [sol_wallet.clone(), token_wallet.clone()];
+        let levels = [
+            level(dec!(1), dec!(4), OrderSide::Buy),
+            level(dec!(0.9), dec!(5), OrderSide::Buy),
+            // The SOL wallet has 1.5 left and no order slots
+            level(dec!(0.8), dec!(1), OrderSide::Buy),
+            level(dec!(1.1), dec!(600), OrderSide::Sell),
+            // Only 400 tokens left, which are still placed
+            level(dec!(1.2), dec!(500), OrderSide::Sell),
+        ];
+
+        let allocator = LevelAllocator::new();
+        let allocation = allocator.allocate(
+            &levels,
+            &balances,
+            &SolReserves::default(),
+            &settings(),
+            None,
+            0,
+        );
+        let wallets: Vec<Pubkey> = allocation.assignments.iter().map(|(w, _)| *w).collect();
+        assert_eq!(
+            wallets,
+            vec![
+                sol_wallet.wallet,
+                sol_wallet.wallet,
+                token_wallet.wallet,
+                token_wallet.wallet
+            ]
+        );
+        assert_eq!(allocation.assignments[3].1.size, dec!(400));
+        assert_eq!(allocation.unallocated, 1);
+
+        // Per-wallet exposure cap of 5 SOL leaves 1 SOL for the second bid, in whole lots
+        let capped = allocator.allocate(
+            &levels[..2],
+            &balances,
+            &SolReserves::default(),
+            &AllocationSettings {
+                max_wallet_exposure_sol: dec!(5),
+                ..settings()
+            },
+            Some(dec!(0.1)),
+            0,
+        );
+        assert_eq!(capped.assignments.len(), 2);
+        assert_eq!(capped.assignments[1].1.size, dec!(1.1));
+        assert_eq!(capped.unallocated, 0);
+    }
+
+    #[test]
+    fn test_levels_split_across_more_wallets_than_levels() {
+        // Two bids worth 6 SOL each; no wallet holds more than 4
+        let balances = [
+            balance(dec!(4), Decimal::ZERO),
+            balance(dec!(4), Decimal::ZERO),
+            balance(dec!(4), Decimal::ZERO),
+        ];
+        let levels = [
+            level(dec!(2), dec!(3), OrderSide::Buy),
+            level(dec!(1), dec!(6), OrderSide::Buy),
+        ];
+
+        let allocation = LevelAllocator::new().allocate(
+            &levels,
+            &balances,
+            &SolReserves::default(),
+            &settings(),
+            Some(Decimal::ONE),
+            0,
+        );
+        let placed: Vec<(Pubkey, Decimal, Decimal)> = allocation
+            .assignments
+            .iter()
+            .map(|(w, l)| (*w, l.price, l.size))
+            .collect();
+        assert_eq!(
+            placed,
+            vec![
+                (balances[0].wallet, dec!(2), dec!(2)),
+                (balances[1].wallet, dec!(2), dec!(1)),
+                (balances[2].wallet, dec!(1), dec!(4)),
+                (balances[1].wallet, dec!(1), dec!(2)),
+            ]
+        );
+        assert_eq!(allocation.split, 2);
+        assert_eq!(allocation.unallocated, 0);
+    }
+
+    #[test]
+    fn test_sticky_assignment_survives_balance_changes() {
+        let a = balance(dec!(10), Decimal::ZERO);
+        let b = balance(dec!(5), Decimal::ZERO);
+        let levels = [level(dec!(1), dec!(2), OrderSide::Buy)];
+        let allocator = LevelAllocator::new();
+
+        let first = allocator.allocate(
+            &levels,
+            &[a.clone(), b.clone()],
+            &SolReserves::default(),
+            &settings(),
+            None,
+            0,
+        );
+        assert_eq!(first.assignments[0].0, a.wallet);
+
+        // b now holds more, but a can still fund the slot it had
+        let richer_b = WalletBalance {
+            sol: dec!(50),
+            ..b.clone()
+        };
+        let second = allocator.allocate(
+            &levels,
+            &[a.clone(), richer_b.clone()],
+            &SolReserves::default(),
+            &settings(),
+            None,
+            0,
+        );
+        assert_eq!(second.assignments[0].0, a.wallet);
+
+        let not_sticky = allocator.allocate(
+            &levels,
+            &[a, richer_b.clone()],
+            &SolReserves::default(),
+            &AllocationSettings {
+                sticky: false,
+                ..settings()
+            },
+            None,
+            0,
+        );
+        assert_eq!(not_sticky.assignments[0].0, richer_b.wallet);
+    }
+
+    #[test]
+    fn test_rotation_chunks_levels_per_wallet() {
+        let balances = [balance(dec!(1), dec!(1)), balance(dec!(1), dec!(1))];
+        let levels = vec![level(dec!(1), dec!(1), OrderSide::Buy); 5];
+        let allocation = LevelAllocator::new().allocate(
+            &levels,
+            &balances,
+            &SolReserves::default(),
+            &AllocationSettings {
+                strategy: AllocationStrategy::Rotation,
+                ..settings()
+            },
+            None,
+            3,
+        );
+
+        let wallets: Vec<Pubkey> = allocation.assignments.iter().map(|(w, _)| *w).collect();
+        let (first, second) = (balances[1].wallet, balances[0].wallet);
+        assert_eq!(wallets, vec![first, first, second, second]);
+        assert_eq!(allocation.unallocated, 1);
+    }
+}
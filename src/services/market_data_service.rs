@@ -1,83 +1,439 @@
-use crate::domain::{OrderSide, Trade};
-use crate::infra::openbook::{OPENBOOK_V1_PROGRAM_ID, OPENBOOK_V2_PROGRAM_ID};
-
-use crate::infra::DatabaseProvider;
+use crate::domain::{OrderSide, SignatureInfo, Trade};
+use crate::infra::ws_stream::{self, HEARTBEAT_TIMEOUT, PING_INTERVAL};
+use crate::infra::{DatabaseProvider, SolanaProvider};
 use crate::services::PivotEngine;
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use futures_util::{SinkExt, StreamExt};
+use metrics::{counter, gauge};
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+use tokio_tungstenite::tungstenite::protocol::Message;
 use tracing::{debug, error, info, warn};
+// How often signature history is compared with the stream to catch dropped notifications
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(60);
+const BACKFILL_SIGNATURE_LIMIT: usize = 1000;
+// A slot whose block time could not be resolved is not asked for again for this long
+const BLOCK_TIME_MISS_TTL: Duration = Duration::from_secs(30);
+// Newest signature covered by the historical backfill; later runs stop there.
+const HISTORY_BACKFILL_STATE_KEY: &str = "trade_backfill_newest_signature";
+// Where a history backfill cut short by its signature cap continues
+const HISTORY_BACKFILL_PROGRESS_KEY: &str = "trade_backfill_progress";
+
+/// Newest transaction processed for a market, persisted so gaps across restarts are replayed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamCursor {
+    pub signature: String,
+    pub slot: u64,
+}
+
+/// A history backfill that stopped at its signature cap.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct BackfillProgress {
+    /// Oldest signature replayed; the next run walks on below it.
+    resume_before: String,
+    /// Newest signature of the interrupted walk, stored as the bound once it completes.
+    newest: String,
+}
 
 pub struct MarketDataService {
     ws_url: String,
     database: Arc<dyn crate::infra::DatabaseProvider>,
+    solana: Arc<dyn SolanaProvider>,
     market_id: String,
     pivot_engine: Arc<PivotEngine>,
+    // Newest transaction the confirmed backfill has verified; gap replays walk signature
+    // history back to here. Only the backfill moves it, never the stream, which can drop
+    // notifications.
+    cursor: RwLock<Option<StreamCursor>>,
+    // Signatures already ingested from the stream (-> slot), so the backfill that confirms
+    // them does not fetch them again.
+    live_signatures: RwLock<HashMap<String, u64>>,
+    // Block time of the most recent live slot, so a burst of fills in one slot costs one lookup.
+    last_block_time: RwLock<Option<(u64, i64)>>,
+    // Last slot whose block time could not be resolved, and when
+    block_time_miss: RwLock<Option<(u64, Instant)>>,
 }
 
 impl MarketDataService {
     pub fn new(
         ws_url: &str,
         database: Arc<dyn DatabaseProvider>,
+        solana: Arc<dyn SolanaProvider>,
         market_id: &str,
         pivot_engine: Arc<PivotEngine>,
     ) -> Self {
         Self {
             ws_url: ws_url.to_string(),
             database,
+            solana,
             market_id: market_id.to_string(),
             pivot_engine,
+            cursor: RwLock::new(None),
+            live_signatures: RwLock::new(HashMap::new()),
+            last_block_time: RwLock::new(None),
+            block_time_miss: RwLock::new(None),
         }
     }
 
+    fn cursor_state_key(&self) -> String {
+        format!("market_data_cursor:{}", self.market_id)
+    }
+
+    async fn load_cursor(&self) -> Result<()> {
+        let stored = self.database.get_state(&self.cursor_state_key()).await?;
+        if let Some(cursor) = stored.and_then(|v| serde_json::from_str::<StreamCursor>(&v).ok()) {
+            info!(slot = cursor.slot, signature = %cursor.signature, "market_data_cursor_restored");
+            let mut current = self.cursor.write().await;
+            if current.as_ref().is_none_or(|c| c.slot < cursor.slot) {
+                *current = Some(cursor);
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the cursor forward; an older slot never moves it back.
+    async fn advance_cursor(&self, signature: &str, slot: u64) -> Result<()> {
+        {
+            let mut current = self.cursor.write().await;
+            if current.as_ref().is_some_and(|c| c.slot > slot) {
+                return Ok(());
+            }
+            *current = Some(StreamCursor {
+                signature: signature.to_string(),
+                slot,
+            });
+        }
+        gauge!("bot_market_data_cursor_slot", slot as f64, "market" => self.market_id.clone());
+        let value = serde_json::to_string(&StreamCursor {
+            signature: signature.to_string(),
+            slot,
+        })?;
+        self.database
+            .set_state(&self.cursor_state_key(), &value)
+            .await
+    }
+
     pub async fn run(&self) -> Result<()> {
         info!(url = %self.ws_url, market = %self.market_id, "starting_market_data_service");
+        if let Err(e) = self.load_cursor().await {
+            warn!(error = ?e, "market_data_cursor_load_failed");
+        }
+
+        ws_stream::run_with_reconnect(
+            "market_data",
+            || self.connect_and_stream(),
+            || {
+                gauge!("bot_market_data_connected", 0.0);
+                counter!("bot_market_data_reconnects_total", 1);
+            },
+        )
+        .await
+    }
 
-        let (mut ws_stream, _) = connect_async(&self.ws_url).await?;
+    async fn connect_and_stream(&self) -> Result<()> {
+        let (mut write, mut read) = ws_stream::connect(&self.ws_url).await?.split();
 
-        // Subscribe to program logs for OpenBook V1 and V2
+        // Subscribe to transactions touching this market. `mentions` accepts a single
+        // address, and the market account keeps the stream aligned with its signature history.
+        // Confirmed slots already have a block time, so stamping a fill never has to wait
+        // inside the read loop.
         let sub_request = serde_json::json!({
             "jsonrpc": "2.0",
             "id": 1,
             "method": "logsSubscribe",
             "params": [
                 {
-                    "mentions": [OPENBOOK_V1_PROGRAM_ID, OPENBOOK_V2_PROGRAM_ID]
+                    "mentions": [self.market_id]
                 },
                 {
-                    "commitment": "processed"
+                    "commitment": "confirmed"
                 }
             ]
         });
 
-        ws_stream
+        write
             .send(Message::Text(sub_request.to_string()))
-            .await?;
+            .await
+            .context("failed_to_send_logs_subscription")?;
+        gauge!("bot_market_data_connected", 1.0);
+        info!(url = %self.ws_url, "market_data_stream_subscribed");
 
-        while let Some(msg) = ws_stream.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    if let Err(e) = self.handle_message(&text).await {
-                        warn!(error = %e, "failed_to_handle_ws_message");
+        // Trades that landed while we were disconnected never reach the stream
+        if let Err(e) = self.backfill_gap().await {
+            warn!(error = ?e, "market_data_gap_backfill_failed");
+        }
+
+        let mut ping = tokio::time::interval(PING_INTERVAL);
+        ping.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        ping.tick().await;
+        let mut reconcile = tokio::time::interval(RECONCILE_INTERVAL);
+        reconcile.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        reconcile.tick().await;
+        let mut last_message = Instant::now();
+
+        loop {
+            let stale_deadline = tokio::time::Instant::from_std(last_message + HEARTBEAT_TIMEOUT);
+
+            tokio::select! {
+                msg = read.next() => {
+                    last_message = Instant::now();
+                    match msg {
+                        Some(Ok(Message::Text(text))) => {
+                            if let Err(e) = self.handle_message(&text).await {
+                                warn!(error = %e, "failed_to_handle_ws_message");
+                            }
+                        }
+                        Some(Ok(Message::Ping(payload))) => {
+                            write.send(Message::Pong(payload)).await?;
+                        }
+                        Some(Ok(Message::Close(frame))) => {
+                            warn!(?frame, "websocket_connection_closed");
+                            return Ok(());
+                        }
+                        Some(Ok(_)) => {}
+                        Some(Err(e)) => {
+                            error!(error = %e, "websocket_error");
+                            return Err(e.into());
+                        }
+                        None => return Ok(()),
                     }
                 }
-                Ok(Message::Close(_)) => {
-                    warn!("websocket_connection_closed");
-                    break;
+                _ = ping.tick() => {
+                    write.send(Message::Ping(Vec::new())).await?;
                 }
-                Err(e) => {
-                    error!(error = %e, "websocket_error");
-                    break;
+                _ = reconcile.tick() => {
+                    // A connected stream can still drop notifications or lag behind the chain
+                    if let Err(e) = self.backfill_gap().await {
+                        warn!(error = ?e, "market_data_reconcile_failed");
+                    }
+                }
+                _ = tokio::time::sleep_until(stale_deadline) => {
+                    counter!("bot_market_data_stale_total", 1);
+                    return Err(anyhow!(
+                        "no websocket traffic for {}s, treating stream as stale",
+                        HEARTBEAT_TIMEOUT.as_secs()
+                    ));
                 }
-                _ => {}
             }
         }
+    }
 
-        Ok(())
+    /// Replays fills for signatures newer than the cursor, i.e. transactions the stream
+    /// missed while disconnected, lagging, or dropping notifications. Confirmed signature
+    /// history is contiguous down to the cursor, so this is the only place it moves; one
+    /// state write per run.
+    pub async fn backfill_gap(&self) -> Result<usize> {
+        let until = match self.cursor.read().await.clone() {
+            Some(cursor) => cursor.signature,
+            None => {
+                // Nothing verified yet: start from the chain head, older fills are the
+                // history backfill's job
+                let head = self
+                    .solana
+                    .get_signatures_for_address(&self.market_id, None, None, 1)
+                    .await?;
+                if let Some(newest) = head.first() {
+                    self.advance_cursor(&newest.signature, newest.slot).await?;
+                }
+                return Ok(0);
+            }
+        };
+
+        let mut signatures = self.signatures_until(&until).await?;
+        if signatures.is_empty() {
+            return Ok(0);
+        }
+        counter!(
+            "bot_market_data_gap_signatures_total",
+            signatures.len() as u64,
+            "market" => self.market_id.clone()
+        );
+
+        // RPC returns newest first; replay in chain order, skipping what the stream ingested
+        signatures.reverse();
+        let missed: Vec<SignatureInfo> = {
+            let live = self.live_signatures.read().await;
+            signatures
+                .iter()
+                .filter(|s| !live.contains_key(&s.signature))
+                .cloned()
+                .collect()
+        };
+
+        let replayed = self.replay_signatures(&missed).await?;
+        if let Some(newest) = signatures.last() {
+            self.advance_cursor(&newest.signature, newest.slot).await?;
+            // Everything up to here is confirmed; forked-out signatures go as well
+            self.live_signatures
+                .write()
+                .await
+                .retain(|_, slot| *slot > newest.slot);
+        }
+
+        counter!("bot_market_data_backfilled_trades_total", replayed as u64);
+        info!(
+            signatures = signatures.len(),
+            missed = missed.len(),
+            trades = replayed,
+            %until,
+            "market_data_gap_backfilled"
+        );
+        Ok(replayed)
+    }
+
+    /// Every signature newer than `until`, newest first, paging back with `before` so a
+    /// gap longer than one RPC page is replayed in full.
+    async fn signatures_until(&self, until: &str) -> Result<Vec<SignatureInfo>> {
+        let mut signatures: Vec<SignatureInfo> = Vec::new();
+        loop {
+            let page = self
+                .solana
+                .get_signatures_for_address(
+                    &self.market_id,
+                    signatures.last().map(|s| s.signature.clone()),
+                    Some(until.to_string()),
+                    BACKFILL_SIGNATURE_LIMIT,
+                )
+                .await?;
+            let page_len = page.len();
+            signatures.extend(page);
+            if page_len < BACKFILL_SIGNATURE_LIMIT {
+                return Ok(signatures);
+            }
+            debug!(
+                signatures = signatures.len(),
+                "market_data_gap_paging_further"
+            );
+        }
+    }
+
+    /// Walks the market's signature history backwards until `horizon_secs` ago (or up to
+    /// `max_signatures`) and stores the decoded fills, so the pivot starts from real trades
+    /// after a fresh deploy. Safe to re-run: trade ids are deterministic and a previous run's
+    /// newest signature bounds the walk. A walk cut short by the cap resumes below its oldest
+    /// signature on the next run.
+    pub async fn backfill_history(
+        &self,
+        horizon_secs: i64,
+        max_signatures: usize,
+    ) -> Result<usize> {
+        let cutoff = now_secs() - horizon_secs;
+        let until = self.database.get_state(HISTORY_BACKFILL_STATE_KEY).await?;
+        let progress: Option<BackfillProgress> = self
+            .database
+            .get_state(HISTORY_BACKFILL_PROGRESS_KEY)
+            .await?
+            .and_then(|v| serde_json::from_str(&v).ok())
+            .flatten();
+        if let Some(progress) = &progress {
+            info!(before = %progress.resume_before, "trade_history_backfill_resuming");
+        }
+
+        let mut signatures = Vec::new();
+        let mut before = progress.as_ref().map(|p| p.resume_before.clone());
+        let mut capped = false;
+        'pages: loop {
+            let page = self
+                .solana
+                .get_signatures_for_address(
+                    &self.market_id,
+                    before.clone(),
+                    until.clone(),
+                    BACKFILL_SIGNATURE_LIMIT,
+                )
+                .await?;
+            let page_len = page.len();
+            before = page.last().map(|s| s.signature.clone());
+
+            for info in page {
+                if info.block_time.is_some_and(|t| t < cutoff) {
+                    break 'pages;
+                }
+                signatures.push(info);
+                if signatures.len() >= max_signatures {
+                    warn!(
+                        max_signatures,
+                        "trade_history_backfill_signature_cap_reached"
+                    );
+                    capped = true;
+                    break 'pages;
+                }
+            }
+
+            if page_len < BACKFILL_SIGNATURE_LIMIT {
+                break;
+            }
+        }
+
+        let newest = signatures.first().cloned();
+        let oldest = signatures.last().map(|s| s.signature.clone());
+
+        // RPC returns newest first; replay in chain order
+        signatures.reverse();
+        let replayed = self.replay_signatures(&signatures).await?;
+
+        // The walk's newest signature only bounds later runs once everything below it down
+        // to the previous bound has been replayed
+        let walk_newest = progress
+            .as_ref()
+            .map(|p| p.newest.clone())
+            .or_else(|| newest.as_ref().map(|s| s.signature.clone()));
+        let next_progress = match (capped, oldest, walk_newest.clone()) {
+            (true, Some(resume_before), Some(newest)) => Some(BackfillProgress {
+                resume_before,
+                newest,
+            }),
+            _ => None,
+        };
+        if next_progress.is_none() {
+            if let Some(sig) = &walk_newest {
+                self.database
+                    .set_state(HISTORY_BACKFILL_STATE_KEY, sig)
+                    .await?;
+            }
+        }
+        if progress.is_some() || next_progress.is_some() {
+            self.database
+                .set_state(
+                    HISTORY_BACKFILL_PROGRESS_KEY,
+                    &serde_json::to_string(&next_progress)?,
+                )
+                .await?;
+        }
+
+        // Let the first stream connection's gap backfill pick up from the chain head
+        if let (None, Some(newest)) = (&progress, &newest) {
+            self.advance_cursor(&newest.signature, newest.slot).await?;
+        }
+
+        counter!("bot_trade_history_backfilled_total", replayed as u64);
+        info!(
+            signatures = signatures.len(),
+            trades = replayed,
+            horizon_secs,
+            complete = next_progress.is_none(),
+            "trade_history_backfilled"
+        );
+        Ok(replayed)
+    }
+
+    async fn replay_signatures(&self, signatures: &[SignatureInfo]) -> Result<usize> {
+        let mut replayed = 0;
+        for info in signatures.iter().filter(|s| !s.failed) {
+            if let Some(tx) = self.solana.get_transaction_logs(&info.signature).await? {
+                let block_time = tx.block_time.or(info.block_time);
+                replayed += self
+                    .process_logs(&tx.logs, &tx.signature, tx.slot, block_time)
+                    .await?;
+            }
+        }
+        Ok(replayed)
     }
 
     async fn handle_message(&self, text: &str) -> Result<()> {
@@ -85,26 +441,105 @@ impl MarketDataService {
 
         // Check if it's a notification
         if v["method"] == "logsNotification" {
-            let logs = v["params"]["result"]["value"]["logs"]
+            let logs: Vec<String> = v["params"]["result"]["value"]["logs"]
                 .as_array()
-                .ok_or_else(|| anyhow!("Missing logs"))?;
+                .ok_or_else(|| anyhow!("Missing logs"))?
+                .iter()
+                .filter_map(|l| l.as_str().map(str::to_string))
+                .collect();
             let signature = v["params"]["result"]["value"]["signature"]
                 .as_str()
                 .unwrap_or("unknown");
+            let slot = v["params"]["result"]["context"]["slot"]
+                .as_u64()
+                .unwrap_or(0);
 
-            for log in logs {
-                if let Some(log_str) = log.as_str() {
-                    if log_str.contains("FillEvent") || log_str.contains("TradeEvent") {
-                        self.parse_and_save_event(log_str, signature).await?;
-                    }
-                }
+            gauge!("bot_market_data_last_message_ts", now_secs() as f64);
+            // Fills in a failed transaction never happened
+            if v["params"]["result"]["value"]["err"].is_null() {
+                let Some(block_time) = self.block_time_for_slot(slot).await else {
+                    // Left to the confirmed backfill, which stamps it with the chain time
+                    debug!(%signature, slot, "live_trade_deferred_to_backfill");
+                    return Ok(());
+                };
+                self.process_logs(&logs, signature, slot, Some(block_time))
+                    .await?;
+            }
+            // The cursor waits for the backfill to see this in signature history
+            if signature != "unknown" {
+                self.live_signatures
+                    .write()
+                    .await
+                    .insert(signature.to_string(), slot);
             }
         }
 
         Ok(())
     }
 
-    async fn parse_and_save_event(&self, log: &str, signature: &str) -> Result<()> {
+    /// Chain time for a live slot, looked up once per slot; `None` when the node cannot
+    /// serve it, and the fills are left to the backfill.
+    async fn block_time_for_slot(&self, slot: u64) -> Option<i64> {
+        if slot == 0 {
+            return None;
+        }
+        if let Some((cached_slot, block_time)) = *self.last_block_time.read().await {
+            if cached_slot == slot {
+                return Some(block_time);
+            }
+        }
+        if let Some((missed_slot, at)) = *self.block_time_miss.read().await {
+            if missed_slot == slot && at.elapsed() < BLOCK_TIME_MISS_TTL {
+                return None;
+            }
+        }
+
+        match self.solana.get_block_time(slot).await {
+            Ok(block_time) => {
+                *self.last_block_time.write().await = Some((slot, block_time));
+                return Some(block_time);
+            }
+            Err(e) => debug!(slot, error = %e, "block_time_not_available"),
+        }
+        *self.block_time_miss.write().await = Some((slot, Instant::now()));
+        counter!("bot_market_data_block_time_fallback_total", 1);
+        None
+    }
+
+    async fn process_logs(
+        &self,
+        logs: &[String],
+        signature: &str,
+        slot: u64,
+        block_time: Option<i64>,
+    ) -> Result<usize> {
+        let timestamp = block_time.unwrap_or_else(now_secs);
+        let slot = (slot > 0).then_some(slot);
+        let mut saved = 0;
+        for (event_idx, log) in logs
+            .iter()
+            .filter(|l| l.contains("FillEvent") || l.contains("TradeEvent"))
+            .enumerate()
+        {
+            if self
+                .parse_and_save_event(log, signature, event_idx, timestamp, slot, block_time)
+                .await?
+            {
+                saved += 1;
+            }
+        }
+        Ok(saved)
+    }
+
+    async fn parse_and_save_event(
+        &self,
+        log: &str,
+        signature: &str,
+        event_idx: usize,
+        timestamp: i64,
+        slot: Option<u64>,
+        block_time: Option<i64>,
+    ) -> Result<bool> {
         // Attempt to extract details from log string
         // V2 Format often looks like: "Program log: FillEvent { maker: ..., taker: ..., price: 123, volume: 456, ... }"
 
@@ -129,24 +564,38 @@ impl MarketDataService {
             };
 
             let trade = Trade {
-                id: format!("{}-{}", signature, 0),
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)?
-                    .as_secs() as i64,
+                id: format!("{}-{}", signature, event_idx),
+                timestamp,
                 price: p,
                 volume: v,
                 side,
                 wallet: "unknown".to_string(),
+                slot,
+                block_time,
+                maker: self.extract_account(log, "maker:"),
+                taker: self.extract_account(log, "taker:"),
             };
 
             self.database.save_trade(&trade).await?;
             self.pivot_engine.record_trade(trade.clone()).await;
             info!(price = %trade.price, volume = %trade.volume, side = ?trade.side, "trade_ingested_and_cached");
+            Ok(true)
         } else {
             debug!(log = %log, "log_event_missing_price_or_volume_skipping");
+            Ok(false)
         }
+    }
 
-        Ok(())
+    /// Base58 account following `marker`, e.g. the maker and taker of a fill.
+    fn extract_account(&self, log: &str, marker: &str) -> Option<String> {
+        let start = log.find(marker)? + marker.len();
+        let account: String = log[start..]
+            .trim_start()
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+        // Pubkeys are 32-44 base58 characters; anything shorter is not an account
+        (account.len() >= 32).then_some(account)
     }
 
     fn extract_value(&self, log: &str, marker: &str) -> Option<String> {
@@ -167,3 +616,533 @@ impl MarketDataService {
         None
     }
 }
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::TransactionLogs;
+    use crate::infra::mocks::{MockDatabaseProvider, MockSolanaProvider};
+    use mockall::predicate::*;
+
+    const FILL_MAKER: &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+    const FILL_TAKER: &str = "CDLiCmfzk4ZNKcKx4a6cGwyhNp1Vt7Rzn5ZoRYfk2zmL";
+
+    fn test_pivot_engine() -> Arc<PivotEngine> {
+        Arc::new(PivotEngine::new(
+            Decimal::ZERO,
+            0,
+            60,
+            Decimal::from(1000),
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Decimal::ZERO,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_backfill_gap_replays_missed_fills() {
+        let mut mock_solana = MockSolanaProvider::new();
+        let mut mock_database = MockDatabaseProvider::new();
+        let block_time = now_secs() - 5;
+
+        mock_solana
+            .expect_get_signatures_for_address()
+            .with(
+                eq("MARKET"),
+                eq(None),
+                eq(Some("sig_live".to_string())),
+                always(),
+            )
+            .returning(move |_, _, _, _| {
+                Ok(vec![
+                    SignatureInfo {
+                        signature: "sig_new".to_string(),
+                        slot: 12,
+                        block_time: Some(block_time),
+                        failed: false,
+                    },
+                    SignatureInfo {
+                        signature: "sig_failed".to_string(),
+                        slot: 11,
+                        block_time: Some(block_time - 1),
+                        failed: true,
+                    },
+                ])
+            });
+        // Empty chain: no head to start the cursor from
+        mock_solana
+            .expect_get_signatures_for_address()
+            .with(eq("MARKET"), eq(None), eq(None), eq(1))
+            .times(1)
+            .returning(|_, _, _, _| Ok(vec![]));
+        mock_solana
+            .expect_get_transaction_logs()
+            .with(eq("sig_new"))
+            .times(1)
+            .returning(move |sig| {
+                Ok(Some(TransactionLogs {
+                    signature: sig.to_string(),
+                    slot: 12,
+                    block_time: Some(block_time),
+                    logs: vec![
+                        "Program log: Instruction: PlaceOrder".to_string(),
+                        "Program log: FillEvent { price: 101, volume: 3, side: Buy }".to_string(),
+                    ],
+                }))
+            });
+        mock_database
+            .expect_set_state()
+            .withf(|key, value| key == "market_data_cursor:MARKET" && value.contains("sig_new"))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_database
+            .expect_save_trade()
+            .withf(move |t| {
+                t.id == "sig_new-0" && t.price == Decimal::from(101) && t.timestamp == block_time
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let pivot_engine = test_pivot_engine();
+        let service = MarketDataService::new(
+            "ws://localhost:0",
+            Arc::new(mock_database),
+            Arc::new(mock_solana),
+            "MARKET",
+            pivot_engine.clone(),
+        );
+
+        // No signature seen yet: nothing to backfill
+        assert_eq!(service.backfill_gap().await.unwrap(), 0);
+        assert!(service.cursor.read().await.is_none());
+
+        *service.cursor.write().await = Some(StreamCursor {
+            signature: "sig_live".to_string(),
+            slot: 10,
+        });
+        assert_eq!(service.backfill_gap().await.unwrap(), 1);
+        assert_eq!(
+            service.cursor.read().await.clone().unwrap().signature,
+            "sig_new"
+        );
+        assert_eq!(pivot_engine.cached_trades().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_gap_pages_past_the_signature_limit() {
+        let mut mock_solana = MockSolanaProvider::new();
+        let mut mock_database = MockDatabaseProvider::new();
+
+        let full_page: Vec<SignatureInfo> = (0..BACKFILL_SIGNATURE_LIMIT)
+            .map(|i| SignatureInfo {
+                signature: format!("sig_{}", 2000 - i),
+                slot: (2000 - i) as u64,
+                block_time: None,
+                // Nothing to fetch; only the paging is under test
+                failed: true,
+            })
+            .collect();
+        mock_solana
+            .expect_get_signatures_for_address()
+            .with(eq("MARKET"), eq(None), always(), always())
+            .times(1)
+            .returning(move |_, _, _, _| Ok(full_page.clone()));
+        mock_solana
+            .expect_get_signatures_for_address()
+            .with(
+                eq("MARKET"),
+                eq(Some("sig_1001".to_string())),
+                eq(Some("sig_live".to_string())),
+                always(),
+            )
+            .times(1)
+            .returning(|_, _, _, _| {
+                Ok(vec![SignatureInfo {
+                    signature: "sig_1000".to_string(),
+                    slot: 1000,
+                    block_time: None,
+                    failed: true,
+                }])
+            });
+        mock_database.expect_set_state().returning(|_, _| Ok(()));
+
+        let service = MarketDataService::new(
+            "ws://localhost:0",
+            Arc::new(mock_database),
+            Arc::new(mock_solana),
+            "MARKET",
+            test_pivot_engine(),
+        );
+        *service.cursor.write().await = Some(StreamCursor {
+            signature: "sig_live".to_string(),
+            slot: 999,
+        });
+
+        assert_eq!(service.backfill_gap().await.unwrap(), 0);
+        assert_eq!(
+            service.cursor.read().await.clone().unwrap().signature,
+            "sig_2000"
+        );
+    }
+
+    fn signature(sig: &str, block_time: i64) -> SignatureInfo {
+        SignatureInfo {
+            signature: sig.to_string(),
+            slot: 0,
+            block_time: Some(block_time),
+            failed: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backfill_history_stops_at_horizon() {
+        let mut mock_solana = MockSolanaProvider::new();
+        let mut mock_database = MockDatabaseProvider::new();
+        let now = now_secs();
+
+        mock_database
+            .expect_get_state()
+            .with(eq(HISTORY_BACKFILL_STATE_KEY))
+            .returning(|_| Ok(Some("sig_previous_run".to_string())));
+        mock_database
+            .expect_get_state()
+            .with(eq(HISTORY_BACKFILL_PROGRESS_KEY))
+            .returning(|_| Ok(None));
+        mock_solana
+            .expect_get_signatures_for_address()
+            .with(
+                eq("MARKET"),
+                eq(None),
+                eq(Some("sig_previous_run".to_string())),
+                always(),
+            )
+            .times(1)
+            .returning(move |_, _, _, _| {
+                Ok(vec![
+                    signature("sig_recent", now - 60),
+                    signature("sig_older", now - 1800),
+                    signature("sig_beyond_horizon", now - 7200),
+                ])
+            });
+        mock_solana
+            .expect_get_transaction_logs()
+            .times(2)
+            .returning(|sig| {
+                Ok(Some(TransactionLogs {
+                    signature: sig.to_string(),
+                    slot: 0,
+                    block_time: None,
+                    logs: vec![
+                        "Program log: FillEvent { price: 5, volume: 2, side: Sell }".to_string()
+                    ],
+                }))
+            });
+
+        let saved = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let saved_clone = saved.clone();
+        mock_database.expect_save_trade().returning(move |t| {
+            saved_clone.lock().unwrap().push(t.clone());
+            Ok(())
+        });
+        mock_database
+            .expect_set_state()
+            .with(eq(HISTORY_BACKFILL_STATE_KEY), eq("sig_recent"))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_database
+            .expect_set_state()
+            .withf(|key, _| key == "market_data_cursor:MARKET")
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let service = MarketDataService::new(
+            "ws://localhost:0",
+            Arc::new(mock_database),
+            Arc::new(mock_solana),
+            "MARKET",
+            test_pivot_engine(),
+        );
+
+        assert_eq!(service.backfill_history(3600, 1000).await.unwrap(), 2);
+
+        // Replayed oldest first, stamped with the signature's block time
+        let saved = saved.lock().unwrap();
+        assert_eq!(saved[0].id, "sig_older-0");
+        assert_eq!(saved[0].timestamp, now - 1800);
+        assert_eq!(saved[1].id, "sig_recent-0");
+    }
+
+    #[tokio::test]
+    async fn test_backfill_history_resumes_below_signature_cap() {
+        let mut mock_solana = MockSolanaProvider::new();
+        let mut mock_database = MockDatabaseProvider::new();
+        let now = now_secs();
+
+        let state = Arc::new(std::sync::Mutex::new(std::collections::HashMap::new()));
+        let state_get = state.clone();
+        mock_database
+            .expect_get_state()
+            .returning(move |key| Ok(state_get.lock().unwrap().get(key).cloned()));
+        let state_set = state.clone();
+        mock_database
+            .expect_set_state()
+            .returning(move |key, value| {
+                state_set
+                    .lock()
+                    .unwrap()
+                    .insert(key.to_string(), value.to_string());
+                Ok(())
+            });
+        mock_database.expect_save_trade().returning(|_| Ok(()));
+
+        mock_solana
+            .expect_get_signatures_for_address()
+            .with(eq("MARKET"), eq(None), eq(None), always())
+            .times(1)
+            .returning(move |_, _, _, _| {
+                Ok(vec![
+                    signature("sig_3", now - 10),
+                    signature("sig_2", now - 20),
+                    signature("sig_1", now - 30),
+                ])
+            });
+        mock_solana
+            .expect_get_signatures_for_address()
+            .with(
+                eq("MARKET"),
+                eq(Some("sig_2".to_string())),
+                eq(None),
+                always(),
+            )
+            .times(1)
+            .returning(move |_, _, _, _| Ok(vec![signature("sig_1", now - 30)]));
+        mock_solana
+            .expect_get_transaction_logs()
+            .times(3)
+            .returning(|_| Ok(None));
+
+        let service = MarketDataService::new(
+            "ws://localhost:0",
+            Arc::new(mock_database),
+            Arc::new(mock_solana),
+            "MARKET",
+            test_pivot_engine(),
+        );
+
+        // Capped after two signatures: the bound is not moved, the walk resumes below sig_2
+        service.backfill_history(3600, 2).await.unwrap();
+        {
+            let state = state.lock().unwrap();
+            assert!(!state.contains_key(HISTORY_BACKFILL_STATE_KEY));
+            let progress: Option<BackfillProgress> =
+                serde_json::from_str(&state[HISTORY_BACKFILL_PROGRESS_KEY]).unwrap();
+            assert_eq!(progress.unwrap().resume_before, "sig_2");
+        }
+
+        service.backfill_history(3600, 2).await.unwrap();
+        let state = state.lock().unwrap();
+        assert_eq!(state[HISTORY_BACKFILL_STATE_KEY], "sig_3");
+        assert_eq!(state[HISTORY_BACKFILL_PROGRESS_KEY], "null");
+        assert!(state["market_data_cursor:MARKET"].contains("sig_3"));
+    }
+
+    fn logs_notification(signature: &str, slot: u64, failed: bool) -> String {
+        let err = if failed {
+            serde_json::json!({ "InstructionError": [0, "Custom"] })
+        } else {
+            Value::Null
+        };
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "logsNotification",
+            "params": {
+                "result": {
+                    "context": { "slot": slot },
+                    "value": {
+                        "signature": signature,
+                        "err": err,
+                        "logs": [format!(
+                            "Program log: FillEvent {{ maker: {}, taker: {}, price: 7, volume: 1, side: Buy }}",
+                            FILL_MAKER, FILL_TAKER
+                        )]
+                    }
+                }
+            }
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_cursor_advances_only_from_confirmed_backfill() {
+        let mut mock_solana = MockSolanaProvider::new();
+        let mut mock_database = MockDatabaseProvider::new();
+        mock_solana
+            .expect_get_block_time()
+            .returning(|slot| Ok(1_700_000_000 + slot as i64));
+        mock_database.expect_get_state().returning(|_| {
+            Ok(Some(
+                r#"{"signature":"sig_before_restart","slot":90}"#.to_string(),
+            ))
+        });
+        mock_solana
+            .expect_get_signatures_for_address()
+            .with(
+                eq("MARKET"),
+                eq(None),
+                eq(Some("sig_before_restart".to_string())),
+                always(),
+            )
+            .returning(|_, _, _, _| {
+                Ok(vec![
+                    SignatureInfo {
+                        signature: "sig_dropped".to_string(),
+                        slot: 102,
+                        block_time: Some(1_700_000_102),
+                        failed: false,
+                    },
+                    SignatureInfo {
+                        signature: "sig_ok".to_string(),
+                        slot: 101,
+                        block_time: Some(1_700_000_101),
+                        failed: false,
+                    },
+                    SignatureInfo {
+                        signature: "sig_failed".to_string(),
+                        slot: 100,
+                        block_time: Some(1_700_000_100),
+                        failed: true,
+                    },
+                ])
+            });
+        // Only the notification the stream dropped is fetched again
+        mock_solana
+            .expect_get_transaction_logs()
+            .with(eq("sig_dropped"))
+            .times(1)
+            .returning(|sig| {
+                Ok(Some(TransactionLogs {
+                    signature: sig.to_string(),
+                    slot: 102,
+                    block_time: Some(1_700_000_102),
+                    logs: vec![
+                        "Program log: FillEvent { price: 7, volume: 1, side: Buy }".to_string()
+                    ],
+                }))
+            });
+        // One cursor write for the whole backfill
+        mock_database
+            .expect_set_state()
+            .withf(|key, value| key == "market_data_cursor:MARKET" && value.contains("sig_dropped"))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_database
+            .expect_save_trade()
+            .withf(|t| t.id != "sig_failed-0")
+            .times(2)
+            .returning(|_| Ok(()));
+
+        let service = MarketDataService::new(
+            "ws://localhost:0",
+            Arc::new(mock_database),
+            Arc::new(mock_solana),
+            "MARKET",
+            test_pivot_engine(),
+        );
+
+        service.load_cursor().await.unwrap();
+        assert_eq!(service.cursor.read().await.clone().unwrap().slot, 90);
+
+        service
+            .handle_message(&logs_notification("sig_failed", 100, true))
+            .await
+            .unwrap();
+        service
+            .handle_message(&logs_notification("sig_ok", 101, false))
+            .await
+            .unwrap();
+        // Stream notifications do not move the cursor
+        assert_eq!(service.cursor.read().await.clone().unwrap().slot, 90);
+
+        assert_eq!(service.backfill_gap().await.unwrap(), 1);
+        let cursor = service.cursor.read().await.clone().unwrap();
+        assert_eq!(cursor.signature, "sig_dropped");
+        assert_eq!(cursor.slot, 102);
+        assert!(service.live_signatures.read().await.is_empty());
+
+        // An older slot never moves it back
+        service.advance_cursor("sig_stale", 95).await.unwrap();
+        assert_eq!(service.cursor.read().await.clone().unwrap().slot, 102);
+    }
+
+    #[tokio::test]
+    async fn test_live_trades_use_block_time_of_their_slot() {
+        let mut mock_solana = MockSolanaProvider::new();
+        let mut mock_database = MockDatabaseProvider::new();
+        // One lookup per slot, however many fills land in it
+        mock_solana
+            .expect_get_block_time()
+            .with(eq(200))
+            .times(1)
+            .returning(|_| Ok(1_700_000_123));
+        // Asked once, then not again for that slot
+        mock_solana
+            .expect_get_block_time()
+            .with(eq(201))
+            .times(1)
+            .returning(|_| Err(anyhow!("Block not available for slot 201")));
+        mock_database.expect_set_state().returning(|_, _| Ok(()));
+
+        let saved = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let saved_clone = saved.clone();
+        mock_database.expect_save_trade().returning(move |t| {
+            saved_clone.lock().unwrap().push(t.clone());
+            Ok(())
+        });
+
+        let service = MarketDataService::new(
+            "ws://localhost:0",
+            Arc::new(mock_database),
+            Arc::new(mock_solana),
+            "MARKET",
+            test_pivot_engine(),
+        );
+
+        for (sig, slot) in [
+            ("sig_a", 200),
+            ("sig_b", 200),
+            ("sig_c", 201),
+            ("sig_d", 201),
+        ] {
+            service
+                .handle_message(&logs_notification(sig, slot, false))
+                .await
+                .unwrap();
+        }
+
+        // Without a block time the fills wait for the backfill instead of taking the
+        // ingestion time
+        {
+            let live = service.live_signatures.read().await;
+            assert!(live.contains_key("sig_a"));
+            assert!(!live.contains_key("sig_c") && !live.contains_key("sig_d"));
+        }
+
+        let saved = saved.lock().unwrap();
+        assert_eq!(saved.len(), 2);
+        for trade in &saved[..2] {
+            assert_eq!(trade.slot, Some(200));
+            assert_eq!(trade.block_time, Some(1_700_000_123));
+            assert_eq!(trade.timestamp, 1_700_000_123);
+        }
+        assert_eq!(saved[0].maker.as_deref(), Some(FILL_MAKER));
+        assert_eq!(saved[0].taker.as_deref(), Some(FILL_TAKER));
+    }
+}
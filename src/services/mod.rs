@@ -1,10 +1,20 @@
+pub mod candle_service;
+pub mod channel_width;
+pub mod cost_ledger;
 pub mod emergency_pool;
+pub mod fiat_service;
 pub mod financial_manager;
 pub mod flash_volume;
 pub mod grid_builder;
+pub mod grid_sizing;
+pub mod inventory_skew;
+pub mod level_allocator;
 pub mod market_data_service;
+pub mod orderbook_service;
 pub mod pivot_engine;
+pub mod pivot_strategy;
 pub mod pnl_tracker;
+pub mod price_guard;
 pub mod rebalance_service;
 pub mod rent_recovery;
 pub mod risk_manager;
@@ -13,12 +23,22 @@ pub mod simulation_service;
 pub mod trade_executor;
 pub mod trading_service;
 
+pub use candle_service::CandleService;
+pub use channel_width::{ChannelWidthController, ChannelWidths};
+pub use cost_ledger::{CostLedger, CostReport, TransactionCosts};
+pub use fiat_service::{FiatRates, FiatService};
 pub use financial_manager::FinancialManager;
 pub use flash_volume::FlashVolumeModule;
-pub use grid_builder::GridBuilder;
+pub use grid_builder::{GridBuilder, LadderReport, MarketConstraints, SideLadderReport};
+pub use grid_sizing::{SideBudgets, SolReserves, WalletBalance};
+pub use inventory_skew::InventorySkew;
+pub use level_allocator::{LevelAllocation, LevelAllocator};
 pub use market_data_service::MarketDataService;
+pub use orderbook_service::{OrderbookFeed, OrderbookService, OrderbookSnapshot};
 pub use pivot_engine::PivotEngine;
+pub use pivot_strategy::PivotStrategy;
 pub use pnl_tracker::PnlTracker;
+pub use price_guard::{PriceGuard, QuotingState};
 pub use rebalance_service::RebalanceService;
 pub use rent_recovery::RentRecoveryService;
 pub use risk_manager::{RiskManager, RiskSnapshot};
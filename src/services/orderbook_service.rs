@@ -0,0 +1,493 @@
+use crate::domain::Orderbook;
+use crate::infra::openbook::BookLayout;
+use crate::infra::ws_stream::{self, HEARTBEAT_TIMEOUT, PING_INTERVAL};
+use crate::infra::{SolanaProvider, WalletManager};
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use futures_util::{SinkExt, StreamExt};
+use metrics::{counter, gauge};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::protocol::Message;
+use tracing::{debug, info, warn};
+
+const OWNERS_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+
+const BIDS_REQUEST_ID: u64 = 1;
+const ASKS_REQUEST_ID: u64 = 2;
+
+/// Order book state as of the last account update, with a monotonically increasing version.
+#[derive(Debug, Clone)]
+pub struct OrderbookSnapshot {
+    pub version: u64,
+    pub slot: u64,
+    pub orderbook: Orderbook,
+}
+
+pub type OrderbookFeed = watch::Receiver<Option<OrderbookSnapshot>>;
+
+/// Per-connection state: server subscription ids and the newest slot applied to each side.
+#[derive(Debug, Default)]
+struct StreamState {
+    // subscription id -> is_bids
+    subscriptions: HashMap<u64, bool>,
+    bids_slot: u64,
+    asks_slot: u64,
+}
+
+/// Maintains a local copy of the market's bids and asks from `accountSubscribe`
+/// notifications so readers never have to poll RPC for the book. Orders owned by
+/// the swarm are flagged so depth can be read with or without them.
+pub struct OrderbookService {
+    ws_url: String,
+    solana: Arc<dyn SolanaProvider>,
+    wallet_manager: Arc<WalletManager>,
+    market_id: String,
+    sender: watch::Sender<Option<OrderbookSnapshot>>,
+    version: AtomicU64,
+    // wallet -> OpenOrders account, looked up once per wallet
+    open_orders: tokio::sync::Mutex<HashMap<Pubkey, Pubkey>>,
+    own_owners: RwLock<HashSet<String>>,
+}
+
+impl OrderbookService {
+    pub fn new(
+        ws_url: &str,
+        solana: Arc<dyn SolanaProvider>,
+        wallet_manager: Arc<WalletManager>,
+        market_id: &str,
+    ) -> Self {
+        let (sender, _) = watch::channel(None);
+        Self {
+            ws_url: ws_url.to_string(),
+            solana,
+            wallet_manager,
+            market_id: market_id.to_string(),
+            sender,
+            version: AtomicU64::new(0),
+            open_orders: tokio::sync::Mutex::new(HashMap::new()),
+            own_owners: RwLock::new(HashSet::new()),
+        }
+    }
+
+    pub fn subscribe(&self) -> OrderbookFeed {
+        self.sender.subscribe()
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        info!(url = %self.ws_url, market = %self.market_id, "starting_orderbook_service");
+
+        ws_stream::run_with_reconnect(
+            "orderbook",
+            || self.sync_and_stream(),
+            || {
+                gauge!("bot_orderbook_stream_connected", 0.0);
+                // Updates are missed while disconnected, so readers must not trust the old book
+                self.sender.send_replace(None);
+                counter!("bot_orderbook_reconnects_total", 1);
+            },
+        )
+        .await
+    }
+
+    /// Refreshes the set of leaf owners that belong to swarm wallets. Book leaves record
+    /// the OpenOrders account, so each wallet is resolved to its account for this market.
+    async fn refresh_own_owners(&self) {
+        let wallets = self.wallet_manager.get_all_wallets().await;
+        let mut cache = self.open_orders.lock().await;
+        let mut owners = HashSet::new();
+
+        for wallet in wallets {
+            let pubkey = wallet.pubkey();
+            owners.insert(pubkey.to_string());
+            if let Some(oo) = cache.get(&pubkey) {
+                owners.insert(oo.to_string());
+                continue;
+            }
+            match self.solana.find_open_orders(&self.market_id, &pubkey).await {
+                Ok(Some(oo)) => {
+                    cache.insert(pubkey, oo);
+                    owners.insert(oo.to_string());
+                }
+                Ok(None) => {}
+                Err(e) => warn!(error = ?e, wallet = %pubkey, "failed_to_resolve_open_orders"),
+            }
+        }
+        drop(cache);
+
+        gauge!("bot_orderbook_own_owners", owners.len() as f64);
+        *self.own_owners.write().unwrap_or_else(|e| e.into_inner()) = owners;
+    }
+
+    async fn load_layout(&self) -> Result<BookLayout> {
+        let market_pubkey = Pubkey::from_str(&self.market_id)
+            .map_err(|e| anyhow!("Failed to parse market_id '{}': {}", self.market_id, e))?;
+        let market_data = self.solana.get_account_data(&market_pubkey).await?;
+        BookLayout::from_market_data(&market_data)
+    }
+
+    /// Takes a full snapshot over RPC, then applies account notifications on top of it.
+    async fn sync_and_stream(&self) -> Result<()> {
+        let layout = self.load_layout().await?;
+        self.refresh_own_owners().await;
+
+        let (mut write, mut read) = ws_stream::connect(&self.ws_url).await?.split();
+
+        for (id, account) in [
+            (BIDS_REQUEST_ID, layout.bids()),
+            (ASKS_REQUEST_ID, layout.asks()),
+        ] {
+            let sub_request = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "accountSubscribe",
+                "params": [
+                    account.to_string(),
+                    {
+                        "encoding": "base64",
+                        "commitment": "confirmed"
+                    }
+                ]
+            });
+            write
+                .send(Message::Text(sub_request.to_string()))
+                .await
+                .context("failed_to_send_account_subscription")?;
+        }
+        gauge!("bot_orderbook_stream_connected", 1.0);
+
+        // Snapshot after subscribing so no update can fall between the two; notifications
+        // queued behind it that are older than the snapshot are dropped
+        let (bids_slot, bids_data) = self
+            .solana
+            .get_account_data_with_slot(&layout.bids())
+            .await?;
+        let (asks_slot, asks_data) = self
+            .solana
+            .get_account_data_with_slot(&layout.asks())
+            .await?;
+        self.publish(bids_slot.max(asks_slot), |book| {
+            book.bid_orders = layout.parse_side(&bids_data, true)?;
+            book.ask_orders = layout.parse_side(&asks_data, false)?;
+            Ok(())
+        })?;
+        let mut state = StreamState {
+            bids_slot,
+            asks_slot,
+            ..StreamState::default()
+        };
+
+        let mut ping = tokio::time::interval(PING_INTERVAL);
+        ping.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        ping.tick().await;
+        let mut owners_refresh = tokio::time::interval(OWNERS_REFRESH_INTERVAL);
+        owners_refresh.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        owners_refresh.tick().await;
+        let mut last_message = Instant::now();
+
+        loop {
+            let stale_deadline = tokio::time::Instant::from_std(last_message + HEARTBEAT_TIMEOUT);
+
+            tokio::select! {
+                msg = read.next() => {
+                    last_message = Instant::now();
+                    match msg {
+                        Some(Ok(Message::Text(text))) => {
+                            if let Err(e) = self.handle_message(&text, &layout, &mut state) {
+                                warn!(error = %e, "failed_to_handle_orderbook_message");
+                            }
+                        }
+                        Some(Ok(Message::Ping(payload))) => {
+                            write.send(Message::Pong(payload)).await?;
+                        }
+                        Some(Ok(Message::Close(frame))) => {
+                            warn!(?frame, "orderbook_stream_closed");
+                            return Ok(());
+                        }
+                        Some(Ok(_)) => {}
+                        Some(Err(e)) => return Err(e.into()),
+                        None => return Ok(()),
+                    }
+                }
+                _ = ping.tick() => {
+                    write.send(Message::Ping(Vec::new())).await?;
+                }
+                _ = owners_refresh.tick() => {
+                    // Wallets can be added at runtime; re-flag the current book
+                    self.refresh_own_owners().await;
+                    self.publish(0, |_| Ok(()))?;
+                }
+                _ = tokio::time::sleep_until(stale_deadline) => {
+                    return Err(anyhow!(
+                        "no websocket traffic for {}s, treating stream as stale",
+                        HEARTBEAT_TIMEOUT.as_secs()
+                    ));
+                }
+            }
+        }
+    }
+
+    fn handle_message(
+        &self,
+        text: &str,
+        layout: &BookLayout,
+        state: &mut StreamState,
+    ) -> Result<()> {
+        let v: Value = serde_json::from_str(text)?;
+
+        // Subscription confirmations map our request ids to server subscription ids
+        if let (Some(id), Some(sub_id)) = (v["id"].as_u64(), v["result"].as_u64()) {
+            match id {
+                BIDS_REQUEST_ID => state.subscriptions.insert(sub_id, true),
+                ASKS_REQUEST_ID => state.subscriptions.insert(sub_id, false),
+                _ => None,
+            };
+            debug!(id, sub_id, "orderbook_subscription_confirmed");
+            return Ok(());
+        }
+
+        if v["method"] != "accountNotification" {
+            return Ok(());
+        }
+
+        let sub_id = v["params"]["subscription"]
+            .as_u64()
+            .ok_or_else(|| anyhow!("Missing subscription id"))?;
+        let is_bids = *state
+            .subscriptions
+            .get(&sub_id)
+            .ok_or_else(|| anyhow!("Unknown subscription {}", sub_id))?;
+        let result = &v["params"]["result"];
+        let slot = result["context"]["slot"].as_u64().unwrap_or(0);
+        let side = if is_bids { "bids" } else { "asks" };
+        let applied = if is_bids {
+            &mut state.bids_slot
+        } else {
+            &mut state.asks_slot
+        };
+        if slot < *applied {
+            counter!("bot_orderbook_stale_updates_total", 1, "side" => side);
+            debug!(
+                slot,
+                applied = *applied,
+                side,
+                "orderbook_update_older_than_book_dropped"
+            );
+            return Ok(());
+        }
+        *applied = slot;
+        let encoded = result["value"]["data"][0]
+            .as_str()
+            .ok_or_else(|| anyhow!("Missing account data"))?;
+        let data = base64::engine::general_purpose::STANDARD.decode(encoded)?;
+
+        let orders = layout.parse_side(&data, is_bids)?;
+        counter!("bot_orderbook_updates_total", 1, "side" => side);
+
+        self.publish(slot, |book| {
+            if is_bids {
+                book.bid_orders = orders;
+            } else {
+                book.ask_orders = orders;
+            }
+            Ok(())
+        })
+    }
+
+    fn publish<F>(&self, slot: u64, update: F) -> Result<()>
+    where
+        F: FnOnce(&mut Orderbook) -> Result<()>,
+    {
+        let mut next = self
+            .sender
+            .borrow()
+            .clone()
+            .unwrap_or_else(|| OrderbookSnapshot {
+                version: 0,
+                slot: 0,
+                orderbook: Orderbook::from_orders(&self.market_id, 0, Vec::new(), Vec::new()),
+            });
+
+        update(&mut next.orderbook)?;
+        next.orderbook
+            .mark_own_orders(&self.own_owners.read().unwrap_or_else(|e| e.into_inner()));
+        next.version = self.version.fetch_add(1, Ordering::Relaxed) + 1;
+        next.slot = next.slot.max(slot);
+        next.orderbook.timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+
+        gauge!("bot_orderbook_version", next.version as f64);
+        self.sender.send_replace(Some(next));
+        Ok(())
+    }
+}
+
+/// Returns the current book, or `None` while the service is (re)syncing.
+pub fn latest_orderbook(feed: &OrderbookFeed) -> Option<Orderbook> {
+    feed.borrow().as_ref().map(|s| s.orderbook.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::mocks::MockSolanaProvider;
+    use crate::infra::openbook::{MarketStateV2, BOOK_SIDE_DISCRIMINATOR};
+    use rust_decimal_macros::dec;
+
+    fn test_layout() -> BookLayout {
+        BookLayout::V2(MarketStateV2 {
+            bump: 0,
+            base_decimals: 9,
+            quote_decimals: 6,
+            market_authority: Pubkey::new_unique(),
+            bids: Pubkey::new_unique(),
+            asks: Pubkey::new_unique(),
+            event_heap: Pubkey::new_unique(),
+            market_base_vault: Pubkey::new_unique(),
+            market_quote_vault: Pubkey::new_unique(),
+            base_lot_size: 1_000_000,
+            quote_lot_size: 1,
+        })
+    }
+
+    fn book_side_with_leaf(price_lots: u64, quantity_lots: i64, owner: &Pubkey) -> Vec<u8> {
+        let mut data = vec![0u8; 840 + 88];
+        data[0..8].copy_from_slice(&BOOK_SIDE_DISCRIMINATOR);
+        // Fixed-order tree root: node 0, one leaf
+        data[12..16].copy_from_slice(&1u32.to_le_bytes());
+        let leaf = 840;
+        data[leaf] = 2;
+        let key = (price_lots as u128) << 64 | 7;
+        data[leaf + 8..leaf + 24].copy_from_slice(&key.to_le_bytes());
+        data[leaf + 24..leaf + 56].copy_from_slice(&owner.to_bytes());
+        data[leaf + 56..leaf + 64].copy_from_slice(&quantity_lots.to_le_bytes());
+        data[leaf + 64..leaf + 72].copy_from_slice(&1_700_000_000u64.to_le_bytes());
+        data[leaf + 80..leaf + 88].copy_from_slice(&42u64.to_le_bytes());
+        data
+    }
+
+    fn notification(sub_id: u64, slot: u64, data: &[u8]) -> String {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "accountNotification",
+            "params": {
+                "subscription": sub_id,
+                "result": {
+                    "context": { "slot": slot },
+                    "value": {
+                        "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"]
+                    }
+                }
+            }
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_account_notifications_update_feed() {
+        let wallet = solana_sdk::signature::Keypair::new();
+        let swarm_oo = Pubkey::new_unique();
+        let external_oo = Pubkey::new_unique();
+        let wallet_pubkey = wallet.pubkey();
+
+        let mut mock_solana = MockSolanaProvider::new();
+        mock_solana
+            .expect_find_open_orders()
+            .returning(move |_, owner| Ok((*owner == wallet_pubkey).then_some(swarm_oo)));
+        let wallet_manager =
+            Arc::new(WalletManager::new(&[wallet.to_base58_string()], None).unwrap());
+        let service = OrderbookService::new(
+            "ws://localhost",
+            Arc::new(mock_solana),
+            wallet_manager,
+            "market",
+        );
+        service.refresh_own_owners().await;
+        let feed = service.subscribe();
+        let layout = test_layout();
+        let mut state = StreamState::default();
+
+        assert!(latest_orderbook(&feed).is_none());
+
+        service
+            .handle_message(
+                r#"{"jsonrpc":"2.0","result":77,"id":1}"#,
+                &layout,
+                &mut state,
+            )
+            .unwrap();
+        service
+            .handle_message(
+                r#"{"jsonrpc":"2.0","result":78,"id":2}"#,
+                &layout,
+                &mut state,
+            )
+            .unwrap();
+
+        service
+            .handle_message(
+                &notification(77, 100, &book_side_with_leaf(150, 2_000, &external_oo)),
+                &layout,
+                &mut state,
+            )
+            .unwrap();
+        service
+            .handle_message(
+                &notification(78, 101, &book_side_with_leaf(160, 1_000, &swarm_oo)),
+                &layout,
+                &mut state,
+            )
+            .unwrap();
+
+        let snapshot = feed.borrow().clone().unwrap();
+        assert_eq!(snapshot.version, 2);
+        assert_eq!(snapshot.slot, 101);
+        assert_eq!(snapshot.orderbook.bids.len(), 1);
+        assert_eq!(snapshot.orderbook.bids[0].price, dec!(0.15));
+        assert_eq!(snapshot.orderbook.bids[0].size, dec!(2));
+        assert_eq!(snapshot.orderbook.asks[0].price, dec!(0.16));
+
+        // L3 attribution: the ask belongs to the swarm, the bid does not
+        let bid = &snapshot.orderbook.bid_orders[0];
+        assert_eq!(bid.owner, external_oo.to_string());
+        assert_eq!(bid.order_id, (150u128 << 64) | 7);
+        assert_eq!(bid.client_order_id, 42);
+        assert_eq!(bid.timestamp, Some(1_700_000_000));
+        assert!(!bid.is_own);
+        assert!(snapshot.orderbook.ask_orders[0].is_own);
+        assert_eq!(snapshot.orderbook.asks[0].own_size, dec!(1));
+        assert!(snapshot.orderbook.external_asks().is_empty());
+        assert_eq!(snapshot.orderbook.external_bids().len(), 1);
+
+        // Notifications for unknown subscriptions are rejected without touching the book
+        assert!(service
+            .handle_message(
+                &notification(99, 102, &book_side_with_leaf(170, 1, &external_oo)),
+                &layout,
+                &mut state,
+            )
+            .is_err());
+        assert_eq!(feed.borrow().as_ref().unwrap().version, 2);
+
+        // An update older than the side's book, e.g. queued behind the snapshot, is dropped
+        service
+            .handle_message(
+                &notification(77, 99, &book_side_with_leaf(140, 1, &external_oo)),
+                &layout,
+                &mut state,
+            )
+            .unwrap();
+        let snapshot = feed.borrow().clone().unwrap();
+        assert_eq!(snapshot.version, 2);
+        assert_eq!(snapshot.orderbook.bids[0].price, dec!(0.15));
+    }
+}
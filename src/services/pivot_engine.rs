@@ -1,11 +1,62 @@
-use crate::domain::{AssetPosition, MarketUpdate, Trade};
+use crate::domain::{AssetPosition, MarketUpdate, PriceTick, Trade};
+use crate::infra::DatabaseProvider;
+use crate::services::pivot_strategy::{PivotInputs, PivotStrategy, SeedWeight, VwapStrategy};
+use anyhow::Result;
 use rust_decimal::prelude::*;
-use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 use tracing::{info, warn};
 
-#[derive(Debug)]
+const PIVOT_STATE_KEY: &str = "pivot_engine_state";
+
+/// Last pivot and what went into it, persisted each tick so a restart neither resets the
+/// seed-decay clock nor starts from a blank pivot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PivotState {
+    pub seed_started_at: i64,
+    pub last_pivot: Decimal,
+    pub computed_at: i64,
+    pub strategy: String,
+    pub trade_count: usize,
+    pub trade_volume: Decimal,
+    pub market_price: Option<Decimal>,
+    pub seed_remaining_secs: Option<i64>,
+    /// Fills between two swarm accounts in the window, whatever weight they were given.
+    #[serde(default)]
+    pub internal_trade_count: usize,
+}
+
+/// Swarm accounts (wallets and their OpenOrders accounts) and how much fills between two
+/// of them count towards the pivot: 0 drops them, 1 counts them like any other trade.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InternalTradeFilter {
+    pub accounts: HashSet<String>,
+    pub weight: Decimal,
+}
+
+impl InternalTradeFilter {
+    /// Weight of each trade for the strategies: 1 for external fills, the configured weight
+    /// for internal ones. Also returns how many were internal.
+    fn weights(&self, trades: &[Trade]) -> (Vec<Decimal>, usize) {
+        let weight = self.weight.clamp(Decimal::ZERO, Decimal::ONE);
+        let mut internal = 0;
+        let weights = trades
+            .iter()
+            .map(|trade| {
+                if !trade.is_internal(&self.accounts) {
+                    return Decimal::ONE;
+                }
+                internal += 1;
+                weight
+            })
+            .collect();
+        (weights, internal)
+    }
+}
+
 pub struct PivotEngine {
     pub seed_price: Decimal,
     pub lookback_minutes: u32,
@@ -19,6 +70,22 @@ pub struct PivotEngine {
     // In-memory trade cache for responsiveness
     trade_cache: RwLock<VecDeque<Trade>>,
     last_pivot: RwLock<Decimal>,
+    strategy: RwLock<Arc<dyn PivotStrategy>>,
+    // Start of the seed-decay window; restored from the database on boot
+    seed_started_at: RwLock<Option<i64>>,
+    last_state: RwLock<Option<PivotState>>,
+    internal_filter: RwLock<InternalTradeFilter>,
+}
+
+impl std::fmt::Debug for PivotEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PivotEngine")
+            .field("seed_price", &self.seed_price)
+            .field("lookback_minutes", &self.lookback_minutes)
+            .field("lookback_days", &self.lookback_days)
+            .field("nominal_daily_volume", &self.nominal_daily_volume)
+            .finish_non_exhaustive()
+    }
 }
 
 impl PivotEngine {
@@ -44,7 +111,84 @@ impl PivotEngine {
             fee_bps,
             trade_cache: RwLock::new(VecDeque::with_capacity(1000)),
             last_pivot: RwLock::new(seed_price),
+            strategy: RwLock::new(Arc::new(VwapStrategy)),
+            seed_started_at: RwLock::new(None),
+            last_state: RwLock::new(None),
+            internal_filter: RwLock::new(InternalTradeFilter::default()),
+        }
+    }
+
+    /// Restores the persisted state; returns whether there was any.
+    pub async fn load_state(&self, database: &dyn DatabaseProvider) -> Result<bool> {
+        let stored = database.get_state(PIVOT_STATE_KEY).await?;
+        let Some(state) = stored.and_then(|v| serde_json::from_str::<PivotState>(&v).ok()) else {
+            return Ok(false);
+        };
+        info!(
+            seed_started_at = state.seed_started_at,
+            last_pivot = %state.last_pivot,
+            strategy = %state.strategy,
+            "pivot_state_restored"
+        );
+        *self.seed_started_at.write().await = Some(state.seed_started_at);
+        *self.last_pivot.write().await = state.last_pivot;
+        *self.last_state.write().await = Some(state);
+        Ok(true)
+    }
+
+    /// Persists the state of the last `compute_pivot`, if any.
+    pub async fn save_state(&self, database: &dyn DatabaseProvider) -> Result<()> {
+        let state = self.last_state.read().await.clone();
+        if let Some(state) = state {
+            database
+                .set_state(PIVOT_STATE_KEY, &serde_json::to_string(&state)?)
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn last_state(&self) -> Option<PivotState> {
+        self.last_state.read().await.clone()
+    }
+
+    /// Seconds since the seed-decay window opened: the restored start, the oldest cached
+    /// trade or the first call, whichever is earliest.
+    pub async fn seed_elapsed_seconds(&self, now: i64) -> i64 {
+        let oldest_trade = self.cached_trades().await.first().map(|t| t.timestamp);
+        let mut started = self.seed_started_at.write().await;
+        let start = [*started, oldest_trade, Some(now)]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(now);
+        *started = Some(start);
+        now.saturating_sub(start)
+    }
+
+    pub async fn strategy(&self) -> Arc<dyn PivotStrategy> {
+        self.strategy.read().await.clone()
+    }
+
+    /// Swaps the pivot strategy; takes effect on the next `compute_pivot`.
+    pub async fn set_strategy(&self, strategy: Arc<dyn PivotStrategy>) {
+        let mut current = self.strategy.write().await;
+        if current.name() != strategy.name() {
+            info!(
+                from = current.name(),
+                to = strategy.name(),
+                "pivot_strategy_changed"
+            );
         }
+        *current = strategy;
+    }
+
+    pub async fn set_internal_filter(&self, filter: InternalTradeFilter) {
+        *self.internal_filter.write().await = filter;
+    }
+
+    /// Swarm wallets and OpenOrders accounts last handed over by the trading loop.
+    pub async fn swarm_accounts(&self) -> HashSet<String> {
+        self.internal_filter.read().await.accounts.clone()
     }
 
     pub async fn get_last_pivot(&self) -> Decimal {
@@ -69,6 +213,10 @@ impl PivotEngine {
 
     pub async fn record_trade(&self, trade: Trade) {
         let mut cache = self.trade_cache.write().await;
+        // Gap backfills can replay a fill that also arrived on the live stream
+        if cache.iter().rev().any(|t| t.id == trade.id) {
+            return;
+        }
         cache.push_back(trade);
         self.prune_cache_locked(&mut cache);
     }
@@ -98,35 +246,92 @@ impl PivotEngine {
         &self,
         _positions: &[AssetPosition],
         historical_trades: &[Trade],
+        price_history: &[PriceTick],
         market_update: Option<&MarketUpdate>,
         elapsed_seconds: i64,
     ) -> Decimal {
-        let mut total_value = Decimal::ZERO;
-        let mut total_volume = Decimal::ZERO;
-
-        // 1. Calculate from historical trades (from DB)
-        for trade in historical_trades {
-            total_value += trade.price * trade.volume;
-            total_volume += trade.volume;
-        }
-
-        // 2. Include in-memory cached trades (for real-time responsiveness)
+        // 1. Historical trades (from DB) plus in-memory cached trades (for responsiveness)
+        let mut trades = historical_trades.to_vec();
         let cached = self.cached_trades().await;
         for trade in cached {
             // Avoid double counting if trade is already in historical_trades
             if !historical_trades.iter().any(|t| t.id == trade.id) {
-                total_value += trade.price * trade.volume;
-                total_volume += trade.volume;
+                trades.push(trade);
             }
         }
+        trades.sort_by_key(|t| t.timestamp);
 
-        // 3. Include current market data (last 24h) if available
-        if let Some(update) = market_update {
-            total_value += update.price * update.volume_24h;
-            total_volume += update.volume_24h;
-        }
+        // 1a. The swarm trading with itself says nothing about outside demand
+        let (trade_weights, internal_trades) = self.internal_filter.read().await.weights(&trades);
+
+        // 2. Seeded Pivot weight
+        let seed = self.seed_weight(market_update, elapsed_seconds);
+
+        // 3. Strategy
+        let strategy = self.strategy().await;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let inputs = PivotInputs {
+            trades: &trades,
+            trade_weights: &trade_weights,
+            price_history,
+            market_update,
+            seed,
+            now,
+        };
+
+        let pivot = match strategy.compute(&inputs) {
+            Some(pivot) => pivot,
+            None => {
+                warn!(
+                    strategy = strategy.name(),
+                    "no_volume_detected_falling_back_to_seed_or_market"
+                );
+                if !self.seed_price.is_zero() {
+                    self.seed_price
+                } else {
+                    market_update.map(|m| m.price).unwrap_or(Decimal::ZERO)
+                }
+            }
+        };
+
+        let current_price = market_update.map(|m| m.price).unwrap_or(Decimal::ZERO);
+        let counted = inputs.weighted_trades().filter(|(_, w)| !w.is_zero());
+        let (trade_count, trade_volume) = counted.fold((0, Decimal::ZERO), |(n, v), (t, w)| {
+            (n + 1, v + t.volume * w)
+        });
+        info!(
+            strategy = strategy.name(),
+            trades = trade_count,
+            internal_trades,
+            ?pivot,
+            ?current_price,
+            "Pivot computed"
+        );
 
-        // 4. Add Seeded Pivot weight
+        *self.last_pivot.write().await = pivot;
+        *self.last_state.write().await = Some(PivotState {
+            seed_started_at: now - elapsed_seconds.max(0),
+            last_pivot: pivot,
+            computed_at: now,
+            strategy: strategy.name().to_string(),
+            trade_count,
+            trade_volume,
+            market_price: market_update.map(|m| m.price),
+            seed_remaining_secs: seed.map(|s| s.remaining_secs),
+            internal_trade_count: internal_trades,
+        });
+        pivot
+    }
+
+    /// Seed price and volume for the part of the lookback window not yet elapsed.
+    fn seed_weight(
+        &self,
+        market_update: Option<&MarketUpdate>,
+        elapsed_seconds: i64,
+    ) -> Option<SeedWeight> {
         let lookback_days = if self.lookback_minutes > 0 {
             Decimal::from(self.lookback_window_seconds() as u64) / Decimal::from(86_400)
         } else {
@@ -139,47 +344,31 @@ impl PivotEngine {
             Decimal::ZERO
         };
 
-        if remaining_days > Decimal::ZERO {
-            let seed_price = if self.seed_price.is_zero() {
-                market_update.map(|m| m.price).unwrap_or(Decimal::from(100))
-            } else {
-                self.seed_price
-            };
-            let seed_volume = remaining_days * self.nominal_daily_volume;
-
-            total_value += seed_price * seed_volume;
-            total_volume += seed_volume;
-
-            info!(
-                ?remaining_days,
-                ?seed_price,
-                ?seed_volume,
-                "seeded_pivot_active"
-            );
+        if remaining_days <= Decimal::ZERO {
+            return None;
         }
 
-        let pivot = if total_volume.is_zero() {
-            warn!("no_volume_detected_falling_back_to_seed_or_market");
-            if !self.seed_price.is_zero() {
-                self.seed_price
-            } else {
-                market_update.map(|m| m.price).unwrap_or(Decimal::ZERO)
-            }
+        let seed_price = if self.seed_price.is_zero() {
+            market_update.map(|m| m.price).unwrap_or(Decimal::from(100))
         } else {
-            total_value / total_volume
+            self.seed_price
         };
+        let seed_volume = remaining_days * self.nominal_daily_volume;
+        let remaining_secs = (remaining_days * Decimal::from(86_400))
+            .to_i64()
+            .unwrap_or(0);
 
-        let current_price = market_update.map(|m| m.price).unwrap_or(Decimal::ZERO);
         info!(
-            ?total_volume,
-            ?total_value,
-            ?pivot,
-            ?current_price,
-            "Pivot computed (Pure VWAP)"
+            ?remaining_days,
+            ?seed_price,
+            ?seed_volume,
+            "seeded_pivot_active"
         );
-
-        *self.last_pivot.write().await = pivot;
-        pivot
+        Some(SeedWeight {
+            price: seed_price,
+            volume: seed_volume,
+            remaining_secs,
+        })
     }
 
     pub async fn set_last_price(&self, price: Decimal) {
@@ -222,6 +411,10 @@ mod tests {
                     volume: Decimal::from(1),
                     side: OrderSide::Buy,
                     wallet: "w1".to_string(),
+                    slot: None,
+                    block_time: None,
+                    maker: None,
+                    taker: None,
                 })
                 .await;
 
@@ -234,6 +427,10 @@ mod tests {
                     volume: Decimal::from(1),
                     side: OrderSide::Buy,
                     wallet: "w1".to_string(),
+                    slot: None,
+                    block_time: None,
+                    maker: None,
+                    taker: None,
                 })
                 .await;
 
@@ -243,6 +440,120 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn test_state_survives_restart() {
+        use crate::infra::mocks::MockDatabaseProvider;
+        use std::sync::Mutex;
+
+        let engine = || {
+            PivotEngine::new(
+                Decimal::from(2),
+                1,
+                0,
+                Decimal::from(1000),
+                Decimal::ZERO,
+                Decimal::ZERO,
+                Decimal::ZERO,
+                Decimal::ZERO,
+            )
+        };
+        let stored = Arc::new(Mutex::new(None::<String>));
+        let mut database = MockDatabaseProvider::new();
+        let sink = stored.clone();
+        database.expect_set_state().returning(move |_, v| {
+            *sink.lock().unwrap() = Some(v.to_string());
+            Ok(())
+        });
+        let source = stored.clone();
+        database
+            .expect_get_state()
+            .returning(move |_| Ok(source.lock().unwrap().clone()));
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let first = engine();
+        assert!(!first.load_state(&database).await.unwrap());
+        first.seed_elapsed_seconds(now - 3_600).await;
+        let elapsed = first.seed_elapsed_seconds(now).await;
+        assert_eq!(elapsed, 3_600);
+        let pivot = first.compute_pivot(&[], &[], &[], None, elapsed).await;
+        first.save_state(&database).await.unwrap();
+
+        // After a restart the seed clock carries on instead of starting over
+        let second = engine();
+        assert!(second.load_state(&database).await.unwrap());
+        assert_eq!(second.get_last_pivot().await, pivot);
+        assert_eq!(second.seed_elapsed_seconds(now + 60).await, 3_660);
+        assert_eq!(second.last_state().await.unwrap().strategy, "vwap");
+    }
+
+    #[tokio::test]
+    async fn test_internal_trades_excluded_or_down_weighted() {
+        let engine = PivotEngine::new(
+            Decimal::ZERO,
+            1,
+            0,
+            Decimal::from(1000),
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Decimal::ZERO,
+        );
+        let trade = |id: &str, price: i64, maker: &str, taker: &str| Trade {
+            id: id.to_string(),
+            timestamp: 1000,
+            price: Decimal::from(price),
+            volume: Decimal::from(10),
+            side: OrderSide::Buy,
+            wallet: "unknown".to_string(),
+            slot: None,
+            block_time: None,
+            maker: Some(maker.to_string()),
+            taker: Some(taker.to_string()),
+        };
+        let trades = vec![
+            trade("ext", 100, "swarm_oo_a", "outside_oo"),
+            trade("int", 200, "swarm_oo_a", "swarm_oo_b"),
+        ];
+        let accounts: HashSet<String> = ["swarm_oo_a", "swarm_oo_b"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let past_seed = 2 * 86_400;
+
+        // Unfiltered, both fills count
+        let pivot = engine
+            .compute_pivot(&[], &trades, &[], None, past_seed)
+            .await;
+        assert_eq!(pivot, Decimal::from(150));
+
+        engine
+            .set_internal_filter(InternalTradeFilter {
+                accounts: accounts.clone(),
+                weight: Decimal::ZERO,
+            })
+            .await;
+        let pivot = engine
+            .compute_pivot(&[], &trades, &[], None, past_seed)
+            .await;
+        assert_eq!(pivot, Decimal::from(100));
+        assert_eq!(engine.last_state().await.unwrap().internal_trade_count, 1);
+
+        // (100 * 10 + 200 * 2.5) / 12.5
+        engine
+            .set_internal_filter(InternalTradeFilter {
+                accounts,
+                weight: Decimal::new(25, 2),
+            })
+            .await;
+        let pivot = engine
+            .compute_pivot(&[], &trades, &[], None, past_seed)
+            .await;
+        assert_eq!(pivot, Decimal::from(120));
+    }
+
     #[test]
     fn test_compute_pivot_with_cache_no_double_count() {
         let engine = PivotEngine::new(
@@ -263,6 +574,10 @@ mod tests {
             volume: Decimal::from(10),
             side: OrderSide::Buy,
             wallet: "w1".to_string(),
+            slot: None,
+            block_time: None,
+            maker: None,
+            taker: None,
         };
 
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -275,7 +590,7 @@ mod tests {
             // Pivot should be 100, not skewed if double counting happened
             // (100 * 10) / 10 = 100
             let pivot = engine
-                .compute_pivot(&[], &historical_trades, None, 366 * 86_400)
+                .compute_pivot(&[], &historical_trades, &[], None, 366 * 86_400)
                 .await;
             assert_eq!(pivot, Decimal::from(100));
         });
@@ -0,0 +1,365 @@
+use crate::domain::{MarketUpdate, PriceTick, Trade};
+use crate::utils::settings::{PivotStrategyKind, PivotVwapSettings};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Synthetic history standing in for the part of the lookback window not yet observed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeedWeight {
+    pub price: Decimal,
+    pub volume: Decimal,
+    pub remaining_secs: i64,
+}
+
+pub struct PivotInputs<'a> {
+    /// Trades in the lookback window, oldest first, without duplicates.
+    pub trades: &'a [Trade],
+    /// How much each of `trades` counts, in the same order: 1 for external fills and
+    /// `internal_trade_weight` for fills between two swarm accounts.
+    pub trade_weights: &'a [Decimal],
+    /// Price ticks in the lookback window, oldest first; only loaded for strategies that ask.
+    pub price_history: &'a [PriceTick],
+    pub market_update: Option<&'a MarketUpdate>,
+    pub seed: Option<SeedWeight>,
+    pub now: i64,
+}
+
+impl PivotInputs<'_> {
+    /// Trades with their weight; trades without one count fully.
+    pub fn weighted_trades(&self) -> impl Iterator<Item = (&Trade, Decimal)> {
+        self.trades.iter().enumerate().map(|(i, t)| {
+            let weight = self.trade_weights.get(i).copied().unwrap_or(Decimal::ONE);
+            (t, weight)
+        })
+    }
+}
+
+/// How the pivot is derived from the trade and price history.
+pub trait PivotStrategy: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Whether `PivotInputs::price_history` has to be loaded.
+    fn uses_price_history(&self) -> bool {
+        false
+    }
+
+    /// `None` when the inputs are not enough to produce a pivot.
+    fn compute(&self, inputs: &PivotInputs) -> Option<Decimal>;
+}
+
+pub fn from_settings(settings: &PivotVwapSettings) -> Arc<dyn PivotStrategy> {
+    match settings.strategy {
+        PivotStrategyKind::Vwap => Arc::new(VwapStrategy),
+        PivotStrategyKind::Twap => Arc::new(TwapStrategy),
+        PivotStrategyKind::Ema => Arc::new(EmaStrategy {
+            span: settings.ema_span.max(1),
+        }),
+        PivotStrategyKind::FullCostBasis => Arc::new(FullCostBasisStrategy {
+            market_id_rent_sol: settings.market_id_rent_sol,
+            account_rent_sol: settings.account_rent_sol,
+            jito_tip_sol: settings.jito_tip_sol,
+            fee_bps: settings.fee_bps,
+        }),
+    }
+}
+
+/// Value and volume of the weighted trades and the seed. The market update's 24h volume
+/// is summed from the same trade store as `trades`, including internal fills, so adding
+/// it would count those trades twice.
+fn volume_totals(inputs: &PivotInputs) -> (Decimal, Decimal) {
+    let mut total_value = Decimal::ZERO;
+    let mut total_volume = Decimal::ZERO;
+
+    for (trade, weight) in inputs.weighted_trades() {
+        total_value += trade.price * trade.volume * weight;
+        total_volume += trade.volume * weight;
+    }
+    if let Some(seed) = inputs.seed {
+        total_value += seed.price * seed.volume;
+        total_volume += seed.volume;
+    }
+    (total_value, total_volume)
+}
+
+pub struct VwapStrategy;
+
+impl PivotStrategy for VwapStrategy {
+    fn name(&self) -> &'static str {
+        "vwap"
+    }
+
+    fn compute(&self, inputs: &PivotInputs) -> Option<Decimal> {
+        let (value, volume) = volume_totals(inputs);
+        (!volume.is_zero()).then(|| value / volume)
+    }
+}
+
+/// Time-weighted average of the live price ticks; each tick holds until the next one.
+/// Backfilled rows only carry a placeholder asset price and are skipped. A tick whose
+/// latest preceding fill was internal reflects the swarm trading with itself, so it holds
+/// at that fill's weight.
+pub struct TwapStrategy;
+
+impl PivotStrategy for TwapStrategy {
+    fn name(&self) -> &'static str {
+        "twap"
+    }
+
+    fn uses_price_history(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, inputs: &PivotInputs) -> Option<Decimal> {
+        // Several sources can tick in the same second; average them first
+        let mut by_ts: BTreeMap<i64, (Decimal, u32)> = BTreeMap::new();
+        for tick in inputs.price_history {
+            if tick.has_live_asset_price() && tick.asset_price > Decimal::ZERO {
+                let entry = by_ts.entry(tick.timestamp).or_default();
+                entry.0 += tick.asset_price;
+                entry.1 += 1;
+            }
+        }
+        let ticks: Vec<(i64, Decimal)> = by_ts
+            .into_iter()
+            .map(|(ts, (sum, n))| (ts, sum / Decimal::from(n)))
+            .collect();
+
+        let fills: Vec<(&Trade, Decimal)> = inputs.weighted_trades().collect();
+        let mut total_value = Decimal::ZERO;
+        let mut total_secs = Decimal::ZERO;
+        for (i, (ts, price)) in ticks.iter().enumerate() {
+            let until = ticks.get(i + 1).map_or(inputs.now, |(next, _)| *next);
+            let weight = fills
+                .iter()
+                .rev()
+                .find(|(t, _)| t.timestamp <= *ts)
+                .map_or(Decimal::ONE, |(_, w)| *w);
+            let held = Decimal::from((until - ts).max(0)) * weight;
+            total_value += price * held;
+            total_secs += held;
+        }
+        if let Some(seed) = inputs.seed {
+            let held = Decimal::from(seed.remaining_secs.max(0));
+            total_value += seed.price * held;
+            total_secs += held;
+        }
+
+        if total_secs.is_zero() {
+            // A single tick at `now` carries no duration yet
+            return ticks.last().map(|(_, p)| *p);
+        }
+        Some(total_value / total_secs)
+    }
+}
+
+/// Exponential moving average over trade prices, oldest first, with the current market
+/// price as the newest observation. Each trade moves the average by its weight.
+pub struct EmaStrategy {
+    pub span: u32,
+}
+
+impl PivotStrategy for EmaStrategy {
+    fn name(&self) -> &'static str {
+        "ema"
+    }
+
+    fn compute(&self, inputs: &PivotInputs) -> Option<Decimal> {
+        let alpha = Decimal::from(2) / Decimal::from(self.span + 1);
+        let observations = inputs
+            .seed
+            .map(|s| (s.price, Decimal::ONE))
+            .into_iter()
+            .chain(inputs.weighted_trades().map(|(t, w)| (t.price, w)))
+            .chain(inputs.market_update.map(|m| (m.price, Decimal::ONE)))
+            .filter(|(p, w)| *p > Decimal::ZERO && *w > Decimal::ZERO);
+
+        observations.fold(None, |ema, (price, weight)| {
+            Some(match ema {
+                None => price,
+                Some(prev) => prev + alpha * weight * (price - prev),
+            })
+        })
+    }
+}
+
+/// Break-even price of the accumulated inventory: VWAP plus venue fees, with the one-off
+/// market and account rent and a Jito tip per trade amortised over the volume.
+pub struct FullCostBasisStrategy {
+    pub market_id_rent_sol: Decimal,
+    pub account_rent_sol: Decimal,
+    pub jito_tip_sol: Decimal,
+    pub fee_bps: Decimal,
+}
+
+impl PivotStrategy for FullCostBasisStrategy {
+    fn name(&self) -> &'static str {
+        "full_cost_basis"
+    }
+
+    fn compute(&self, inputs: &PivotInputs) -> Option<Decimal> {
+        let (value, volume) = volume_totals(inputs);
+        if volume.is_zero() {
+            return None;
+        }
+        let fee_rate = self.fee_bps / Decimal::from(10_000);
+        let counted = inputs
+            .weighted_trades()
+            .filter(|(_, w)| !w.is_zero())
+            .count();
+        let overhead = self.market_id_rent_sol
+            + self.account_rent_sol
+            + self.jito_tip_sol * Decimal::from(counted);
+        Some(value / volume * (Decimal::ONE + fee_rate) + overhead / volume)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::OrderSide;
+    use rust_decimal_macros::dec;
+
+    fn trade(timestamp: i64, price: Decimal, volume: Decimal) -> Trade {
+        Trade {
+            id: timestamp.to_string(),
+            timestamp,
+            price,
+            volume,
+            side: OrderSide::Buy,
+            wallet: "w1".to_string(),
+            slot: None,
+            block_time: None,
+            maker: None,
+            taker: None,
+        }
+    }
+
+    fn tick(timestamp: i64, asset_price: Decimal) -> PriceTick {
+        PriceTick {
+            timestamp,
+            asset_price,
+            sol_price: dec!(150),
+            source: "trading_loop".to_string(),
+        }
+    }
+
+    fn inputs<'a>(trades: &'a [Trade], history: &'a [PriceTick]) -> PivotInputs<'a> {
+        PivotInputs {
+            trades,
+            trade_weights: &[],
+            price_history: history,
+            market_update: None,
+            seed: None,
+            now: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_twap_weights_ticks_by_duration() {
+        // 10 held for 100s, 20 held for 300s (until now)
+        let history = [
+            tick(600, dec!(10)),
+            tick(700, dec!(20)),
+            tick(700, dec!(20)),
+            // Placeholder price from the startup backfill
+            PriceTick {
+                source: PriceTick::BINANCE_BACKFILL.to_string(),
+                ..tick(800, dec!(1))
+            },
+            // Price held by the guard while the feed was stale
+            PriceTick {
+                source: PriceTick::TRADING_LOOP_HELD.to_string(),
+                ..tick(900, dec!(2))
+            },
+        ];
+        let twap = TwapStrategy.compute(&inputs(&[], &history)).unwrap();
+        assert_eq!(twap, dec!(17.5));
+
+        // The seed fills the unobserved part of the window
+        let seeded = PivotInputs {
+            seed: Some(SeedWeight {
+                price: dec!(5),
+                volume: Decimal::ZERO,
+                remaining_secs: 400,
+            }),
+            ..inputs(&[], &history)
+        };
+        assert_eq!(TwapStrategy.compute(&seeded).unwrap(), dec!(11.25));
+        assert_eq!(TwapStrategy.compute(&inputs(&[], &[])), None);
+    }
+
+    #[test]
+    fn test_ema_follows_recent_trades() {
+        let trades = [
+            trade(1, dec!(10), dec!(1)),
+            trade(2, dec!(10), dec!(1)),
+            trade(3, dec!(16), dec!(1)),
+        ];
+        // alpha = 2 / (2 + 1)
+        let ema = EmaStrategy { span: 2 }
+            .compute(&inputs(&trades, &[]))
+            .unwrap();
+        assert_eq!(ema.round_dp(6), dec!(14));
+
+        let vwap = VwapStrategy.compute(&inputs(&trades, &[])).unwrap();
+        assert_eq!(vwap, dec!(12));
+
+        // The 24h volume covers the same trades and is not counted again
+        let update = MarketUpdate {
+            timestamp: 3,
+            price: dec!(100),
+            volume_24h: dec!(3),
+            trade_count_24h: 3,
+        };
+        let with_update = PivotInputs {
+            market_update: Some(&update),
+            ..inputs(&trades, &[])
+        };
+        assert_eq!(VwapStrategy.compute(&with_update).unwrap(), dec!(12));
+    }
+
+    #[test]
+    fn test_internal_weight_applies_to_ema_and_twap() {
+        let trades = [
+            trade(1, dec!(10), dec!(1)),
+            trade(2, dec!(40), dec!(1)),
+            trade(3, dec!(16), dec!(1)),
+        ];
+        // The middle fill is internal and left out; the last counts at half weight
+        let weighted = PivotInputs {
+            trade_weights: &[Decimal::ONE, Decimal::ZERO, dec!(0.5)],
+            ..inputs(&trades, &[])
+        };
+        // 10 + 2/3 * 0.5 * (16 - 10)
+        let ema = EmaStrategy { span: 2 }.compute(&weighted).unwrap();
+        assert_eq!(ema.round_dp(6), dec!(12));
+
+        // The tick at 700 follows an internal fill at half weight:
+        // (10 * 100 + 20 * 300 * 0.5) / (100 + 150)
+        let fills = [trade(650, dec!(20), dec!(1))];
+        let history = [tick(600, dec!(10)), tick(700, dec!(20))];
+        let twap = TwapStrategy
+            .compute(&PivotInputs {
+                trade_weights: &[dec!(0.5)],
+                ..inputs(&fills, &history)
+            })
+            .unwrap();
+        assert_eq!(twap, dec!(16));
+    }
+
+    #[test]
+    fn test_full_cost_basis_adds_fees_rent_and_tips() {
+        let trades = [trade(1, dec!(1), dec!(10)), trade(2, dec!(2), dec!(10))];
+        let strategy = FullCostBasisStrategy {
+            market_id_rent_sol: dec!(0.4),
+            account_rent_sol: dec!(0.1),
+            jito_tip_sol: dec!(0.25),
+            fee_bps: dec!(100),
+        };
+        // VWAP 1.5 * 1.01 + (0.4 + 0.1 + 2 * 0.25) / 20
+        let pivot = strategy.compute(&inputs(&trades, &[])).unwrap();
+        assert_eq!(pivot, dec!(1.565));
+        assert_eq!(strategy.compute(&inputs(&[], &[])), None);
+    }
+}
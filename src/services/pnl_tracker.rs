@@ -10,6 +10,19 @@ pub struct PnlSnapshot {
     pub average_cost: Decimal,
 }
 
+impl PnlSnapshot {
+    /// Re-expresses the SOL-denominated figures at `rate` units per SOL; the position
+    /// itself is in tokens and stays as is.
+    pub fn converted(&self, rate: Decimal) -> PnlSnapshot {
+        PnlSnapshot {
+            realized_pnl: self.realized_pnl * rate,
+            unrealized_pnl: self.unrealized_pnl * rate,
+            net_position: self.net_position,
+            average_cost: self.average_cost * rate,
+        }
+    }
+}
+
 pub struct PnlTracker {
     net_position: Decimal,
     average_cost: Decimal,
@@ -154,6 +167,21 @@ mod tests {
         assert_eq!(snapshot.unrealized_pnl, Decimal::from(25)); // (95-90)*5
     }
 
+    #[test]
+    fn converts_snapshot_to_fiat() {
+        let mut tracker = PnlTracker::default();
+        tracker.record_trade(OrderSide::Buy, Decimal::from(2), Decimal::from(10));
+        tracker.record_trade(OrderSide::Sell, Decimal::from(3), Decimal::from(5));
+
+        let fiat = tracker
+            .snapshot(Decimal::from(4))
+            .converted(Decimal::from(150));
+        assert_eq!(fiat.realized_pnl, Decimal::from(750));
+        assert_eq!(fiat.unrealized_pnl, Decimal::from(1500));
+        assert_eq!(fiat.average_cost, Decimal::from(300));
+        assert_eq!(fiat.net_position, Decimal::from(5));
+    }
+
     #[test]
     fn handles_zero_volume_and_negative_prices() {
         let mut tracker = PnlTracker::default();
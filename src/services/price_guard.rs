@@ -0,0 +1,257 @@
+use crate::infra::PriceFeed;
+use crate::utils::settings::PriceGuardSettings;
+use metrics::{counter, gauge};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::warn;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PriceRejection {
+    Missing,
+    NonPositive(Decimal),
+    Jump {
+        last: Decimal,
+        candidate: Decimal,
+        change_percent: Decimal,
+        allowed_percent: Decimal,
+    },
+}
+
+impl PriceRejection {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PriceRejection::Missing => "missing",
+            PriceRejection::NonPositive(_) => "non_positive",
+            PriceRejection::Jump { .. } => "jump",
+        }
+    }
+}
+
+impl std::fmt::Display for PriceRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PriceRejection::Missing => write!(f, "no price available"),
+            PriceRejection::NonPositive(price) => write!(f, "non-positive price {}", price),
+            PriceRejection::Jump {
+                last,
+                candidate,
+                change_percent,
+                allowed_percent,
+            } => write!(
+                f,
+                "price jumped {}% ({} -> {}), allowed {}%",
+                change_percent.round_dp(2),
+                last,
+                candidate,
+                allowed_percent
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotingState {
+    Active,
+    /// Last good price is too old to quote on; resting orders are left alone.
+    Paused,
+    /// Last good price is so old that resting orders should be pulled.
+    Halted,
+}
+
+#[derive(Debug, Clone)]
+pub struct GuardedPrice {
+    /// Last good price, which is the candidate itself when it was accepted.
+    pub price: Option<Decimal>,
+    pub age_secs: i64,
+    pub state: QuotingState,
+    pub rejection: Option<PriceRejection>,
+    /// Set only on the check that first enters `Halted`.
+    pub cancel_orders: bool,
+}
+
+#[derive(Debug, Default)]
+struct FeedState {
+    last_good: Option<(Decimal, i64)>,
+    halted: bool,
+}
+
+/// Sits between the price sources and quoting: rejects implausible jumps, tracks how long
+/// it has been since the last good price and escalates from pausing to cancelling.
+pub struct PriceGuard {
+    started_at: i64,
+    feeds: Mutex<HashMap<PriceFeed, FeedState>>,
+}
+
+impl PriceGuard {
+    pub fn new(started_at: i64) -> Self {
+        Self {
+            started_at,
+            feeds: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn check(
+        &self,
+        feed: PriceFeed,
+        candidate: Option<Decimal>,
+        now: i64,
+        settings: &PriceGuardSettings,
+    ) -> GuardedPrice {
+        let mut feeds = self.feeds.lock().unwrap_or_else(|e| e.into_inner());
+        let state = feeds.entry(feed).or_default();
+
+        let rejection = if settings.enabled {
+            Self::validate(state.last_good, candidate, now, settings)
+        } else {
+            candidate
+                .filter(|p| *p > Decimal::ZERO)
+                .is_none()
+                .then_some(PriceRejection::Missing)
+        };
+
+        if rejection.is_none() {
+            let price = candidate.unwrap_or_default();
+            state.last_good = Some((price, now));
+            state.halted = false;
+            gauge!("bot_price_age_seconds", 0.0, "feed" => feed.as_str());
+            return GuardedPrice {
+                price: Some(price),
+                age_secs: 0,
+                state: QuotingState::Active,
+                rejection: None,
+                cancel_orders: false,
+            };
+        }
+        let rejection = rejection.unwrap_or(PriceRejection::Missing);
+        counter!("bot_price_guard_rejections_total", 1, "feed" => feed.as_str(), "reason" => rejection.as_str());
+
+        let age_secs = now - state.last_good.map_or(self.started_at, |(_, ts)| ts);
+        gauge!("bot_price_age_seconds", age_secs as f64, "feed" => feed.as_str());
+
+        let quoting = if age_secs >= settings.cancel_after_secs as i64 {
+            QuotingState::Halted
+        } else if age_secs >= settings.pause_after_secs as i64 || state.last_good.is_none() {
+            QuotingState::Paused
+        } else {
+            QuotingState::Active
+        };
+        let cancel_orders = quoting == QuotingState::Halted && !state.halted;
+        state.halted = quoting == QuotingState::Halted;
+
+        warn!(feed = feed.as_str(), %rejection, age_secs, state = ?quoting, "price_rejected_by_guard");
+        GuardedPrice {
+            price: state.last_good.map(|(p, _)| p),
+            age_secs,
+            state: quoting,
+            rejection: Some(rejection),
+            cancel_orders,
+        }
+    }
+
+    fn validate(
+        last_good: Option<(Decimal, i64)>,
+        candidate: Option<Decimal>,
+        now: i64,
+        settings: &PriceGuardSettings,
+    ) -> Option<PriceRejection> {
+        let candidate = match candidate {
+            None => return Some(PriceRejection::Missing),
+            Some(p) if p <= Decimal::ZERO => return Some(PriceRejection::NonPositive(p)),
+            Some(p) => p,
+        };
+        let (last, last_ts) = last_good?;
+
+        // The allowance grows with each interval since the last good price, so a real
+        // move is accepted eventually instead of halting forever.
+        let interval = settings.jump_interval_secs.max(1) as i64;
+        let intervals = ((now - last_ts).max(0) + interval - 1) / interval;
+        let allowed_percent = settings.max_jump_percent * Decimal::from(intervals.max(1));
+        let change_percent = ((candidate - last) / last).abs() * Decimal::from(100);
+
+        (change_percent > allowed_percent).then_some(PriceRejection::Jump {
+            last,
+            candidate,
+            change_percent,
+            allowed_percent,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn settings() -> PriceGuardSettings {
+        PriceGuardSettings {
+            enabled: true,
+            max_jump_percent: dec!(10),
+            jump_interval_secs: 60,
+            pause_after_secs: 120,
+            cancel_after_secs: 600,
+        }
+    }
+
+    #[test]
+    fn test_rejects_jumps_and_keeps_last_good_price() {
+        let guard = PriceGuard::new(0);
+        let s = settings();
+
+        let first = guard.check(PriceFeed::AssetSol, Some(dec!(100)), 10, &s);
+        assert_eq!(first.price, Some(dec!(100)));
+        assert_eq!(first.state, QuotingState::Active);
+
+        // +50% within one interval is rejected, quoting continues on the last good price
+        let jump = guard.check(PriceFeed::AssetSol, Some(dec!(150)), 20, &s);
+        assert!(matches!(jump.rejection, Some(PriceRejection::Jump { .. })));
+        assert_eq!(jump.price, Some(dec!(100)));
+        assert_eq!(jump.state, QuotingState::Active);
+
+        // The same level is plausible after enough intervals have passed
+        let later = guard.check(PriceFeed::AssetSol, Some(dec!(140)), 10 + 5 * 60, &s);
+        assert!(later.rejection.is_none());
+        assert_eq!(later.price, Some(dec!(140)));
+    }
+
+    #[test]
+    fn test_pauses_then_cancels_once_when_price_goes_stale() {
+        let guard = PriceGuard::new(0);
+        let s = settings();
+        guard.check(PriceFeed::AssetSol, Some(dec!(100)), 0, &s);
+
+        let blip = guard.check(PriceFeed::AssetSol, None, 60, &s);
+        assert_eq!(blip.state, QuotingState::Active);
+
+        let paused = guard.check(PriceFeed::AssetSol, None, 130, &s);
+        assert_eq!(paused.state, QuotingState::Paused);
+        assert!(!paused.cancel_orders);
+
+        let halted = guard.check(PriceFeed::AssetSol, Some(dec!(0)), 600, &s);
+        assert_eq!(halted.state, QuotingState::Halted);
+        assert!(halted.cancel_orders);
+
+        let still_halted = guard.check(PriceFeed::AssetSol, None, 700, &s);
+        assert_eq!(still_halted.state, QuotingState::Halted);
+        assert!(!still_halted.cancel_orders);
+
+        let recovered = guard.check(PriceFeed::AssetSol, Some(dec!(101)), 710, &s);
+        assert_eq!(recovered.state, QuotingState::Active);
+        assert_eq!(recovered.age_secs, 0);
+    }
+
+    #[test]
+    fn test_no_price_yet_pauses_without_cancelling() {
+        let guard = PriceGuard::new(1000);
+        let s = settings();
+
+        let startup = guard.check(PriceFeed::SolUsd, None, 1010, &s);
+        assert_eq!(startup.state, QuotingState::Paused);
+        assert_eq!(startup.price, None);
+        assert!(!startup.cancel_orders);
+
+        // Feeds are tracked independently
+        let asset = guard.check(PriceFeed::AssetSol, Some(dec!(1)), 1010, &s);
+        assert_eq!(asset.state, QuotingState::Active);
+    }
+}
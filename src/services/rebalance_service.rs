@@ -35,6 +35,10 @@ impl RebalanceService {
         *last_grid_lock = grid;
     }
 
+    pub fn last_grid(&self) -> Vec<crate::domain::GridLevel> {
+        self.last_grid.lock().unwrap().clone()
+    }
+
     pub async fn should_rebuild(&self, current_pivot: Decimal, current_price: Decimal) -> bool {
         let now = std::time::Instant::now();
 
@@ -1,4 +1,5 @@
 use crate::infra::{SolanaProvider, WalletManager};
+use crate::services::{CostLedger, TransactionCosts};
 use crate::utils::BotSettings;
 use anyhow::Result;
 use rust_decimal::Decimal;
@@ -9,6 +10,7 @@ pub struct RentRecoveryService {
     solana: std::sync::Arc<dyn SolanaProvider>,
     wallet_manager: std::sync::Arc<WalletManager>,
     _settings: std::sync::Arc<tokio::sync::RwLock<BotSettings>>,
+    cost_ledger: std::sync::Arc<CostLedger>,
 }
 
 impl RentRecoveryService {
@@ -16,20 +18,22 @@ impl RentRecoveryService {
         solana: std::sync::Arc<dyn SolanaProvider>,
         wallet_manager: std::sync::Arc<WalletManager>,
         _settings: std::sync::Arc<tokio::sync::RwLock<BotSettings>>,
+        cost_ledger: std::sync::Arc<CostLedger>,
     ) -> Self {
         Self {
             solana,
             wallet_manager,
             _settings,
+            cost_ledger,
         }
     }
 
     pub async fn recover_rent(&self) -> Result<()> {
         info!("Rent Recovery Service: scanning for closed accounts");
 
-        let market_id = {
+        let (market_id, account_rent_sol) = {
             let s = self._settings.read().await;
-            s.openbook_market_id.clone()
+            (s.openbook_market_id.clone(), s.pivot_vwap.account_rent_sol)
         };
         let wallets = self.wallet_manager.get_all_wallets().await;
         let mut total_reclaimed = Decimal::ZERO;
@@ -49,7 +53,21 @@ impl RentRecoveryService {
                             {
                                 Ok(sig) => {
                                     info!(%sig, "Closed OpenOrders account and reclaimed rent");
-                                    total_reclaimed += Decimal::new(23, 3); // ~0.023 SOL
+                                    total_reclaimed += account_rent_sol;
+                                    let owner = owner.to_string();
+                                    self.cost_ledger
+                                        .record_rent_reclaimed(&sig, &owner, account_rent_sol)
+                                        .await;
+                                    self.cost_ledger
+                                        .record_transaction(
+                                            &sig,
+                                            &owner,
+                                            TransactionCosts {
+                                                signatures: 1,
+                                                ..TransactionCosts::default()
+                                            },
+                                        )
+                                        .await;
                                 }
                                 Err(e) => {
                                     warn!(error = %e, %oo_pubkey, "Failed to close OpenOrders account")
@@ -1,7 +1,8 @@
-use anyhow::{anyhow, Result};
+use crate::infra::HttpClient;
+use anyhow::Result;
 use metrics::gauge;
-use reqwest::Client;
 use serde::Deserialize;
+use std::sync::Arc;
 use tracing::info;
 
 #[derive(Debug, Deserialize)]
@@ -10,35 +11,21 @@ struct RugCheckReport {
 }
 
 pub struct RugCheckService {
-    client: Client,
-}
-
-impl Default for RugCheckService {
-    fn default() -> Self {
-        Self::new()
-    }
+    http: Arc<HttpClient>,
 }
 
 impl RugCheckService {
-    pub fn new() -> Self {
-        Self {
-            client: Client::new(),
-        }
+    pub fn new(http: Arc<HttpClient>) -> Self {
+        Self { http }
     }
 
     pub async fn fetch_score(&self, mint: &str) -> Result<i64> {
-        let url = format!("https://rugcheck.xyz/api/v1/tokens/{}/report", mint);
-
-        let response = self.client.get(&url).send().await?;
-
-        if !response.status().is_success() {
-            return Err(anyhow!(
-                "RugCheck API failed with status: {}",
-                response.status()
-            ));
-        }
-
-        let report: RugCheckReport = response.json().await?;
+        let url = format!("{}/tokens/{}/report", self.http.urls().rugcheck, mint);
+        let report: RugCheckReport = self
+            .http
+            .get_json(&url)
+            .await
+            .map_err(|e| e.context("RugCheck API failed"))?;
 
         info!(mint, score = report.score, "RugCheck score updated");
         gauge!("bot_rugcheck_score", report.score as f64);
@@ -1,5 +1,5 @@
 use crate::domain::{GridLevel, OrderSide};
-use crate::services::GridBuilder;
+use crate::services::{GridBuilder, LadderReport};
 use rust_decimal::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -26,6 +26,8 @@ pub struct SimulationResult {
     pub scenario_name: String,
     pub price_history: Vec<SimulationPriceTick>,
     pub projected_grids: Vec<Vec<GridLevel>>,
+    /// What fitting each projected grid to the market did to it, in the same order.
+    pub ladder_reports: Vec<LadderReport>,
     pub density_distribution: Vec<DensityLevel>,
     pub total_buy_orders: usize,
     pub total_sell_orders: usize,
@@ -61,10 +63,25 @@ impl SimulationEngine {
         base_price: Decimal,
         steps: usize,
         volatility: Decimal,
+    ) -> SimulationResult {
+        self.run_simulation_with(&self.grid_builder, scenario, base_price, steps, volatility)
+            .await
+    }
+
+    /// Same as `run_simulation` but projects the ladder of `grid_builder`, so a config can
+    /// be previewed before it is applied.
+    pub async fn run_simulation_with(
+        &self,
+        grid_builder: &GridBuilder,
+        scenario: ScenarioType,
+        base_price: Decimal,
+        steps: usize,
+        volatility: Decimal,
     ) -> SimulationResult {
         let scenario_name = format!("{:?}", scenario);
         let price_history = self.generate_prices(scenario, base_price, steps, volatility);
         let mut projected_grids = Vec::with_capacity(steps);
+        let mut ladder_reports = Vec::with_capacity(steps);
 
         let mut total_buy = 0;
         let mut total_sell = 0;
@@ -79,9 +96,8 @@ impl SimulationEngine {
                 max_p = tick.price;
             }
 
-            let grid = self
-                .grid_builder
-                .build(tick.price, Decimal::from(100))
+            let (grid, report) = grid_builder
+                .build_with_report(tick.price, Decimal::from(100))
                 .await;
 
             for level in &grid {
@@ -91,6 +107,7 @@ impl SimulationEngine {
                 }
             }
             projected_grids.push(grid);
+            ladder_reports.push(report);
         }
 
         let density_distribution = self.calculate_density(&projected_grids);
@@ -99,6 +116,7 @@ impl SimulationEngine {
             scenario_name,
             price_history,
             projected_grids,
+            ladder_reports,
             density_distribution,
             total_buy_orders: total_buy,
             total_sell_orders: total_sell,
@@ -1,19 +1,44 @@
-use crate::domain::OrderSide;
-use crate::infra::{DatabaseProvider, KillSwitch, PriceAggregator, SolanaProvider, WalletManager};
+use crate::domain::{OrderSide, PriceTick};
+use crate::infra::openbook::BookLayout;
+use crate::infra::{
+    DatabaseProvider, HttpClient, KillSwitch, PriceAggregator, PriceFeed, SolanaProvider,
+    WalletManager,
+};
+use crate::services::pivot_engine::InternalTradeFilter;
 use crate::services::{
-    FinancialManager, FlashVolumeModule, GridBuilder, PivotEngine, PnlTracker, RebalanceService,
-    RentRecoveryService, RiskManager, RiskSnapshot, RugCheckService,
+    ChannelWidthController, CostLedger, FiatService, FinancialManager, FlashVolumeModule,
+    GridBuilder, InventorySkew, LevelAllocator, MarketConstraints, OrderbookFeed, PivotEngine,
+    PnlTracker, PriceGuard, QuotingState, RebalanceService, RentRecoveryService, RiskManager,
+    RiskSnapshot, RugCheckService, SideBudgets, SolReserves, TransactionCosts, WalletBalance,
 };
+use crate::utils::settings::ChannelBoundsSettings;
 use crate::utils::BotSettings;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use metrics::{counter, gauge};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signer::Signer;
-use std::collections::HashSet;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tracing::{debug, error, info, warn};
 
+/// How long a wallet without an OpenOrders account, or whose lookup failed, is left
+/// alone before the program accounts are scanned for it again.
+const OPEN_ORDERS_MISS_TTL: Duration = Duration::from_secs(60);
+
+/// How long wallet balances are reused before they are read again.
+const WALLET_BALANCE_TTL: Duration = Duration::from_secs(30);
+
+/// A wallet's balances as last read, in whole units.
+#[derive(Clone, Copy)]
+struct CachedBalance {
+    sol: Decimal,
+    token: Decimal,
+    read_at: Instant,
+}
+
 pub struct TradingService {
     solana: std::sync::Arc<dyn SolanaProvider>,
     database: std::sync::Arc<dyn DatabaseProvider>,
@@ -32,10 +57,25 @@ pub struct TradingService {
     rent_recovery: RentRecoveryService,
     rugcheck: RugCheckService,
     price_aggregator: std::sync::Arc<PriceAggregator>,
+    price_guard: PriceGuard,
+    // market -> layout, read once
+    market_layouts: tokio::sync::Mutex<HashMap<String, BookLayout>>,
+    channel_widths: std::sync::Arc<ChannelWidthController>,
+    level_allocator: LevelAllocator,
+    fiat: FiatService,
+    cost_ledger: std::sync::Arc<CostLedger>,
+    // (market, wallet) -> OpenOrders account, resolved once
+    open_orders_accounts: tokio::sync::Mutex<HashMap<(String, Pubkey), Pubkey>>,
+    // (market, wallet) -> when a lookup last found no account or failed
+    open_orders_misses: tokio::sync::Mutex<HashMap<(String, Pubkey), Instant>>,
+    // (wallet, mint) -> last balances read
+    wallet_balance_cache: tokio::sync::Mutex<HashMap<(Pubkey, Pubkey), CachedBalance>>,
+    orderbook_feed: OrderbookFeed,
     _settings: std::sync::Arc<tokio::sync::RwLock<BotSettings>>,
 }
 
 impl TradingService {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         settings: std::sync::Arc<tokio::sync::RwLock<BotSettings>>,
         solana: std::sync::Arc<dyn SolanaProvider>,
@@ -43,6 +83,9 @@ impl TradingService {
         wallet_manager: std::sync::Arc<WalletManager>,
         pivot_engine: std::sync::Arc<PivotEngine>,
         price_aggregator: std::sync::Arc<PriceAggregator>,
+        http: std::sync::Arc<HttpClient>,
+        orderbook_feed: OrderbookFeed,
+        channel_widths: std::sync::Arc<ChannelWidthController>,
     ) -> Self {
         let grid_builder = GridBuilder::default(); // Will be configured per-tick from settings
 
@@ -55,17 +98,36 @@ impl TradingService {
         };
         let risk_manager = RiskManager::new(crate::utils::RiskLimitsSettings::default());
 
-        let flash_volume =
-            FlashVolumeModule::new(solana.clone(), wallet_manager.clone(), settings.clone());
-        let financial_manager =
-            FinancialManager::new(solana.clone(), wallet_manager.clone(), settings.clone());
+        let fiat = FiatService::new(price_aggregator.clone(), database.clone());
+        if let Err(e) = fiat.load_latest().await {
+            warn!(error = %e, "fiat_rates_load_failed");
+        }
+
+        let cost_ledger = std::sync::Arc::new(CostLedger::new(database.clone()));
+        let flash_volume = FlashVolumeModule::new(
+            solana.clone(),
+            wallet_manager.clone(),
+            settings.clone(),
+            cost_ledger.clone(),
+        );
+        let financial_manager = FinancialManager::new(
+            solana.clone(),
+            wallet_manager.clone(),
+            settings.clone(),
+            cost_ledger.clone(),
+        );
         let emergency_pool = crate::services::emergency_pool::EmergencyPoolService::new(
             solana.clone(),
             wallet_manager.clone(),
             settings.clone(),
+            cost_ledger.clone(),
+        );
+        let rent_recovery = RentRecoveryService::new(
+            solana.clone(),
+            wallet_manager.clone(),
+            settings.clone(),
+            cost_ledger.clone(),
         );
-        let rent_recovery =
-            RentRecoveryService::new(solana.clone(), wallet_manager.clone(), settings.clone());
 
         Self {
             solana,
@@ -81,8 +143,23 @@ impl TradingService {
             _emergency_pool: emergency_pool,
             flash_volume,
             rent_recovery,
-            rugcheck: RugCheckService::new(),
+            rugcheck: RugCheckService::new(http),
+            market_layouts: tokio::sync::Mutex::new(HashMap::new()),
+            channel_widths,
+            level_allocator: LevelAllocator::new(),
+            fiat,
+            cost_ledger,
+            open_orders_accounts: tokio::sync::Mutex::new(HashMap::new()),
+            open_orders_misses: tokio::sync::Mutex::new(HashMap::new()),
+            wallet_balance_cache: tokio::sync::Mutex::new(HashMap::new()),
             price_aggregator,
+            price_guard: PriceGuard::new(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs() as i64,
+            ),
+            orderbook_feed,
             _settings: settings,
         }
     }
@@ -128,11 +205,13 @@ impl TradingService {
             _jito_tip_lamports,
             _jito_url,
             risk_limits,
-            orders_per_side,
-            buy_bounds,
-            sell_bounds,
-            buy_mult,
-            sell_mult,
+            grid_settings,
+            channel_bounds,
+            price_guard_settings,
+            fiat_settings,
+            pivot_settings,
+            skew_settings,
+            min_sol_reserve_percent,
         ) = {
             let s = self._settings.read().await;
             (
@@ -147,11 +226,13 @@ impl TradingService {
                 s.jito_bundle.tip_lamports,
                 s.jito_bundle.bundler_url.clone(),
                 s.risk_limits.clone(),
-                s.order_grid.orders_per_side,
-                s.channel_bounds.buy_percent,
-                s.channel_bounds.sell_percent,
-                s.order_grid.buy_volume_multiplier,
-                s.order_grid.sell_volume_multiplier,
+                s.order_grid.clone(),
+                s.channel_bounds.clone(),
+                s.price_guard.clone(),
+                s.fiat.clone(),
+                s.pivot_vwap.clone(),
+                s.inventory_skew.clone(),
+                s.financial_manager.min_sol_reserve_percent,
             )
         };
 
@@ -243,36 +324,73 @@ impl TradingService {
         // Try to get real price from aggregator first
         let real_bmv_price = self
             .price_aggregator
-            .fetch_price_native(&market_id)
+            .aggregate(PriceFeed::AssetSol)
             .await
+            .map(|p| p.price)
             .ok();
 
-        let market_data = match self.solana.get_market_data(&market_id).await {
-            Ok(mut data) => {
-                if let Some(p) = real_bmv_price {
-                    data.price = p; // override with real aggregator price if available
-                }
-                data
-            }
+        let rpc_market_data = match self.solana.get_market_data(&market_id).await {
+            Ok(data) => Some(data),
             Err(e) => {
                 warn!(error = %e, ?market_id, "Failed to fetch market data from Solana RPC");
-                let price = if let Some(p) = real_bmv_price {
-                    p
-                } else {
-                    let fallback = self.pivot_engine.get_last_pivot().await;
-                    if fallback.is_zero() {
-                        Decimal::from(150)
-                    } else {
-                        fallback
-                    }
-                };
+                None
+            }
+        };
+        // Aggregated price wins over the RPC mid; neither is replaced by a made-up value
+        let candidate_price = real_bmv_price.or_else(|| {
+            rpc_market_data
+                .as_ref()
+                .map(|d| d.price)
+                .filter(|p| !p.is_zero())
+        });
 
-                crate::domain::MarketUpdate {
-                    price,
-                    volume_24h: Decimal::from(1000),
-                    timestamp: now,
+        // 2a. Price guard: quote only on a plausible, recent price
+        let guarded = self.price_guard.check(
+            PriceFeed::AssetSol,
+            candidate_price,
+            now,
+            &price_guard_settings,
+        );
+        gauge!(
+            "bot_quoting_state",
+            match guarded.state {
+                QuotingState::Active => 0.0,
+                QuotingState::Paused => 1.0,
+                QuotingState::Halted => 2.0,
+            }
+        );
+        // Only the grid waits for a fresh price; the bookkeeping below keeps running on the
+        // last good one
+        let quoting = match guarded.state {
+            QuotingState::Halted => {
+                if guarded.cancel_orders {
+                    warn!(
+                        age_secs = guarded.age_secs,
+                        "price_stale_cancelling_open_orders"
+                    );
+                    self.cancel_all_orders().await?;
                 }
+                false
+            }
+            QuotingState::Paused => {
+                warn!(age_secs = guarded.age_secs, "price_stale_quoting_paused");
+                false
             }
+            QuotingState::Active => true,
+        };
+        let Some(asset_price) = guarded.price else {
+            // No price has been accepted since startup, so there is nothing to book against
+            return Ok(());
+        };
+
+        let rolling = self.database.get_trade_volume(now - 86_400).await?;
+        gauge!("bot_volume_24h", rolling.volume.to_f64().unwrap_or(0.0));
+        gauge!("bot_trade_count_24h", rolling.trade_count as f64);
+        let market_data = crate::domain::MarketUpdate {
+            price: asset_price,
+            volume_24h: rolling.volume,
+            trade_count_24h: rolling.trade_count,
+            timestamp: rpc_market_data.map(|d| d.timestamp).unwrap_or(now),
         };
         // Ensure pivot engine is updated with this price even in simulation
         self.pivot_engine.set_last_price(market_data.price).await;
@@ -281,20 +399,39 @@ impl TradingService {
         let lookback_secs = self.pivot_engine.lookback_window_seconds();
         let recent_trades = self.database.get_recent_trades(now - lookback_secs).await?;
 
-        // 4. Compute Elapsed seconds for Seeded Pivot
-        let cached_trades = self.pivot_engine.cached_trades().await;
-        let elapsed_seconds = if let Some(first_trade) = cached_trades.first() {
-            now.saturating_sub(first_trade.timestamp)
+        // 3a. Strategy is re-read every tick so config reloads switch it
+        let strategy = crate::services::pivot_strategy::from_settings(&pivot_settings);
+        let price_history = if strategy.uses_price_history() {
+            self.database.get_price_history(now - lookback_secs).await?
         } else {
-            0
+            Vec::new()
         };
+        self.pivot_engine.set_strategy(strategy).await;
+        self.pivot_engine
+            .set_internal_filter(InternalTradeFilter {
+                accounts: self.swarm_accounts(&market_id).await,
+                weight: pivot_settings.internal_trade_weight,
+            })
+            .await;
+
+        // 4. Compute Elapsed seconds for Seeded Pivot
+        let elapsed_seconds = self.pivot_engine.seed_elapsed_seconds(now).await;
 
         // 5. Compute Pivot
         let pivot = self
             .pivot_engine
-            .compute_pivot(&[], &recent_trades, Some(&market_data), elapsed_seconds)
+            .compute_pivot(
+                &[],
+                &recent_trades,
+                &price_history,
+                Some(&market_data),
+                elapsed_seconds,
+            )
             .await;
         gauge!("bot_last_pivot_price", pivot.to_f64().unwrap_or(0.0));
+        if let Err(e) = self.pivot_engine.save_state(self.database.as_ref()).await {
+            warn!(error = %e, "pivot_state_save_failed");
+        }
 
         // 5a. Emit PnL Metrics
         let pnl_snapshot = {
@@ -318,20 +455,32 @@ impl TradingService {
             pnl_snapshot.average_cost.to_f64().unwrap_or(0.0)
         );
 
-        // 5aa. SOL/USDC and Cross-rate (v0.3.0 Requirement)
-        let sol_usdc_price = match self.price_aggregator.fetch_price_usd(&sol_usdc_id).await {
-            Ok(p) => p,
+        // 5aa. SOL/USDC and Cross-rate (v0.3.0 Requirement); the Pyth account is one of the
+        // aggregator's sources, so it goes through the same median and outlier rejection
+        let sol_usd_candidate = match self.price_aggregator.aggregate(PriceFeed::SolUsd).await {
+            Ok(p) => Some(p.price),
             Err(e) => {
                 debug!(error = %e, ?sol_usdc_id, "Aggregator failed for SOL/USDC, trying Solana RPC");
                 match self.solana.get_market_data(&sol_usdc_id).await {
-                    Ok(data) => data.price,
+                    Ok(data) => Some(data.price),
                     Err(rpc_e) => {
-                        warn!(error = %rpc_e, "Solana RPC also failed for SOL/USDC, using fallback 150.0");
-                        Decimal::from(150)
+                        warn!(error = %rpc_e, "Solana RPC also failed for SOL/USDC");
+                        None
                     }
                 }
             }
         };
+        // USD figures are skipped rather than reported from an invented or stale rate
+        let sol_guarded = self.price_guard.check(
+            PriceFeed::SolUsd,
+            sol_usd_candidate,
+            now,
+            &price_guard_settings,
+        );
+        let sol_usdc_price = match (sol_guarded.state, sol_guarded.price) {
+            (QuotingState::Active, Some(p)) => p,
+            _ => Decimal::ZERO,
+        };
         let bmv_price_sol = market_data.price;
         let bmv_price_usdc = bmv_price_sol * sol_usdc_price;
 
@@ -339,6 +488,35 @@ impl TradingService {
         gauge!("bot_bmv_price_sol", bmv_price_sol.to_f64().unwrap_or(0.0));
         gauge!("bot_bmv_price_usdc", bmv_price_usdc.to_f64().unwrap_or(0.0));
 
+        // 5ab. Fiat reporting in the configured currency
+        if let Err(e) = self
+            .fiat
+            .refresh(
+                (!sol_usdc_price.is_zero()).then_some(sol_usdc_price),
+                now,
+                &fiat_settings,
+            )
+            .await
+        {
+            warn!(error = %e, "fiat_refresh_failed");
+        }
+        let currency = fiat_settings.reporting_currency;
+        match self.fiat.rates().sol_to(currency) {
+            Some(rate) => {
+                let fiat_pnl = pnl_snapshot.converted(rate);
+                gauge!("bot_pnl_realized_fiat", fiat_pnl.realized_pnl.to_f64().unwrap_or(0.0), "currency" => currency.as_str());
+                gauge!("bot_pnl_unrealized_fiat", fiat_pnl.unrealized_pnl.to_f64().unwrap_or(0.0), "currency" => currency.as_str());
+                gauge!("bot_bmv_price_fiat", (bmv_price_sol * rate).to_f64().unwrap_or(0.0), "currency" => currency.as_str());
+                info!(
+                    currency = currency.as_str(),
+                    realized = %fiat_pnl.realized_pnl.round_dp(2),
+                    unrealized = %fiat_pnl.unrealized_pnl.round_dp(2),
+                    "pnl_fiat_report"
+                );
+            }
+            None => debug!(currency = currency.as_str(), "fiat_rate_unavailable"),
+        }
+
         // 5b. Target Control (v0.3.0 Requirement)
         // TARGET_CONTROL_% = 100% − LockedTokens − OwnedTokens
         let (total_emission, locked_tokens) = {
@@ -369,26 +547,119 @@ impl TradingService {
             "Target Control: emission status computed"
         );
 
-        // 7. Check for rebalance trigger
-        if self
+        // 6. Inventory skew: centre the grid on a reservation price that sheds excess inventory.
+        // Both assets come from the wallet balances; the PnL tracker's net position counts
+        // every market fill and starts at zero after a restart. Without balances there is no
+        // skew and no grid to size this tick.
+        let balances = match self.wallet_balances(&market_id, &token_mint).await {
+            Ok(balances) => Some(balances),
+            Err(e) => {
+                warn!(error = %e, "wallet_balances_unavailable_skew_skipped");
+                None
+            }
+        };
+        let skew = match balances.as_deref() {
+            Some(balances) if skew_settings.enabled => InventorySkew::compute(
+                balances.iter().map(|b| b.token).sum(),
+                balances.iter().map(|b| b.sol).sum(),
+                pivot,
+                &skew_settings,
+            ),
+            _ => InventorySkew::default(),
+        };
+        let centre = skew.reservation_price(pivot);
+        gauge!(
+            "bot_inventory_deviation",
+            skew.deviation.to_f64().unwrap_or(0.0)
+        );
+        gauge!("bot_grid_centre_price", centre.to_f64().unwrap_or(0.0));
+        if skew_settings.enabled {
+            debug!(
+                deviation = %skew.deviation.round_dp(4),
+                %centre,
+                buy_factor = %skew.buy_size_factor.round_dp(4),
+                sell_factor = %skew.sell_size_factor.round_dp(4),
+                "inventory_skew_computed"
+            );
+        }
+
+        // 6a. Channel widths follow realised volatility when enabled
+        let volatility_history = if channel_bounds.adaptive.enabled {
+            self.database
+                .get_price_history(now - channel_bounds.adaptive.lookback_secs)
+                .await?
+        } else {
+            Vec::new()
+        };
+        let widths = self
+            .channel_widths
+            .update(&volatility_history, &channel_bounds);
+        gauge!("bot_channel_width", widths.buy.to_f64().unwrap_or(0.0), "side" => "buy");
+        gauge!("bot_channel_width", widths.sell.to_f64().unwrap_or(0.0), "side" => "sell");
+        let channel_bounds = ChannelBoundsSettings {
+            buy_percent: widths.buy,
+            sell_percent: widths.sell,
+            ..channel_bounds
+        };
+
+        // 7. Check for rebalance trigger; a broken ladder config keeps the current grid
+        let grid_builder = GridBuilder {
+            constraints: MarketConstraints::from_layout(&self.market_layout(&market_id).await?),
+            ..GridBuilder::from_settings(&grid_settings, &channel_bounds)
+        };
+        if !quoting {
+            debug!(state = ?guarded.state, "grid_rebuild_skipped_while_price_stale");
+        } else if let Err(e) = grid_builder.validate() {
+            error!(error = %e, "grid_config_invalid");
+        } else if balances.is_none() {
+            warn!("grid_rebuild_skipped_without_balances");
+        } else if risk_limits.max_order_usd > Decimal::ZERO && sol_usdc_price.is_zero() {
+            // The USD order cap cannot be converted; never build an uncapped grid instead
+            warn!(
+                max_order_usd = %risk_limits.max_order_usd,
+                "grid_rebuild_skipped_without_sol_usd"
+            );
+        } else if self
             .rebalance_service
-            .should_rebuild(pivot, market_data.price)
+            .should_rebuild(centre, market_data.price)
             .await
         {
-            info!(?pivot, "Rebuilding order grid");
-
-            // 7. Build Grid
-            let grid_builder = GridBuilder {
-                orders_per_side,
-                buy_channel_width: buy_bounds,
-                sell_channel_width: sell_bounds,
-                buy_volume_multiplier: buy_mult,
-                sell_volume_multiplier: sell_mult,
+            info!(?pivot, ?centre, "Rebuilding order grid");
+            let balances = balances.as_deref().unwrap_or_default();
+
+            // 7. Build Grid sized from what the swarm can fund
+            let reserves = SolReserves {
+                fee_reserve_sol: grid_settings.fee_reserve_sol_per_wallet,
+                account_rent_sol: pivot_settings.account_rent_sol,
+                min_sol_reserve_percent,
             };
-            let mut grid = grid_builder.build(pivot, Decimal::from(100)).await;
+            let budgets = skew.apply_budgets(SideBudgets::from_balances(balances, &reserves));
+            let max_order_sol = (risk_limits.max_order_usd > Decimal::ZERO)
+                .then(|| risk_limits.max_order_usd / sol_usdc_price);
+            gauge!("bot_grid_budget", budgets.buy_sol.to_f64().unwrap_or(0.0), "side" => "buy");
+            gauge!("bot_grid_budget", budgets.sell_tokens.to_f64().unwrap_or(0.0), "side" => "sell");
+            let mut grid = grid_builder
+                .build_funded(centre, budgets, max_order_sol)
+                .await;
 
             // 7a. L2 Scan & Front-running Protection
-            if let Ok(ob) = self.solana.get_orderbook(&market_id).await {
+            let orderbook =
+                match crate::services::orderbook_service::latest_orderbook(&self.orderbook_feed) {
+                    Some(ob) => Some(ob),
+                    None => {
+                        debug!("Local orderbook not synced, falling back to RPC");
+                        match self.solana.get_orderbook(&market_id).await {
+                            Ok(mut ob) => {
+                                // Flag the swarm's quotes as the live feed does, so they
+                                // are never mistaken for competitors
+                                ob.mark_own_orders(&self.swarm_accounts(&market_id).await);
+                                Some(ob)
+                            }
+                            Err(_) => None,
+                        }
+                    }
+                };
+            if let Some(ob) = orderbook {
                 grid_builder.apply_front_running_protection(
                     &mut grid,
                     &ob,
@@ -396,72 +667,79 @@ impl TradingService {
                     tick_size,
                 );
             }
+            // Front-running can put two levels on one price and moves bids up; merge and
+            // cap them again
+            let (grid, ladder) = grid_builder.fit_to_market(grid, max_order_sol);
 
             self.rebalance_service.update_last_grid(grid.clone());
 
             gauge!("bot_grid_levels_count", grid.len() as f64);
-            info!(levels = grid.len(), "Grid constructed");
-
-            // 8. Execute Grid Update & Emit Metrics (Swarm Segmentation)
-            let wallets = self.wallet_manager.get_all_wallets().await;
-            if wallets.is_empty() {
+            info!(
+                levels = grid.len(),
+                buy_levels = ladder.buy.levels_out,
+                sell_levels = ladder.sell.levels_out,
+                merged = ladder.buy.merged + ladder.sell.merged,
+                capped = ladder.buy.capped + ladder.sell.capped,
+                "Grid constructed"
+            );
+
+            // 8. Execute Grid Update & Emit Metrics (levels go to wallets that can fund them)
+            if balances.is_empty() {
                 error!("No wallets available for grid placement!");
                 return Ok(());
             }
 
-            let max_orders = wallets.len() * 32;
-            let final_grid = if grid.len() > max_orders {
+            let allocation = self.level_allocator.allocate(
+                &grid,
+                balances,
+                &reserves,
+                &grid_settings.allocation,
+                grid_builder.constraints.map(|c| c.lot_size),
+                now,
+            );
+            gauge!("bot_grid_levels_unallocated", allocation.unallocated as f64);
+            gauge!("bot_grid_levels_split", allocation.split as f64);
+            if allocation.unallocated > 0 {
                 warn!(
                     orders = grid.len(),
-                    max_orders,
-                    wallets = wallets.len(),
-                    "Truncating grid levels to fit wallet capacity (32 per wallet max)"
+                    unallocated = allocation.unallocated,
+                    wallets = balances.len(),
+                    strategy = ?grid_settings.allocation.strategy,
+                    "grid_levels_unallocated"
                 );
-                grid[..max_orders].to_vec()
-            } else {
-                grid
-            };
+            }
 
             let mut total_depth = Decimal::ZERO;
-            let rotation_offset = (now % wallets.len() as i64) as usize;
-
-            for (segment_idx, segment) in final_grid.chunks(32).enumerate() {
-                let rotated_wallet_idx = (segment_idx + rotation_offset) % wallets.len();
-                let wallet = &wallets[rotated_wallet_idx];
-                let wallet_pub = wallet.pubkey();
-
-                for (idx, level) in segment.iter().enumerate() {
-                    let global_idx = (segment_idx * 32 + idx) as u32;
-                    let side_str = match level.side {
-                        crate::domain::OrderSide::Buy => "BUY",
-                        crate::domain::OrderSide::Sell => "SELL",
-                    };
-
-                    gauge!(
-                        "bot_grid_level_price",
-                        level.price.to_f64().unwrap_or(0.0),
-                        "side" => side_str,
-                        "index" => global_idx.to_string(),
-                        "wallet" => wallet_pub.to_string()
-                    );
-                    gauge!(
-                        "bot_grid_level_size",
-                        level.size.to_f64().unwrap_or(0.0),
-                        "side" => side_str,
-                        "index" => global_idx.to_string(),
-                        "wallet" => wallet_pub.to_string()
-                    );
+            for (idx, (wallet_pub, level)) in allocation.assignments.iter().enumerate() {
+                let side_str = match level.side {
+                    crate::domain::OrderSide::Buy => "BUY",
+                    crate::domain::OrderSide::Sell => "SELL",
+                };
 
-                    total_depth += level.size * level.price;
+                gauge!(
+                    "bot_grid_level_price",
+                    level.price.to_f64().unwrap_or(0.0),
+                    "side" => side_str,
+                    "index" => idx.to_string(),
+                    "wallet" => wallet_pub.to_string()
+                );
+                gauge!(
+                    "bot_grid_level_size",
+                    level.size.to_f64().unwrap_or(0.0),
+                    "side" => side_str,
+                    "index" => idx.to_string(),
+                    "wallet" => wallet_pub.to_string()
+                );
 
-                    info!(
-                        side = %side_str,
-                        price = %level.price,
-                        size = %level.size,
-                        wallet = %wallet_pub,
-                        "Scheduling grid order (Phase 1 simulation)"
-                    );
-                }
+                total_depth += level.size * level.price;
+
+                info!(
+                    side = %side_str,
+                    price = %level.price,
+                    size = %level.size,
+                    wallet = %wallet_pub,
+                    "Scheduling grid order (Phase 1 simulation)"
+                );
             }
 
             // 9. Performance Indicators (Placeholders for real metrics later)
@@ -479,9 +757,12 @@ impl TradingService {
         self.financial_manager
             .check_balances(market_data.price)
             .await?;
-        self.financial_manager
-            .rebalance_fiat(market_data.price, pivot)
-            .await?;
+        // The fiat rebalance swaps against the asset price, so it waits for a fresh one
+        if quoting {
+            self.financial_manager
+                .rebalance_fiat(market_data.price, pivot, widths)
+                .await?;
+        }
 
         // 12. Periodic Rent Recovery
         self.rent_recovery.recover_rent().await?;
@@ -510,7 +791,18 @@ impl TradingService {
         if !bmv_price_sol.is_zero() && !sol_usdc_price.is_zero() {
             if let Err(e) = self
                 .database
-                .save_price_tick(bmv_price_sol, sol_usdc_price)
+                .save_price_tick(&PriceTick {
+                    timestamp: now,
+                    asset_price: bmv_price_sol,
+                    sol_price: sol_usdc_price,
+                    // A held price is not an observation for TWAP or volatility
+                    source: if quoting {
+                        PriceTick::TRADING_LOOP
+                    } else {
+                        PriceTick::TRADING_LOOP_HELD
+                    }
+                    .to_string(),
+                })
                 .await
             {
                 error!(error = %e, "failed_to_save_price_tick");
@@ -560,6 +852,142 @@ impl TradingService {
         })
     }
 
+    /// Swarm wallets plus their OpenOrders accounts on `market_id`; fills name the latter.
+    /// Found accounts are kept; wallets without one are looked up again after
+    /// `OPEN_ORDERS_MISS_TTL`.
+    async fn swarm_accounts(&self, market_id: &str) -> HashSet<String> {
+        let mut cache = self.open_orders_accounts.lock().await;
+        let mut misses = self.open_orders_misses.lock().await;
+        let mut accounts = HashSet::new();
+
+        for wallet in self.wallet_manager.get_all_wallets().await {
+            let pubkey = wallet.pubkey();
+            accounts.insert(pubkey.to_string());
+            let key = (market_id.to_string(), pubkey);
+            if let Some(oo) = cache.get(&key) {
+                accounts.insert(oo.to_string());
+                continue;
+            }
+            if misses
+                .get(&key)
+                .is_some_and(|at| at.elapsed() < OPEN_ORDERS_MISS_TTL)
+            {
+                continue;
+            }
+            match self.solana.find_open_orders(market_id, &pubkey).await {
+                Ok(Some(oo)) => {
+                    misses.remove(&key);
+                    cache.insert(key, oo);
+                    accounts.insert(oo.to_string());
+                    let rent = self._settings.read().await.pivot_vwap.account_rent_sol;
+                    self.cost_ledger
+                        .record_open_orders_rent(&pubkey.to_string(), &oo.to_string(), rent)
+                        .await;
+                }
+                Ok(None) => {
+                    misses.insert(key, Instant::now());
+                }
+                Err(e) => {
+                    warn!(error = ?e, wallet = %pubkey, "failed_to_resolve_open_orders");
+                    misses.insert(key, Instant::now());
+                }
+            }
+        }
+        accounts
+    }
+
+    /// Market layout of `market_id`, read once; lot sizes and decimals never change.
+    async fn market_layout(&self, market_id: &str) -> Result<BookLayout> {
+        let mut cache = self.market_layouts.lock().await;
+        if let Some(layout) = cache.get(market_id) {
+            return Ok(layout.clone());
+        }
+        let market = Pubkey::from_str(market_id)
+            .map_err(|e| anyhow!("Failed to parse market_id '{}': {}", market_id, e))?;
+        let layout = BookLayout::from_market_data(&self.solana.get_account_data(&market).await?)?;
+        cache.insert(market_id.to_string(), layout.clone());
+        Ok(layout)
+    }
+
+    /// SOL and token balances of every swarm wallet, in whole units. Balances are read at
+    /// most every `WALLET_BALANCE_TTL`; a wallet whose read fails keeps its last known
+    /// balance, or is left out if it has none. Fails only when no wallet has a balance.
+    async fn wallet_balances(
+        &self,
+        market_id: &str,
+        token_mint: &str,
+    ) -> Result<Vec<WalletBalance>> {
+        let mint = Pubkey::from_str(token_mint)
+            .map_err(|e| anyhow!("Failed to parse token_mint '{}': {}", token_mint, e))?;
+        let token_scale =
+            Decimal::from(10u64.pow(self.market_layout(market_id).await?.base_decimals() as u32));
+
+        let wallets = self.wallet_manager.get_all_wallets().await;
+        let mut cache = self.wallet_balance_cache.lock().await;
+        let mut balances = Vec::with_capacity(wallets.len());
+        for wallet in &wallets {
+            let pubkey = wallet.pubkey();
+            let key = (pubkey, mint);
+            let cached = cache.get(&key).copied();
+            let fresh = cached.filter(|c| c.read_at.elapsed() < WALLET_BALANCE_TTL);
+            let (sol, token) = match fresh {
+                Some(c) => (c.sol, c.token),
+                None => match self.read_wallet_balance(&pubkey, &mint).await {
+                    Ok((lamports, token_units)) => {
+                        let sol = Decimal::from(lamports) / Decimal::from(1_000_000_000u64);
+                        let token = Decimal::from(token_units) / token_scale;
+                        let read_at = Instant::now();
+                        cache.insert(
+                            key,
+                            CachedBalance {
+                                sol,
+                                token,
+                                read_at,
+                            },
+                        );
+                        (sol, token)
+                    }
+                    Err(e) => match cached {
+                        Some(CachedBalance {
+                            sol,
+                            token,
+                            read_at: at,
+                        }) => {
+                            warn!(error = %e, wallet = %pubkey, age_secs = at.elapsed().as_secs(), "wallet_balance_read_failed_using_last_known");
+                            (sol, token)
+                        }
+                        None => {
+                            warn!(error = %e, wallet = %pubkey, "wallet_balance_read_failed_wallet_skipped");
+                            continue;
+                        }
+                    },
+                },
+            };
+            let has_open_orders = self
+                .open_orders_accounts
+                .lock()
+                .await
+                .contains_key(&(market_id.to_string(), pubkey));
+            balances.push(WalletBalance {
+                wallet: pubkey,
+                sol,
+                token,
+                has_open_orders,
+            });
+        }
+        if balances.is_empty() && !wallets.is_empty() {
+            return Err(anyhow!("No swarm wallet balance could be read"));
+        }
+        Ok(balances)
+    }
+
+    /// Lamports and raw token units held by `wallet`.
+    async fn read_wallet_balance(&self, wallet: &Pubkey, mint: &Pubkey) -> Result<(u64, u64)> {
+        let lamports = self.solana.get_balance(&wallet.to_string()).await?;
+        let token_units = self.solana.get_token_balance(wallet, mint).await?;
+        Ok((lamports, token_units))
+    }
+
     async fn handle_kill_switch(&self, reason: &str) -> Result<()> {
         counter!("bot_kill_switch_trigger_total", 1, "reason" => reason.to_string());
         info!(%reason, "Kill switch triggered; canceling all orders and pausing trading");
@@ -588,6 +1016,20 @@ impl TradingService {
                 .cancel_all_orders(&market_id, &wallet, &jito_url, tip_lamports)
                 .await?;
             info!(wallet = %wallet.pubkey(), %result, "Cancel-all submitted");
+            // Nothing is sent when the wallet has no OpenOrders account
+            if result != "no_open_orders" {
+                self.cost_ledger
+                    .record_transaction(
+                        &result,
+                        &wallet.pubkey().to_string(),
+                        TransactionCosts {
+                            signatures: 1,
+                            jito_tip_lamports: tip_lamports,
+                            ..TransactionCosts::default()
+                        },
+                    )
+                    .await;
+            }
         }
 
         Ok(())
@@ -603,14 +1045,12 @@ mod tests {
     use rust_decimal_macros::dec;
     use std::sync::Arc;
 
-    #[tokio::test]
-    async fn test_trading_service_tick_basic() {
+    /// Mocks every tick needs apart from SOL/USD: the BMV market, an empty trade store,
+    /// one funded wallet without an OpenOrders account and the market account.
+    fn tick_mocks(settings: &BotSettings) -> (MockSolanaProvider, MockDatabaseProvider) {
         let mut mock_solana = MockSolanaProvider::new();
         let mut mock_database = MockDatabaseProvider::new();
 
-        let mut settings = BotSettings::default();
-        settings.flash_volume.enabled = false;
-
         // Mock get_market_data
         let market_id_clone = settings.openbook_market_id.clone();
         mock_solana
@@ -620,19 +1060,7 @@ mod tests {
                 Ok(crate::domain::MarketUpdate {
                     price: dec!(100.5),
                     volume_24h: dec!(1000000),
-                    timestamp: 123456789,
-                })
-            });
-
-        // Mock SOL/USDC market data
-        let sol_usdc_id_clone = settings.sol_usdc_market_id.clone();
-        mock_solana
-            .expect_get_market_data()
-            .with(eq(sol_usdc_id_clone))
-            .returning(|_| {
-                Ok(crate::domain::MarketUpdate {
-                    price: dec!(150.0),
-                    volume_24h: dec!(5000000),
+                    trade_count_24h: 0,
                     timestamp: 123456789,
                 })
             });
@@ -641,9 +1069,20 @@ mod tests {
         mock_database
             .expect_get_recent_trades()
             .returning(|_| Ok(vec![]));
+        mock_database.expect_get_trade_volume().returning(|_| {
+            Ok(crate::domain::TradeVolume {
+                volume: dec!(2500),
+                trade_count: 40,
+            })
+        });
 
         // Mock state
         mock_database.expect_get_state().returning(|_| Ok(None));
+        mock_database
+            .expect_set_state()
+            .withf(|key, _| key == "pivot_engine_state")
+            .times(1)
+            .returning(|_, _| Ok(()));
 
         // Elaboration for v0.3.0 modules:
         mock_solana
@@ -656,20 +1095,40 @@ mod tests {
             .expect_find_open_orders()
             .returning(|_, _| Ok(None));
         mock_solana.expect_get_orderbook().returning(|_| {
-            Ok(crate::domain::Orderbook {
-                market_id: "TEST_MARKET".to_string(),
-                timestamp: 0,
-                bids: vec![],
-                asks: vec![],
-            })
+            Ok(crate::domain::Orderbook::from_orders(
+                "TEST_MARKET",
+                0,
+                vec![],
+                vec![],
+            ))
         });
 
+        // Market account, read once for the token decimals
+        let market = Pubkey::from_str(&settings.openbook_market_id).unwrap();
+        mock_solana
+            .expect_get_account_data()
+            .withf(move |pk| *pk == market)
+            .times(1)
+            .returning(|_| {
+                let mut data = vec![0u8; 450];
+                data[0..8].copy_from_slice(&crate::infra::openbook::MARKET_DISCRIMINATOR);
+                data[9] = 6;
+                Ok(data)
+            });
+
         mock_database
-            .expect_save_price_tick()
-            .returning(|_, _| Ok(()));
+            .expect_get_latest_fiat_quote()
+            .returning(|_| Ok(None));
 
-        let solana: Arc<dyn SolanaProvider> = Arc::new(mock_solana);
-        let database: Arc<dyn DatabaseProvider> = Arc::new(mock_database);
+        (mock_solana, mock_database)
+    }
+
+    async fn tick_service(
+        settings: BotSettings,
+        solana: Arc<dyn SolanaProvider>,
+        database: Arc<dyn DatabaseProvider>,
+        price_aggregator: PriceAggregator,
+    ) -> TradingService {
         let wallet_manager = Arc::new(
             crate::infra::WalletManager::new(
                 &[solana_sdk::signature::Keypair::new().to_base58_string()],
@@ -689,19 +1148,319 @@ mod tests {
             dec!(10),
         ));
 
-        let price_aggregator = Arc::new(PriceAggregator::default());
         let settings_arc = Arc::new(tokio::sync::RwLock::new(settings));
-        let service = TradingService::new(
+        let (_orderbook_tx, orderbook_feed) = tokio::sync::watch::channel(None);
+        TradingService::new(
             settings_arc,
             solana,
             database,
             wallet_manager,
             pivot_engine,
-            price_aggregator,
+            Arc::new(price_aggregator),
+            Arc::new(HttpClient::default()),
+            orderbook_feed,
+            Arc::new(ChannelWidthController::new()),
         )
-        .await;
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_trading_service_tick_basic() {
+        let mut settings = BotSettings::default();
+        settings.flash_volume.enabled = false;
+        settings.token_mint = Pubkey::new_unique().to_string();
+        let (mut mock_solana, mut mock_database) = tick_mocks(&settings);
+
+        // Mock SOL/USDC market data
+        let sol_usdc_id_clone = settings.sol_usdc_market_id.clone();
+        mock_solana
+            .expect_get_market_data()
+            .with(eq(sol_usdc_id_clone))
+            .returning(|_| {
+                Ok(crate::domain::MarketUpdate {
+                    price: dec!(150.0),
+                    volume_24h: dec!(5000000),
+                    trade_count_24h: 0,
+                    timestamp: 123456789,
+                })
+            });
+
+        // SOL/USD comes from the Pyth account rather than the SOL/USDC market
+        let pyth_account = Pubkey::from_str(&settings.price_sources.pyth_sol_usd_account).unwrap();
+        let publish_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        mock_solana
+            .expect_get_account_data()
+            .withf(move |pk| *pk == pyth_account)
+            .returning(move |_| Ok(crate::infra::pyth::fixture_account_data(publish_time)));
+
+        mock_database
+            .expect_save_price_tick()
+            .withf(|t| t.sol_price == dec!(145.23))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_database
+            .expect_save_fiat_quote()
+            .withf(|q| q.pair == crate::domain::FiatQuote::SOL_USD && q.price == dec!(145.23))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let solana: Arc<dyn SolanaProvider> = Arc::new(mock_solana);
+        let price_aggregator = PriceAggregator::default().with_source(
+            PriceFeed::SolUsd,
+            Arc::new(crate::infra::price_sources::PythOracleSource::new(
+                solana.clone(),
+                pyth_account,
+                dec!(0.02),
+                Decimal::ONE,
+            )),
+        );
+        let service =
+            tick_service(settings, solana, Arc::new(mock_database), price_aggregator).await;
 
         let result = service.tick().await;
         result.expect("Trading service tick failed");
+        assert!(!service.rebalance_service.last_grid().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_inventory_skew_reads_tokens_from_wallet_balances() {
+        // Highest bid of a fresh service whose wallet holds 1 SOL and 1 token worth 100.5 SOL
+        async fn best_bid(skew_enabled: bool) -> Decimal {
+            let mut settings = BotSettings::default();
+            settings.flash_volume.enabled = false;
+            settings.token_mint = Pubkey::new_unique().to_string();
+            settings.inventory_skew.enabled = skew_enabled;
+            let (mut mock_solana, mock_database) = tick_mocks(&settings);
+            mock_solana
+                .expect_get_market_data()
+                .with(eq(settings.sol_usdc_market_id.clone()))
+                .returning(|_| Err(anyhow!("SOL/USDC market unavailable")));
+
+            let service = tick_service(
+                settings,
+                Arc::new(mock_solana),
+                Arc::new(mock_database),
+                PriceAggregator::default(),
+            )
+            .await;
+            service.tick().await.expect("Trading service tick failed");
+            service
+                .rebalance_service
+                .last_grid()
+                .iter()
+                .filter(|l| l.side == crate::domain::OrderSide::Buy)
+                .map(|l| l.price)
+                .max()
+                .unwrap()
+        }
+
+        // The PnL tracker has seen no fills, yet the swarm is token-heavy: the centre moves
+        // down to shed tokens instead of up to buy more
+        assert!(best_bid(true).await < best_bid(false).await);
+    }
+
+    #[tokio::test]
+    async fn test_stale_price_pauses_the_grid_but_not_the_bookkeeping() {
+        let mut settings = BotSettings::default();
+        settings.flash_volume.enabled = false;
+        settings.token_mint = Pubkey::new_unique().to_string();
+        settings.price_guard.pause_after_secs = 0;
+        // Pivot state is saved once by `tick_mocks`
+        let (mut mock_solana, mut mock_database) = tick_mocks(&settings);
+        mock_solana
+            .expect_get_market_data()
+            .with(eq(settings.sol_usdc_market_id.clone()))
+            .returning(|_| {
+                Ok(crate::domain::MarketUpdate {
+                    price: dec!(150),
+                    volume_24h: Decimal::ZERO,
+                    trade_count_24h: 0,
+                    timestamp: 123456789,
+                })
+            });
+        // The tick is stored with the held price, marked as such
+        mock_database
+            .expect_save_price_tick()
+            .withf(|t| t.source == PriceTick::TRADING_LOOP_HELD && t.asset_price == dec!(50))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_database.expect_save_fiat_quote().returning(|_| Ok(()));
+
+        let guard_settings = settings.price_guard.clone();
+        let service = tick_service(
+            settings,
+            Arc::new(mock_solana),
+            Arc::new(mock_database),
+            PriceAggregator::default(),
+        )
+        .await;
+        // Last good price 50; the market's 100.5 is rejected as a jump
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        service
+            .price_guard
+            .check(PriceFeed::AssetSol, Some(dec!(50)), now, &guard_settings);
+
+        service.tick().await.expect("Trading service tick failed");
+        assert!(service.rebalance_service.last_grid().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_usd_order_cap_without_sol_usd_skips_the_grid() {
+        let mut settings = BotSettings::default();
+        settings.flash_volume.enabled = false;
+        settings.token_mint = Pubkey::new_unique().to_string();
+        settings.risk_limits.max_order_usd = dec!(100);
+        let (mut mock_solana, mock_database) = tick_mocks(&settings);
+
+        // No SOL/USD source answers, so the guard holds the rate as stale
+        mock_solana
+            .expect_get_market_data()
+            .with(eq(settings.sol_usdc_market_id.clone()))
+            .returning(|_| Err(anyhow!("SOL/USDC market unavailable")));
+
+        let service = tick_service(
+            settings,
+            Arc::new(mock_solana),
+            Arc::new(mock_database),
+            PriceAggregator::default(),
+        )
+        .await;
+
+        service.tick().await.expect("Trading service tick failed");
+        assert!(service.rebalance_service.last_grid().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_wallet_balances_are_cached_and_survive_one_failing_wallet() {
+        let settings = BotSettings::default();
+        let healthy = solana_sdk::signature::Keypair::new();
+        let failing = solana_sdk::signature::Keypair::new();
+        let healthy_pubkey = healthy.pubkey();
+        let failing_address = failing.pubkey().to_string();
+
+        let mut mock_solana = MockSolanaProvider::new();
+        let market = Pubkey::from_str(&settings.openbook_market_id).unwrap();
+        mock_solana
+            .expect_get_account_data()
+            .withf(move |pk| *pk == market)
+            .returning(|_| {
+                let mut data = vec![0u8; 450];
+                data[0..8].copy_from_slice(&crate::infra::openbook::MARKET_DISCRIMINATOR);
+                data[9] = 6;
+                Ok(data)
+            });
+        // The healthy wallet is read once and then served from the cache
+        mock_solana
+            .expect_get_balance()
+            .with(eq(healthy_pubkey.to_string()))
+            .times(1)
+            .returning(|_| Ok(2_000_000_000));
+        mock_solana
+            .expect_get_balance()
+            .with(eq(failing_address))
+            .times(2)
+            .returning(|_| Err(anyhow!("node is behind")));
+        mock_solana
+            .expect_get_token_balance()
+            .times(1)
+            .returning(|_, _| Ok(5_000_000));
+        let mut mock_database = MockDatabaseProvider::new();
+        mock_database
+            .expect_get_latest_fiat_quote()
+            .returning(|_| Ok(None));
+
+        let wallet_manager = Arc::new(
+            crate::infra::WalletManager::new(
+                &[healthy.to_base58_string(), failing.to_base58_string()],
+                None,
+            )
+            .unwrap(),
+        );
+        let (_orderbook_tx, orderbook_feed) = tokio::sync::watch::channel(None);
+        let service = TradingService::new(
+            Arc::new(tokio::sync::RwLock::new(settings.clone())),
+            Arc::new(mock_solana),
+            Arc::new(mock_database),
+            wallet_manager,
+            Arc::new(PivotEngine::new(
+                dec!(1),
+                1,
+                0,
+                dec!(1000),
+                Decimal::ZERO,
+                Decimal::ZERO,
+                Decimal::ZERO,
+                Decimal::ZERO,
+            )),
+            Arc::new(PriceAggregator::default()),
+            Arc::new(HttpClient::default()),
+            orderbook_feed,
+            Arc::new(ChannelWidthController::new()),
+        )
+        .await;
+
+        let mint = Pubkey::new_unique().to_string();
+        for _ in 0..2 {
+            let balances = service
+                .wallet_balances(&settings.openbook_market_id, &mint)
+                .await
+                .unwrap();
+            assert_eq!(balances.len(), 1);
+            assert_eq!(balances[0].wallet, healthy_pubkey);
+            assert_eq!(balances[0].sol, dec!(2));
+            assert_eq!(balances[0].token, dec!(5));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_orders_misses_are_not_looked_up_every_tick() {
+        let mut mock_solana = MockSolanaProvider::new();
+        mock_solana
+            .expect_find_open_orders()
+            .times(1)
+            .returning(|_, _| Ok(None));
+        let mut mock_database = MockDatabaseProvider::new();
+        mock_database
+            .expect_get_latest_fiat_quote()
+            .returning(|_| Ok(None));
+        mock_database.expect_get_state().returning(|_| Ok(None));
+
+        let wallet = solana_sdk::signature::Keypair::new();
+        let wallet_pubkey = wallet.pubkey().to_string();
+        let wallet_manager =
+            Arc::new(crate::infra::WalletManager::new(&[wallet.to_base58_string()], None).unwrap());
+        let (_orderbook_tx, orderbook_feed) = tokio::sync::watch::channel(None);
+        let service = TradingService::new(
+            Arc::new(tokio::sync::RwLock::new(BotSettings::default())),
+            Arc::new(mock_solana),
+            Arc::new(mock_database),
+            wallet_manager,
+            Arc::new(PivotEngine::new(
+                dec!(1),
+                1,
+                0,
+                dec!(1000),
+                Decimal::ZERO,
+                Decimal::ZERO,
+                Decimal::ZERO,
+                Decimal::ZERO,
+            )),
+            Arc::new(PriceAggregator::default()),
+            Arc::new(HttpClient::default()),
+            orderbook_feed,
+            Arc::new(ChannelWidthController::new()),
+        )
+        .await;
+
+        for _ in 0..3 {
+            let accounts = service.swarm_accounts("TEST_MARKET").await;
+            assert_eq!(accounts, HashSet::from([wallet_pubkey.clone()]));
+        }
     }
 }
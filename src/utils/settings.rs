@@ -1,8 +1,10 @@
+use crate::domain::FiatCurrency;
 use anyhow::{Context, Result};
 use dotenvy::dotenv;
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
@@ -16,6 +18,85 @@ pub struct OrderGridSettings {
     pub rebalance_threshold_percent: Decimal,
     pub large_order_threshold_sol: Decimal,
     pub front_run_tick_size_sol: Decimal,
+    /// SOL kept back in every wallet for transaction fees; not posted to the bids.
+    #[serde(default = "default_fee_reserve_sol_per_wallet")]
+    pub fee_reserve_sol_per_wallet: Decimal,
+    #[serde(default)]
+    pub spacing: GridSpacing,
+    #[serde(default)]
+    pub sizing: GridSizing,
+    #[serde(default)]
+    pub allocation: AllocationSettings,
+}
+
+/// How grid levels are spread across the channel, nearest level first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum GridSpacing {
+    /// Equal price steps
+    #[default]
+    Linear,
+    /// Each level a constant percentage beyond the previous one
+    Geometric,
+    /// Steps widen away from the pivot in Fibonacci proportions
+    Fibonacci,
+    /// Explicit levels as fractional distances from the pivot, one per order
+    Custom {
+        buy_offsets: Vec<Decimal>,
+        sell_offsets: Vec<Decimal>,
+    },
+}
+
+/// How each side's size is split across its levels, nearest level first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum GridSizing {
+    /// Same size on every level
+    Flat,
+    /// Each level `*_volume_multiplier` times the previous one
+    #[default]
+    Exponential,
+    /// Each level grows by `*_volume_multiplier - 1` times the first one
+    LinearRamp,
+    /// Relative weights, one per order
+    Custom {
+        buy_weights: Vec<Decimal>,
+        sell_weights: Vec<Decimal>,
+    },
+}
+
+/// How grid levels are handed out to the swarm wallets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllocationStrategy {
+    /// Consecutive chunks of levels per wallet, rotated every tick; balances are ignored.
+    Rotation,
+    /// Bids to wallets holding SOL, asks to wallets holding the token, keeping levels on
+    /// the wallet they had before where it can still fund them. A level no single wallet
+    /// can fund is split over the wallets with the most headroom.
+    #[default]
+    BalanceAware,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationSettings {
+    pub strategy: AllocationStrategy,
+    pub max_orders_per_wallet: u32,
+    /// Largest value of open orders one wallet may carry, in SOL; 0 disables the cap.
+    pub max_wallet_exposure_sol: Decimal,
+    /// Keep a ladder slot on its previous wallet when that wallet can still fund it.
+    pub sticky: bool,
+}
+
+impl Default for AllocationSettings {
+    fn default() -> Self {
+        Self {
+            strategy: AllocationStrategy::default(),
+            max_orders_per_wallet: 32,
+            max_wallet_exposure_sol: Decimal::ZERO,
+            sticky: true,
+        }
+    }
 }
 
 impl Default for OrderGridSettings {
@@ -27,10 +108,25 @@ impl Default for OrderGridSettings {
             rebalance_threshold_percent: Decimal::ONE,  // 1.0%
             large_order_threshold_sol: Decimal::from(50), // 50 SOL
             front_run_tick_size_sol: Decimal::new(1, 6), // 0.000001 SOL
+            fee_reserve_sol_per_wallet: default_fee_reserve_sol_per_wallet(),
+            spacing: GridSpacing::default(),
+            sizing: GridSizing::default(),
+            allocation: AllocationSettings::default(),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PivotStrategyKind {
+    Vwap,
+    /// Time-weighted average over the live ticks in `price_history`
+    Twap,
+    Ema,
+    /// VWAP plus fees, rent and Jito tips
+    FullCostBasis,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PivotVwapSettings {
     pub pivot_price: Decimal,
@@ -42,6 +138,16 @@ pub struct PivotVwapSettings {
     pub account_rent_sol: Decimal,
     pub jito_tip_sol: Decimal,
     pub fee_bps: Decimal,
+    #[serde(default = "default_pivot_strategy")]
+    pub strategy: PivotStrategyKind,
+    /// Number of observations the EMA strategy averages over.
+    #[serde(default = "default_ema_span")]
+    pub ema_span: u32,
+    /// Weight of fills where both maker and taker are swarm accounts: 0 leaves them out
+    /// of the pivot, 1 counts them like external trades. Every strategy applies it. The
+    /// default is 0, so out of the box the swarm trading with itself never moves the pivot.
+    #[serde(default)]
+    pub internal_trade_weight: Decimal,
 }
 
 impl Default for PivotVwapSettings {
@@ -56,6 +162,9 @@ impl Default for PivotVwapSettings {
             account_rent_sol: Decimal::new(23, 3),       // 0.023 SOL
             jito_tip_sol: Decimal::ZERO,
             fee_bps: Decimal::new(25, 0), // 25 bps = 0.25%
+            strategy: default_pivot_strategy(),
+            ema_span: default_ema_span(),
+            internal_trade_weight: Decimal::ZERO,
         }
     }
 }
@@ -64,6 +173,8 @@ impl Default for PivotVwapSettings {
 pub struct ChannelBoundsSettings {
     pub buy_percent: Decimal,
     pub sell_percent: Decimal,
+    #[serde(default)]
+    pub adaptive: AdaptiveChannelSettings,
 }
 
 impl Default for ChannelBoundsSettings {
@@ -71,6 +182,39 @@ impl Default for ChannelBoundsSettings {
         Self {
             buy_percent: Decimal::new(15, 2),
             sell_percent: Decimal::new(30, 2),
+            adaptive: AdaptiveChannelSettings::default(),
+        }
+    }
+}
+
+/// Scales the channel widths with realised volatility. Widths are fractions of the pivot,
+/// like `buy_percent` and `sell_percent`, which apply at the reference volatility.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveChannelSettings {
+    pub enabled: bool,
+    /// Window of price history the volatility is measured over.
+    pub lookback_secs: i64,
+    /// Realised volatility over the window at which the configured widths apply, in percent.
+    pub reference_volatility_percent: Decimal,
+    pub min_buy_percent: Decimal,
+    pub max_buy_percent: Decimal,
+    pub min_sell_percent: Decimal,
+    pub max_sell_percent: Decimal,
+    /// EMA span, in ticks, over which widths move towards their target.
+    pub smoothing_span: u32,
+}
+
+impl Default for AdaptiveChannelSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lookback_secs: 86_400,
+            reference_volatility_percent: Decimal::from(5),
+            min_buy_percent: Decimal::new(5, 2),
+            max_buy_percent: Decimal::new(30, 2),
+            min_sell_percent: Decimal::new(10, 2),
+            max_sell_percent: Decimal::new(60, 2),
+            smoothing_span: 20,
         }
     }
 }
@@ -99,6 +243,8 @@ pub struct RpcSettings {
     pub primary_http: String,
     pub secondary_http: Vec<String>,
     pub primary_ws: String,
+    #[serde(default = "default_rpc_request_timeout_ms")]
+    pub request_timeout_ms: u64,
 }
 
 impl Default for RpcSettings {
@@ -107,6 +253,7 @@ impl Default for RpcSettings {
             primary_http: "https://api.mainnet-beta.solana.com".to_string(),
             secondary_http: Vec::new(),
             primary_ws: "wss://api.mainnet-beta.solana.com".to_string(),
+            request_timeout_ms: default_rpc_request_timeout_ms(),
         }
     }
 }
@@ -310,6 +457,206 @@ impl Default for DatabaseSettings {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeBackfillSettings {
+    pub enabled: bool,
+    pub horizon_hours: u64,
+    pub max_signatures: usize,
+}
+
+impl Default for TradeBackfillSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            horizon_hours: 72,
+            max_signatures: 20_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceSourceToggle {
+    pub enabled: bool,
+    pub weight: Decimal,
+}
+
+impl PriceSourceToggle {
+    fn with_weight(weight: Decimal) -> Self {
+        Self {
+            enabled: true,
+            weight,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceSourcesSettings {
+    /// Quotes older than this are ignored.
+    pub max_age_secs: i64,
+    /// Quotes further than this from the median are rejected as outliers.
+    pub max_deviation_percent: Decimal,
+    pub min_sources: usize,
+    pub dexscreener: PriceSourceToggle,
+    pub jupiter: PriceSourceToggle,
+    pub openbook_mid: PriceSourceToggle,
+    pub pyth: PriceSourceToggle,
+    pub pyth_sol_usd_account: String,
+    /// Oracle prices whose confidence interval is wider than this are ignored.
+    #[serde(default = "default_pyth_max_confidence_percent")]
+    pub pyth_max_confidence_percent: Decimal,
+}
+
+impl Default for PriceSourcesSettings {
+    fn default() -> Self {
+        Self {
+            max_age_secs: 120,
+            max_deviation_percent: Decimal::from(5),
+            min_sources: 1,
+            dexscreener: PriceSourceToggle::with_weight(Decimal::ONE),
+            jupiter: PriceSourceToggle::with_weight(Decimal::ONE),
+            openbook_mid: PriceSourceToggle::with_weight(Decimal::ONE),
+            pyth: PriceSourceToggle::with_weight(Decimal::from(2)),
+            pyth_sol_usd_account: "H6ARHf6YXhGYeQfUzQNGk6rDNnLBQKrenN712K4AQJEG".to_string(),
+            pyth_max_confidence_percent: default_pyth_max_confidence_percent(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceGuardSettings {
+    pub enabled: bool,
+    /// Largest accepted move per `jump_interval_secs`, in percent.
+    pub max_jump_percent: Decimal,
+    pub jump_interval_secs: u64,
+    /// Quoting pauses once the last good price is this old.
+    pub pause_after_secs: u64,
+    /// Open orders are cancelled once the last good price is this old.
+    pub cancel_after_secs: u64,
+}
+
+impl Default for PriceGuardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_jump_percent: Decimal::from(10),
+            jump_interval_secs: 60,
+            pause_after_secs: 120,
+            cancel_after_secs: 600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FiatSettings {
+    pub reporting_currency: FiatCurrency,
+    /// How often USD/EUR is re-fetched and rates are written to `fiat_rates`.
+    pub refresh_interval_secs: i64,
+    pub frankfurter: PriceSourceToggle,
+    /// Manual USD/EUR rate added as a source, e.g. when running without internet access.
+    #[serde(default)]
+    pub fixed_usd_eur: Option<Decimal>,
+}
+
+impl Default for FiatSettings {
+    fn default() -> Self {
+        Self {
+            reporting_currency: FiatCurrency::Usd,
+            refresh_interval_secs: 300,
+            frankfurter: PriceSourceToggle::with_weight(Decimal::ONE),
+            fixed_usd_eur: None,
+        }
+    }
+}
+
+/// Avellaneda–Stoikov-style skew: the grid centre moves away from the side the swarm
+/// holds too much of and size shifts towards the side that sheds it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventorySkewSettings {
+    pub enabled: bool,
+    /// Share of the swarm's value meant to be held in the token, between 0 and 1.
+    pub target_token_share: Decimal,
+    /// Centre shift at full inventory deviation, in percent of the pivot.
+    pub risk_aversion_percent: Decimal,
+    /// Cap on the centre shift, in percent of the pivot.
+    pub max_centre_shift_percent: Decimal,
+    /// Size moved from the accumulating side to the shedding side at full deviation, in percent.
+    /// Grid sizes are limited by wallet balances, so neutral sizes keep this much headroom
+    /// below what the swarm can fund.
+    pub max_size_skew_percent: Decimal,
+}
+
+impl Default for InventorySkewSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_token_share: Decimal::new(5, 1),
+            risk_aversion_percent: Decimal::from(5),
+            max_centre_shift_percent: Decimal::from(3),
+            max_size_skew_percent: Decimal::from(50),
+        }
+    }
+}
+
+/// Base URLs of the external HTTP APIs, overridable so a local stub can stand in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalApiUrls {
+    pub dexscreener: String,
+    pub jupiter_price: String,
+    pub jupiter_swap: String,
+    pub binance: String,
+    pub rugcheck: String,
+    #[serde(default = "default_frankfurter_url")]
+    pub frankfurter: String,
+}
+
+impl Default for ExternalApiUrls {
+    fn default() -> Self {
+        Self {
+            dexscreener: "https://api.dexscreener.com".to_string(),
+            jupiter_price: "https://api.jup.ag/price/v2".to_string(),
+            jupiter_swap: "https://quote-api.jup.ag/v6".to_string(),
+            binance: "https://api.binance.com".to_string(),
+            rugcheck: "https://rugcheck.xyz/api/v1".to_string(),
+            frankfurter: default_frankfurter_url(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpSettings {
+    pub timeout_ms: u64,
+    /// Retries after the first attempt for idempotent requests.
+    pub max_retries: u32,
+    pub retry_base_delay_ms: u64,
+    /// How long successful GET responses are served from cache; 0 disables caching.
+    pub cache_ttl_ms: u64,
+    /// Requests per second allowed to a host without an entry in `host_rate_limits`.
+    pub default_rate_limit_per_sec: f64,
+    #[serde(default)]
+    pub host_rate_limits: HashMap<String, f64>,
+    /// Consecutive failures after which a host is short-circuited.
+    pub circuit_failure_threshold: u32,
+    pub circuit_open_secs: u64,
+    #[serde(default)]
+    pub base_urls: ExternalApiUrls,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 10_000,
+            max_retries: 2,
+            retry_base_delay_ms: 250,
+            cache_ttl_ms: 2_000,
+            default_rate_limit_per_sec: 5.0,
+            host_rate_limits: HashMap::new(),
+            circuit_failure_threshold: 5,
+            circuit_open_secs: 30,
+            base_urls: ExternalApiUrls::default(),
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct BotSettings {
     pub token_mint: String,
@@ -330,6 +677,18 @@ pub struct BotSettings {
     pub kill_switch: KillSwitchSettings,
     #[serde(default)]
     pub database: DatabaseSettings,
+    #[serde(default)]
+    pub trade_backfill: TradeBackfillSettings,
+    #[serde(default)]
+    pub price_sources: PriceSourcesSettings,
+    #[serde(default)]
+    pub price_guard: PriceGuardSettings,
+    #[serde(default)]
+    pub http: HttpSettings,
+    #[serde(default)]
+    pub fiat: FiatSettings,
+    #[serde(default)]
+    pub inventory_skew: InventorySkewSettings,
     pub dry_run: DryRunSettings,
     #[serde(default = "default_run_mode")]
     pub run_mode: String,
@@ -356,6 +715,12 @@ impl std::fmt::Debug for BotSettings {
             .field("target_control", &self.target_control)
             .field("kill_switch", &self.kill_switch)
             .field("database", &self.database)
+            .field("trade_backfill", &self.trade_backfill)
+            .field("price_sources", &self.price_sources)
+            .field("price_guard", &self.price_guard)
+            .field("http", &self.http)
+            .field("fiat", &self.fiat)
+            .field("inventory_skew", &self.inventory_skew)
             .field("dry_run", &self.dry_run)
             .field("run_mode", &self.run_mode)
             .field(
@@ -370,6 +735,30 @@ impl std::fmt::Debug for BotSettings {
     }
 }
 
+fn default_pivot_strategy() -> PivotStrategyKind {
+    PivotStrategyKind::Vwap
+}
+
+fn default_ema_span() -> u32 {
+    20
+}
+
+fn default_fee_reserve_sol_per_wallet() -> Decimal {
+    Decimal::new(5, 2)
+}
+
+fn default_pyth_max_confidence_percent() -> Decimal {
+    Decimal::from(2)
+}
+
+fn default_rpc_request_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_frankfurter_url() -> String {
+    "https://api.frankfurter.app".to_string()
+}
+
 fn default_run_mode() -> String {
     "paper".to_string()
 }
@@ -401,6 +790,12 @@ impl Default for BotSettings {
             sol_usdc_market_id: "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2".to_string(),
             kill_switch: KillSwitchSettings::default(),
             database: DatabaseSettings::default(),
+            trade_backfill: TradeBackfillSettings::default(),
+            price_sources: PriceSourcesSettings::default(),
+            price_guard: PriceGuardSettings::default(),
+            http: HttpSettings::default(),
+            fiat: FiatSettings::default(),
+            inventory_skew: InventorySkewSettings::default(),
             dry_run: DryRunSettings::default(),
             run_mode: default_run_mode(),
             trading_tick_interval_seconds: default_trading_tick_interval(),
@@ -669,6 +1064,20 @@ sol_usdc_market_id: "SOL_USDC"
         assert!(!settings.dry_run.enabled);
     }
 
+    #[test]
+    fn test_base_urls_without_frankfurter() {
+        let yaml = r#"
+dexscreener: "http://dex.local"
+jupiter_price: "http://jup.local/price"
+jupiter_swap: "http://jup.local/swap"
+binance: "http://binance.local"
+rugcheck: "http://rugcheck.local"
+"#;
+        let urls: ExternalApiUrls = serde_yml::from_str(yaml).unwrap();
+        assert_eq!(urls.dexscreener, "http://dex.local");
+        assert_eq!(urls.frankfurter, "https://api.frankfurter.app");
+    }
+
     #[test]
     fn test_settings_masking() {
         let mut settings = BotSettings::default();
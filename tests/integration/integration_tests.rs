@@ -23,6 +23,7 @@ async fn test_full_trading_loop_integration() {
             Ok(MarketUpdate {
                 price: dec!(150.0),
                 volume_24h: dec!(1000000),
+                trade_count_24h: 0,
                 timestamp: 123456789,
             })
         });
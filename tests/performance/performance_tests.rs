@@ -33,13 +33,17 @@ async fn bench_pivot_engine_compute() {
                     OrderSide::Sell
                 },
                 wallet: "bench".to_string(),
+                slot: None,
+                block_time: None,
+                maker: None,
+                taker: None,
             })
             .await;
     }
 
     // 2. Measure compute_pivot
     let start = Instant::now();
-    let pivot = engine.compute_pivot(&[], &[], None, 0).await;
+    let pivot = engine.compute_pivot(&[], &[], &[], None, 0).await;
     let duration = start.elapsed();
 
     println!("PivotEngine Compute Performance:");